@@ -0,0 +1,207 @@
+//! Deterministic, time-driven light animation.
+//!
+//! A [`LightAnimation`] is a pure function of the engine clock: sampling it at
+//! the same millisecond always yields the same [`AnimationFrame`], on every
+//! platform. [`crate::engine::LightingEngine::tick`] advances that clock and
+//! re-renders every animated light, so two clients that apply the same seeds
+//! and the same tick sequence produce bit-identical canvases.
+//!
+//! Everything is integer arithmetic. Randomness ([`LightAnimation::Flicker`])
+//! comes from a counter-based hash of `(seed, step)` rather than a stateful
+//! generator, so the output depends only on the clock value — not on how the
+//! elapsed time was split into ticks.
+
+/// How an animation modulates its light at one instant. Applied on top of the
+/// light's configured values; [`AnimationFrame::STILL`] changes nothing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AnimationFrame {
+    /// Intensity multiplier in 8.8 fixed point (`256` = unchanged).
+    pub intensity: u16,
+    /// Fraction of the configured radius that is lit, `0`–`255`.
+    pub radius: u8,
+    /// Degrees added to a spotlight's heading.
+    pub rotation: u16,
+}
+
+impl AnimationFrame {
+    /// The identity frame.
+    pub const STILL: AnimationFrame = AnimationFrame {
+        intensity: 256,
+        radius: 255,
+        rotation: 0,
+    };
+}
+
+/// A periodic or random modulation of a light's intensity, reach or heading.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LightAnimation {
+    /// Fire-like random intensity between `min` and `max` (8.8 multipliers).
+    /// A new target is drawn every `interval_ms` and approached linearly, so
+    /// the light wavers rather than jumps.
+    Flicker {
+        seed: u32,
+        min: u16,
+        max: u16,
+        interval_ms: u32,
+    },
+    /// Smooth intensity swing between `min` and `max` (8.8 multipliers) with
+    /// period `period_ms`, starting at `min`.
+    Pulse { min: u16, max: u16, period_ms: u32 },
+    /// Full intensity for `on_ms`, then dark for `off_ms`, repeating.
+    Strobe { on_ms: u32, off_ms: u32 },
+    /// Lit radius swings smoothly between `min` and the full radius
+    /// (`min` as a fraction `0`–`255`) with period `period_ms`. The canvas
+    /// keeps its full-radius size, so its pointer stays valid.
+    RadiusOscillation { min: u8, period_ms: u32 },
+    /// Rotating beacon: a spotlight's heading advances by
+    /// `degrees_per_second` (negative turns the other way). No effect on
+    /// lights without a cone.
+    Beacon { degrees_per_second: i16 },
+}
+
+impl LightAnimation {
+    /// Evaluate the animation at engine time `time_ms`.
+    pub fn sample(&self, time_ms: u64) -> AnimationFrame {
+        let mut frame = AnimationFrame::STILL;
+        match *self {
+            LightAnimation::Flicker {
+                seed,
+                min,
+                max,
+                interval_ms,
+            } => {
+                let interval = interval_ms.max(1) as u64;
+                let step = time_ms / interval;
+                let frac = (time_ms % interval) as u32;
+                let from = pick(seed, step, min, max) as u64;
+                let to = pick(seed, step + 1, min, max) as u64;
+                let lerp = (from * (interval - frac as u64) + to * frac as u64) / interval;
+                frame.intensity = lerp as u16;
+            }
+            LightAnimation::Pulse {
+                min,
+                max,
+                period_ms,
+            } => {
+                let wave = smooth_wave(time_ms, period_ms) as u32;
+                frame.intensity =
+                    (min as u32 + (max.saturating_sub(min) as u32) * wave / 255) as u16;
+            }
+            LightAnimation::Strobe { on_ms, off_ms } => {
+                let period = (on_ms as u64 + off_ms as u64).max(1);
+                if time_ms % period >= on_ms as u64 {
+                    frame.intensity = 0;
+                }
+            }
+            LightAnimation::RadiusOscillation { min, period_ms } => {
+                let wave = smooth_wave(time_ms, period_ms) as u32;
+                frame.radius = (min as u32 + (255 - min as u32) * wave / 255) as u8;
+            }
+            LightAnimation::Beacon { degrees_per_second } => {
+                let degrees = time_ms as i64 * degrees_per_second as i64 / 1000;
+                frame.rotation = degrees.rem_euclid(360) as u16;
+            }
+        }
+        frame
+    }
+}
+
+/// Smoothstep-eased triangle wave over `period_ms`: `0` at the start of each
+/// period, `255` half-way, cosine-like in between.
+fn smooth_wave(time_ms: u64, period_ms: u32) -> u8 {
+    let period = period_ms.max(1) as u64;
+    let phase = time_ms % period;
+    let tri = if phase * 2 < period {
+        phase * 510 / period
+    } else {
+        (period - phase) * 510 / period
+    }
+    .min(255) as u32;
+    (tri * tri * (3 * 255 - 2 * tri) / (255 * 255)) as u8
+}
+
+/// Deterministic value in `min..=max` for flicker step `step`.
+fn pick(seed: u32, step: u64, min: u16, max: u16) -> u16 {
+    let (lo, hi) = (min.min(max) as u32, min.max(max) as u32);
+    lo as u16 + (hash(seed, step) % (hi - lo + 1)) as u16
+}
+
+/// Counter-based integer hash (a murmur3-style finaliser over the mixed seed
+/// and step).
+fn hash(seed: u32, step: u64) -> u32 {
+    let mut x = seed ^ (step as u32).wrapping_mul(0x9E37_79B9) ^ ((step >> 32) as u32);
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7FEB_352D);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846C_A68B);
+    x ^= x >> 16;
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flicker_depends_only_on_clock_and_seed() {
+        let a = LightAnimation::Flicker {
+            seed: 7,
+            min: 128,
+            max: 320,
+            interval_ms: 90,
+        };
+        let b = LightAnimation::Flicker {
+            seed: 8,
+            min: 128,
+            max: 320,
+            interval_ms: 90,
+        };
+        let samples = |anim: LightAnimation| {
+            (0..2000)
+                .step_by(37)
+                .map(|t| anim.sample(t))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(samples(a), samples(a));
+        assert_ne!(samples(a), samples(b));
+        assert!(samples(a)
+            .iter()
+            .all(|f| (128..=320).contains(&f.intensity)));
+    }
+
+    #[test]
+    fn pulse_and_radius_swing_between_bounds() {
+        let pulse = LightAnimation::Pulse {
+            min: 64,
+            max: 256,
+            period_ms: 1000,
+        };
+        assert_eq!(pulse.sample(0).intensity, 64);
+        assert_eq!(pulse.sample(500).intensity, 256);
+        assert_eq!(pulse.sample(1000).intensity, 64);
+
+        let breathe = LightAnimation::RadiusOscillation {
+            min: 100,
+            period_ms: 400,
+        };
+        assert_eq!(breathe.sample(0).radius, 100);
+        assert_eq!(breathe.sample(200).radius, 255);
+    }
+
+    #[test]
+    fn strobe_and_beacon() {
+        let strobe = LightAnimation::Strobe {
+            on_ms: 50,
+            off_ms: 150,
+        };
+        assert_eq!(strobe.sample(10).intensity, 256);
+        assert_eq!(strobe.sample(60).intensity, 0);
+        assert_eq!(strobe.sample(210).intensity, 256);
+
+        let beacon = LightAnimation::Beacon {
+            degrees_per_second: -90,
+        };
+        assert_eq!(beacon.sample(1000).rotation, 270);
+        assert_eq!(beacon.sample(4000).rotation, 0);
+    }
+}
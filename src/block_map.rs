@@ -1,15 +1,19 @@
 //! Per-cell edge-blocking flags derived from the tile map.
 //!
 //! Each cell carries `n/e/s/w_blocked` flags marking which of its four edges
-//! sit on a Wall (a boundary between two Tiles of different types). The
-//! derivation is a pure function of the tile map; [`LightingEngine`] holds the
-//! current tile array and the derived cell array as owned fields.
+//! sit on a Wall (a boundary between two Tiles of different types, or a thin
+//! wall authored on a tile or cell edge or along a tile's [`Diagonal`]). The
+//! derivation is a pure function of the tile map and the thin-wall overlay;
+//! [`LightingEngine`] holds the current tile array and the derived cell
+//! array as owned fields.
 //!
 //! Free functions in this module are back-compat shims that operate on
 //! [`crate::engine::DEFAULT_ENGINE`].
 //!
 //! [`LightingEngine`]: crate::engine::LightingEngine
 
+use std::collections::HashSet;
+
 use crate::engine::DEFAULT_ENGINE;
 
 /// Per-cell edge-blocking flags.
@@ -102,6 +106,79 @@ pub fn compute_cell_details_for_tile(
     }
 }
 
+/// A diagonal wall across one tile, named by the two corners it joins. It
+/// splits the tile's cells into two half-triangles along a staircase of cell
+/// edges; cells on the diagonal itself belong to the northern half.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Diagonal {
+    /// From the north-east corner to the south-west one.
+    NeSw,
+    /// From the north-west corner to the south-east one.
+    NwSe,
+}
+
+impl Diagonal {
+    /// Decode the WASM encoding: `1` = NE-SW, `2` = NW-SE, anything else =
+    /// no diagonal.
+    pub fn from_u8(kind: u8) -> Option<Self> {
+        match kind {
+            1 => Some(Diagonal::NeSw),
+            2 => Some(Diagonal::NwSe),
+            _ => None,
+        }
+    }
+
+    /// The cell edges this diagonal cuts in a tile `cells_per_tile` cells
+    /// wide, as pairs of 4-adjacent tile-local `(x, y)` cells: each cell on
+    /// the diagonal paired with its neighbours in the southern half.
+    pub fn cut_edges(self, cells_per_tile: usize) -> Vec<((usize, usize), (usize, usize))> {
+        let mut cuts = Vec::with_capacity(2 * cells_per_tile);
+        for k in 0..cells_per_tile {
+            match self {
+                Diagonal::NwSe => {
+                    if k > 0 {
+                        cuts.push(((k, k), (k - 1, k)));
+                    }
+                    if k + 1 < cells_per_tile {
+                        cuts.push(((k, k), (k, k + 1)));
+                    }
+                }
+                Diagonal::NeSw => {
+                    let x = cells_per_tile - 1 - k;
+                    if x + 1 < cells_per_tile {
+                        cuts.push(((x, k), (x + 1, k)));
+                    }
+                    if k + 1 < cells_per_tile {
+                        cuts.push(((x, k), (x, k + 1)));
+                    }
+                }
+            }
+        }
+        cuts
+    }
+}
+
+/// Raise the edge flags of both cells of every thin-wall cell edge in
+/// `edges` (canonical `(lo, hi)` cell-index pairs of 4-adjacent cells).
+pub fn mark_wall_edges(
+    cells: &mut [CellDetails],
+    edges: &HashSet<(usize, usize)>,
+    cells_per_row: usize,
+) {
+    for &(lo, hi) in edges {
+        if hi >= cells.len() {
+            continue;
+        }
+        if hi == lo + 1 && hi % cells_per_row != 0 {
+            cells[lo].e_blocked = true;
+            cells[hi].w_blocked = true;
+        } else if hi == lo + cells_per_row {
+            cells[lo].s_blocked = true;
+            cells[hi].n_blocked = true;
+        }
+    }
+}
+
 // ------------------------------- shims ----------------------------------
 
 /// WASM/back-compat shim. Returns a pointer to the default engine's tile array.
@@ -163,6 +240,35 @@ mod tests {
         set_tile(TILES_PER_ROW as u32 - 1, TILES_PER_ROW as u32 - 1, 2);
     }
 
+    #[test]
+    fn diagonal_cuts_separate_the_two_halves() {
+        for diagonal in [Diagonal::NeSw, Diagonal::NwSe] {
+            let cuts = diagonal.cut_edges(4);
+            assert_eq!(cuts.len(), 6);
+            let north = |(x, y): (usize, usize)| match diagonal {
+                Diagonal::NwSe => x >= y,
+                Diagonal::NeSw => x + y <= 3,
+            };
+            // Every 4-adjacent pair straddling the halves is cut, and only those.
+            for y in 0..4 {
+                for x in 0..4 {
+                    for (nx, ny) in [(x + 1, y), (x, y + 1)] {
+                        if nx == 4 || ny == 4 {
+                            continue;
+                        }
+                        let straddles = north((x, y)) != north((nx, ny));
+                        let cut = cuts.iter().any(|&(a, b)| {
+                            (a, b) == ((x, y), (nx, ny)) || (b, a) == ((x, y), (nx, ny))
+                        });
+                        assert_eq!(cut, straddles, "{diagonal:?} ({x},{y})-({nx},{ny})");
+                    }
+                }
+            }
+        }
+        assert_eq!(Diagonal::from_u8(2), Some(Diagonal::NwSe));
+        assert_eq!(Diagonal::from_u8(0), None);
+    }
+
     #[test]
     fn test_get_functions_return_valid_pointers() {
         let tiles_ptr = get_tiles();
@@ -4,15 +4,30 @@
 //! `is_blocked` query runs two phases on the same [`HybridCollisionMap`]:
 //!
 //! 1. **Broad phase** — [`crate::map_grid::UnionFind`] rejects rays whose
-//!    endpoints lie in different rooms (i.e. a Wall lies between them).
+//!    endpoints lie in different rooms (i.e. a Wall lies between them), and
+//!    rays that cross a thin wall authored on a cell edge.
 //! 2. **Narrow phase** — bitmap walk through the cell-level [`PixelCollisionMap`]
 //!    catches rays that hit an Object.
 //!
+//! Moving occluders live in a second, transient bitmap that callers replace
+//! wholesale each frame ([`HybridCollisionMap::set_dynamic_occluders`]); the
+//! narrow phase checks it alongside the static one.
+//!
+//! Every primitive blocks some set of [`Channels`] — light, sight, movement.
+//! The narrow phase keeps one bitmap per [`Channel`] and the broad phase one
+//! set of passable Wall edges per channel, so
+//! [`HybridCollisionMap::is_blocked_for`] answers for one channel at a time.
+//! [`CollisionDetector::is_blocked`] is the [`Channel::Light`] query.
+//!
+//! Translucent Objects (glass, water, smoke) don't block at all; they carry a
+//! [`PixelMaterial`] instead, and [`HybridCollisionMap::filter_along`] folds
+//! every material a ray crosses into one RGB [`Filter`].
+//!
 //! Free functions in this module are back-compat shims operating on
 //! [`crate::engine::DEFAULT_ENGINE`]; new Rust callers should construct a
 //! [`crate::engine::LightingEngine`] and call methods on it.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, RwLock};
 
 use crate::engine::DEFAULT_ENGINE;
@@ -146,17 +161,154 @@ impl CollisionDetector for PixelCollisionMap {
     }
 }
 
+/// One of the things an occluder can block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Channel {
+    /// Rays traced by lights, emissive glows and ambient occlusion.
+    Light,
+    /// Field-of-view rays (`compute_fov`) and tile line-of-sight (`cast_ray`).
+    Sight,
+    /// Pathfinding (`path`, `neighbours`).
+    Movement,
+}
+
+impl Channel {
+    pub const ALL: [Channel; 3] = [Channel::Light, Channel::Sight, Channel::Movement];
+
+    /// Decode the WASM-facing numeric form (`0` light, `1` sight,
+    /// `2` movement). Unknown values yield `None`.
+    pub fn from_u8(channel: u8) -> Option<Self> {
+        Channel::ALL.get(channel as usize).copied()
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// A set of [`Channel`]s, as a bitmask: bit `0` light, bit `1` sight,
+/// bit `2` movement.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Channels(u8);
+
+impl Channels {
+    pub const NONE: Channels = Channels(0);
+    pub const LIGHT: Channels = Channels(1);
+    pub const SIGHT: Channels = Channels(2);
+    pub const MOVEMENT: Channels = Channels(4);
+    pub const ALL: Channels = Channels(7);
+
+    /// Build a set from its bitmask; unknown bits are dropped.
+    pub fn from_bits(bits: u8) -> Self {
+        Channels(bits & Self::ALL.0)
+    }
+
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// `self | other`, usable in constants.
+    pub const fn union(self, other: Channels) -> Channels {
+        Channels(self.0 | other.0)
+    }
+
+    pub fn contains(self, channel: Channel) -> bool {
+        self.0 & (1 << channel.index()) != 0
+    }
+
+    /// The channels in the set, in [`Channel::ALL`] order.
+    pub fn iter(self) -> impl Iterator<Item = Channel> {
+        Channel::ALL.into_iter().filter(move |&c| self.contains(c))
+    }
+}
+
+impl std::ops::BitOr for Channels {
+    type Output = Channels;
+
+    fn bitor(self, other: Channels) -> Channels {
+        self.union(other)
+    }
+}
+
+/// Per-channel light transmission (`255` = passes unchanged, `0` = stopped)
+/// accumulated along a ray.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Filter(pub u8, pub u8, pub u8);
+
+impl Filter {
+    /// Passes all light unchanged.
+    pub const CLEAR: Filter = Filter(255, 255, 255);
+
+    /// `true` if no channel gets through; such rays are treated as blocked.
+    pub fn is_opaque(self) -> bool {
+        self.0 == 0 && self.1 == 0 && self.2 == 0
+    }
+
+    /// Chain two filters: light passing through both.
+    pub fn then(self, other: Filter) -> Filter {
+        let (r, g, b) = other.apply((self.0, self.1, self.2));
+        Filter(r, g, b)
+    }
+
+    /// Apply the filter to an RGB triple.
+    pub fn apply(self, (r, g, b): (u8, u8, u8)) -> (u8, u8, u8) {
+        let mul = |a: u8, b: u8| (a as u16 * b as u16 / 255) as u8;
+        (mul(r, self.0), mul(g, self.1), mul(b, self.2))
+    }
+}
+
+/// Optical material of a translucent Object cell.
+///
+/// A material cell does not block rays; it attenuates them by
+/// `transmittance` and colours them by `tint`. A fully opaque material
+/// (`transmittance == 0`) behaves like a blocked cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PixelMaterial {
+    /// Fraction of light passing through, `0`–`255`.
+    pub transmittance: u8,
+    /// Colour the passing light is multiplied by.
+    pub tint: (u8, u8, u8),
+}
+
+impl PixelMaterial {
+    /// The [`Filter`] a single crossing of this cell applies.
+    pub fn filter(&self) -> Filter {
+        let t = self.transmittance;
+        Filter(t, t, t).then(Filter(self.tint.0, self.tint.1, self.tint.2))
+    }
+}
+
 /// Combined room-graph (broad phase) + cell-bitmap (narrow phase) detector.
 pub struct HybridCollisionMap {
     union_find: Arc<RwLock<UnionFind>>,
+    /// Object cells blocking [`Channel::Light`].
     pixel_map: PixelCollisionMap,
+    /// Object cells blocking [`Channel::Sight`].
+    sight_map: PixelCollisionMap,
+    /// Object cells blocking [`Channel::Movement`].
+    movement_map: PixelCollisionMap,
     map_size: usize,
     /// Canonical `(lo, hi)` cell-index pairs where the broad-phase walk is
     /// allowed to step between two cells that the union-find considers to be
-    /// in different rooms. Populated by the engine from its `door_edges`
-    /// overlay — a door dissolves the wall only along its own cell-edges, not
-    /// across the entire room boundary (which is what a UF union would do).
+    /// in different rooms. Populated by the engine from the doors in its
+    /// `door_edges` overlay that pass every channel — a door dissolves the
+    /// wall only along its own cell-edges, not across the entire room
+    /// boundary (which is what a UF union would do).
     door_cell_edges: HashSet<(usize, usize)>,
+    /// Like `door_cell_edges`, but per [`Channel`]: Wall edges that let that
+    /// one channel through (a glass wall passes light, not movement).
+    channel_cell_edges: [HashSet<(usize, usize)>; 3],
+    /// Thin walls: canonical cell-index pairs the broad-phase walk may never
+    /// step across, whatever the rooms, doors or channels say.
+    wall_cell_edges: HashSet<(usize, usize)>,
+    /// Translucent Object cells, keyed by cell coordinate.
+    materials: HashMap<(u16, u16), PixelMaterial>,
+    /// Transient occluders (moving characters), replaced wholesale every
+    /// frame and consulted alongside `pixel_map`.
+    dynamic_map: PixelCollisionMap,
+    /// The cells currently set in `dynamic_map`, so the next frame can unset
+    /// exactly those.
+    dynamic_cells: Vec<(u16, u16)>,
 }
 
 impl HybridCollisionMap {
@@ -165,14 +317,33 @@ impl HybridCollisionMap {
         Self {
             union_find: Arc::new(RwLock::new(uf)),
             pixel_map: PixelCollisionMap::new(map_size as u16, map_size as u16),
+            sight_map: PixelCollisionMap::new(map_size as u16, map_size as u16),
+            movement_map: PixelCollisionMap::new(map_size as u16, map_size as u16),
             map_size,
             door_cell_edges: HashSet::new(),
+            channel_cell_edges: Default::default(),
+            wall_cell_edges: HashSet::new(),
+            materials: HashMap::new(),
+            dynamic_map: PixelCollisionMap::new(map_size as u16, map_size as u16),
+            dynamic_cells: Vec::new(),
         }
     }
 
     pub fn update_map_data(&mut self, map_data: Vec<i32>, map_size: usize) {
+        self.update_map_data_with_cuts(map_data, map_size, &HashSet::new());
+    }
+
+    /// Like [`Self::update_map_data`], but the canonical `(lo, hi)` cell
+    /// pairs in `cuts` never join a Room, so a Diagonal can split one tile's
+    /// cells between two.
+    pub fn update_map_data_with_cuts(
+        &mut self,
+        map_data: Vec<i32>,
+        map_size: usize,
+        cuts: &HashSet<(usize, usize)>,
+    ) {
         if let Ok(mut uf) = self.union_find.write() {
-            *uf = UnionFind::new(map_data, map_size);
+            *uf = UnionFind::new_with_cuts(map_data, map_size, cuts);
         }
         self.map_size = map_size;
     }
@@ -188,13 +359,201 @@ impl HybridCollisionMap {
         self.door_cell_edges = edges;
     }
 
+    /// Replace the Wall cell-edges that let `channel` through, in the same
+    /// canonical form as [`Self::set_door_cell_edges`].
+    pub fn set_channel_cell_edges(&mut self, channel: Channel, edges: HashSet<(usize, usize)>) {
+        self.channel_cell_edges[channel.index()] = edges;
+    }
+
+    /// Replace the set of thin-wall cell-edges, in the same canonical form as
+    /// [`Self::set_door_cell_edges`]. They block every channel.
+    pub fn set_wall_cell_edges(&mut self, edges: HashSet<(usize, usize)>) {
+        self.wall_cell_edges = edges;
+    }
+
+    /// Add or remove one thin-wall cell edge, in the canonical form of
+    /// [`Self::set_wall_cell_edges`].
+    pub fn set_wall_cell_edge(&mut self, pair: (usize, usize), wall: bool) {
+        if wall {
+            self.wall_cell_edges.insert(pair);
+        } else {
+            self.wall_cell_edges.remove(&pair);
+        }
+    }
+
+    /// The [`Channel::Light`] Object bitmap.
     pub fn pixel_map(&self) -> &PixelCollisionMap {
         &self.pixel_map
     }
+
+    /// The Object bitmap of one channel.
+    pub fn channel_map(&self, channel: Channel) -> &PixelCollisionMap {
+        match channel {
+            Channel::Light => &self.pixel_map,
+            Channel::Sight => &self.sight_map,
+            Channel::Movement => &self.movement_map,
+        }
+    }
+
+    pub fn channel_map_mut(&mut self, channel: Channel) -> &mut PixelCollisionMap {
+        match channel {
+            Channel::Light => &mut self.pixel_map,
+            Channel::Sight => &mut self.sight_map,
+            Channel::Movement => &mut self.movement_map,
+        }
+    }
+
+    /// The channels cell `(x, y)` blocks as a static Object cell.
+    pub fn cell_channels(&self, x: u16, y: u16) -> Channels {
+        Channel::ALL
+            .into_iter()
+            .filter(|&c| self.channel_map(c).get_pixel(x, y))
+            .fold(Channels::NONE, |set, c| set | Channels(1 << c.index()))
+    }
+
+    /// Side of the square cell map, in cells.
+    pub fn map_size(&self) -> usize {
+        self.map_size
+    }
+
+    /// The Room of cell `(x, y)` in the cell-resolution room graph, as an
+    /// opaque root index; `None` off the map.
+    pub fn cell_room(&self, x: u16, y: u16) -> Option<usize> {
+        let size = self.map_size;
+        if x as usize >= size || y as usize >= size {
+            return None;
+        }
+        let mut uf = self.union_find.write().ok()?;
+        Some(uf.find(y as usize * size + x as usize))
+    }
+
+    /// Replace the transient occluder layer with exactly `cells`. Unlike the
+    /// static bitmap behind [`Self::pixel_map`], this layer is meant to be
+    /// rewritten every frame; both block rays.
+    pub fn set_dynamic_occluders(&mut self, cells: &[(u16, u16)]) {
+        for &(x, y) in &self.dynamic_cells {
+            self.dynamic_map.set_pixel(x, y, false);
+        }
+        for &(x, y) in cells {
+            self.dynamic_map.set_pixel(x, y, true);
+        }
+        self.dynamic_cells = cells.to_vec();
+    }
+
+    /// The cells of the transient occluder layer, as last set.
+    pub fn dynamic_occluders(&self) -> &[(u16, u16)] {
+        &self.dynamic_cells
+    }
+
+    /// Whether cell `(x, y)` blocks light, as a static or a transient
+    /// occluder.
+    pub fn is_occupied(&self, x: u16, y: u16) -> bool {
+        self.pixel_map.get_pixel(x, y) || self.dynamic_map.get_pixel(x, y)
+    }
+
+    /// Give cell `(x, y)` a translucent material, or remove it with `None`.
+    /// Materials are independent of the blocking bitmap: a blocked cell still
+    /// blocks regardless of its material.
+    pub fn set_pixel_material(&mut self, x: u16, y: u16, material: Option<PixelMaterial>) {
+        match material {
+            Some(m) => {
+                self.materials.insert((x, y), m);
+            }
+            None => {
+                self.materials.remove(&(x, y));
+            }
+        }
+    }
+
+    pub fn pixel_material(&self, x: u16, y: u16) -> Option<&PixelMaterial> {
+        self.materials.get(&(x, y))
+    }
+
+    /// Drop every translucent material.
+    pub fn clear_materials(&mut self) {
+        self.materials.clear();
+    }
+
+    /// Clear the Object layers — every channel's bitmap, the materials and
+    /// the transient occluders — leaving rooms, doors and walls as they are.
+    pub fn clear_objects(&mut self) {
+        for channel in Channel::ALL {
+            self.channel_map_mut(channel).clear();
+        }
+        self.materials.clear();
+        self.set_dynamic_occluders(&[]);
+    }
+
+    /// Accumulated [`Filter`] of every material cell on the Bresenham line
+    /// `(x0,y0)→(x1,y1)`, excluding the origin cell and including the target.
+    /// Does not consider blocking; callers check [`CollisionDetector::is_blocked`]
+    /// first.
+    pub fn filter_along(&self, x0: i16, y0: i16, x1: i16, y1: i16) -> Filter {
+        if self.materials.is_empty() {
+            return Filter::CLEAR;
+        }
+        let dx = (x1 - x0).abs();
+        let dy = (y1 - y0).abs();
+        let sx = if x0 < x1 { 1 } else { -1 };
+        let sy = if y0 < y1 { 1 } else { -1 };
+        let mut err = dx - dy;
+        let (mut x, mut y) = (x0, y0);
+        let mut filter = Filter::CLEAR;
+
+        while x != x1 || y != y1 {
+            let e2 = 2 * err;
+            if e2 > -dy {
+                err -= dy;
+                x += sx;
+            }
+            if e2 < dx {
+                err += dx;
+                y += sy;
+            }
+            if x < 0 || y < 0 {
+                continue;
+            }
+            if let Some(m) = self.materials.get(&(x as u16, y as u16)) {
+                filter = filter.then(m.filter());
+                if filter.is_opaque() {
+                    break;
+                }
+            }
+        }
+        filter
+    }
 }
 
 impl CollisionDetector for HybridCollisionMap {
     fn is_blocked(&self, x0: i16, y0: i16, x1: i16, y1: i16) -> bool {
+        self.is_blocked_for(Channel::Light, x0, y0, x1, y1)
+    }
+
+    fn clear(&mut self) {
+        if let Ok(mut uf) = self.union_find.write() {
+            *uf = UnionFind::new(vec![0; self.map_size * self.map_size], self.map_size);
+        }
+        self.clear_objects();
+        self.door_cell_edges.clear();
+        self.channel_cell_edges = Default::default();
+        self.wall_cell_edges.clear();
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+impl HybridCollisionMap {
+    /// Whether the segment `(x0,y0)→(x1,y1)` is blocked for `channel`: by a
+    /// thin wall, by a Wall that neither an open door nor the channel's own
+    /// passable edges let it through, by an Object cell blocking that
+    /// channel, or by a transient occluder (which blocks every channel).
+    pub fn is_blocked_for(&self, channel: Channel, x0: i16, y0: i16, x1: i16, y1: i16) -> bool {
         let size = self.map_size as i32;
         let in_bounds = |x: i32, y: i32| x >= 0 && y >= 0 && x < size && y < size;
         let (x0i, y0i, x1i, y1i) = (x0 as i32, y0 as i32, x1 as i32, y1 as i32);
@@ -229,38 +588,27 @@ impl CollisionDetector for HybridCollisionMap {
                     }
                     let next_idx = (py * size + px) as usize;
                     let next_room = uf.find(next_idx);
-                    if next_room != current_room {
-                        let pair = if prev_idx <= next_idx {
-                            (prev_idx, next_idx)
-                        } else {
-                            (next_idx, prev_idx)
-                        };
-                        if !self.door_cell_edges.contains(&pair) {
-                            return true;
-                        }
+                    let pair = if prev_idx <= next_idx {
+                        (prev_idx, next_idx)
+                    } else {
+                        (next_idx, prev_idx)
+                    };
+                    if !self.wall_cell_edges.is_empty() && self.wall_cell_edges.contains(&pair) {
+                        return true;
+                    }
+                    if next_room != current_room
+                        && !self.door_cell_edges.contains(&pair)
+                        && !self.channel_cell_edges[channel.index()].contains(&pair)
+                    {
+                        return true;
                     }
                     current_idx = next_idx;
                     current_room = next_room;
                 }
             }
         }
-        self.pixel_map.is_blocked(x0, y0, x1, y1)
-    }
-
-    fn clear(&mut self) {
-        if let Ok(mut uf) = self.union_find.write() {
-            *uf = UnionFind::new(vec![0; self.map_size * self.map_size], self.map_size);
-        }
-        self.pixel_map.clear();
-        self.door_cell_edges.clear();
-    }
-
-    fn as_any(&self) -> &dyn std::any::Any {
-        self
-    }
-
-    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
-        self
+        self.channel_map(channel).is_blocked(x0, y0, x1, y1)
+            || (!self.dynamic_cells.is_empty() && self.dynamic_map.is_blocked(x0, y0, x1, y1))
     }
 }
 
@@ -349,6 +697,31 @@ mod tests {
         assert!(!map.get_pixel(4, 4));
     }
 
+    #[test]
+    fn materials_filter_rays_without_blocking() {
+        let mut map = HybridCollisionMap::new(vec![0; 100], 10);
+        let red_glass = PixelMaterial {
+            transmittance: 255,
+            tint: (255, 0, 0),
+        };
+        let smoke = PixelMaterial {
+            transmittance: 128,
+            tint: (255, 255, 255),
+        };
+        map.set_pixel_material(3, 5, Some(red_glass));
+        map.set_pixel_material(6, 5, Some(smoke));
+
+        assert!(!map.is_blocked(0, 5, 9, 5));
+        assert_eq!(map.filter_along(0, 5, 2, 5), Filter::CLEAR);
+        assert_eq!(map.filter_along(0, 5, 3, 5), Filter(255, 0, 0));
+        assert_eq!(map.filter_along(0, 5, 9, 5), Filter(128, 0, 0));
+        // The origin cell's own material is not applied.
+        assert_eq!(map.filter_along(3, 5, 4, 5), Filter::CLEAR);
+
+        map.set_pixel_material(3, 5, None);
+        assert_eq!(map.filter_along(0, 5, 9, 5), Filter(128, 128, 128));
+    }
+
     #[test]
     fn test_unified_collision_system() {
         clear_collisions();
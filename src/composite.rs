@@ -0,0 +1,333 @@
+//! Engine-side compositing of every registered [`Light`] and [`Ambient`] into
+//! one full-map canvas.
+//!
+//! Each Light renders into its own bounding rectangle and each Ambient
+//! into its own full-map canvas. A renderer that wants the final lit scene
+//! would otherwise have to blit and blend every one of those buffers itself;
+//! [`Composite`] does that work once, inside the engine, and hands back a
+//! single pointer.
+//!
+//! Layers are accumulated in 16-bit fixed point ([`Hdr`]) so bright and dim
+//! sources can coexist without clipping at every overlap; a [`ToneMap`]
+//! operator produces the final 8-bit output only once all layers are in. In
+//! [`ColorSpace::Linear`] the accumulator holds linear light and is encoded
+//! to sRGB only at that last step.
+//!
+//! Layers are blended in a fixed order — ambients, then emissive, then
+//! lights, each by ascending id — so the output never depends on `HashMap`
+//! iteration order. That matters for [`BlendMode::Screen`], whose integer
+//! rounding makes it order-sensitive.
+//!
+//! [`Light`]: crate::lighting::Light
+//! [`Ambient`]: crate::lighting::Ambient
+
+use std::collections::HashMap;
+
+use crate::lighting::{Color, ColorSpace, FullMapCanvas, Hdr, HDR_ONE};
+
+/// How a layer's colour combines with what is already in the composite.
+///
+/// Transparent source cells (`alpha == 0`) never touch the destination; the
+/// output alpha is the max of source and destination alpha.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlendMode {
+    /// Per-channel sum, saturating at the top of the HDR range.
+    #[default]
+    Additive,
+    /// Per-channel maximum.
+    Max,
+    /// Per-channel screen: `a + b - a·b` (in units of `1.0`). Brightens like
+    /// additive but approaches white asymptotically instead of clipping;
+    /// inputs above `1.0` are treated as `1.0` in the product term.
+    Screen,
+}
+
+impl BlendMode {
+    /// Decode the WASM-facing numeric form (`0` additive, `1` max, `2` screen).
+    /// Unknown values fall back to [`BlendMode::Additive`].
+    pub fn from_u8(mode: u8) -> Self {
+        match mode {
+            1 => BlendMode::Max,
+            2 => BlendMode::Screen,
+            _ => BlendMode::Additive,
+        }
+    }
+
+    /// Blend `src` over `dst`.
+    pub fn blend(self, dst: Hdr, src: Hdr) -> Hdr {
+        if src.3 == 0 {
+            return dst;
+        }
+        let channel = |a: u16, b: u16| -> u16 {
+            match self {
+                BlendMode::Additive => a.saturating_add(b),
+                BlendMode::Max => a.max(b),
+                BlendMode::Screen => {
+                    let product = a.min(HDR_ONE) as u32 * b.min(HDR_ONE) as u32 / HDR_ONE as u32;
+                    (a as u32 + b as u32 - product).min(u16::MAX as u32) as u16
+                }
+            }
+        };
+        Hdr(
+            channel(dst.0, src.0),
+            channel(dst.1, src.1),
+            channel(dst.2, src.2),
+            dst.3.max(src.3),
+        )
+    }
+}
+
+/// Operator mapping the accumulated [`Hdr`] composite to 8-bit [`Color`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ToneMap {
+    /// Anything above `1.0` clips to 255. Identical to plain 8-bit blending
+    /// when every light has unit intensity.
+    #[default]
+    Clamp,
+    /// Reinhard `x / (1 + x)`: compresses the whole range smoothly, never
+    /// clips, and maps `1.0` to mid-grey.
+    Reinhard,
+    /// Multiply by an 8.8 fixed-point exposure gain (`256` = 1.0), then clamp.
+    Exposure(u16),
+}
+
+impl ToneMap {
+    /// Decode the WASM-facing numeric form: `0` clamp, `1` Reinhard,
+    /// `2` exposure (`param` = 8.8 gain). Unknown kinds fall back to
+    /// [`ToneMap::Clamp`].
+    pub fn from_u8(kind: u8, param: u16) -> Self {
+        match kind {
+            1 => ToneMap::Reinhard,
+            2 => ToneMap::Exposure(param),
+            _ => ToneMap::Clamp,
+        }
+    }
+
+    /// Map one HDR pixel to 8 bits. Alpha is clamped.
+    pub fn apply(self, hdr: Hdr) -> Color {
+        self.curve(hdr).to_color_clamped()
+    }
+
+    /// The operator's curve alone, still in HDR: [`Self::apply`] without the
+    /// final clamp to 8 bits.
+    pub fn curve(self, hdr: Hdr) -> Hdr {
+        match self {
+            ToneMap::Clamp => hdr,
+            ToneMap::Reinhard => {
+                let curve =
+                    |v: u16| (v as u32 * HDR_ONE as u32 / (v as u32 + HDR_ONE as u32)) as u16;
+                Hdr(curve(hdr.0), curve(hdr.1), curve(hdr.2), hdr.3)
+            }
+            ToneMap::Exposure(gain) => hdr.scale(gain),
+        }
+    }
+}
+
+/// Optional single-bounce indirect light, set through
+/// [`crate::engine::LightingEngine::set_bounce`].
+///
+/// Every directly-lit cell that touches a Wall edge or an Object cell gives
+/// back `fraction` (`0`–`255`, of its received light) to its Room, and each
+/// Room's total is spread evenly over all of its cells: a cheap,
+/// radiosity-like fill for the corners a light does not reach.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bounce {
+    pub fraction: u8,
+}
+
+/// The engine's full-map accumulation canvas (`cells_per_row²` cells, blitted
+/// at origin `(0,0)`), rebuilt from scratch by
+/// [`crate::engine::LightingEngine::compute_composite`].
+///
+/// Layers blend into the HDR accumulator, which is then tone-mapped into the
+/// 8-bit canvas handed to JS.
+pub struct Composite {
+    accum: Vec<Hdr>,
+    /// The light layers alone, kept only while a [`Bounce`] needs them: the
+    /// bounce re-emits direct light, never ambient or emissive fill.
+    direct: Option<Vec<Hdr>>,
+    canvas: FullMapCanvas,
+}
+
+impl Composite {
+    /// Allocate a fully-transparent full-map canvas of `canvas_size²` cells.
+    pub(crate) fn new(canvas_size: usize) -> Self {
+        Composite {
+            accum: vec![Hdr::default(); canvas_size * canvas_size],
+            direct: None,
+            canvas: FullMapCanvas::new(canvas_size),
+        }
+    }
+
+    pub(crate) fn canvas(&self) -> &[Color] {
+        self.canvas.cells()
+    }
+
+    pub(crate) fn hdr_canvas(&self) -> &[Hdr] {
+        &self.accum
+    }
+
+    /// Reset every cell to transparent.
+    pub(crate) fn clear(&mut self) {
+        self.accum.iter_mut().for_each(|p| *p = Hdr::default());
+        if let Some(direct) = &mut self.direct {
+            direct.iter_mut().for_each(|p| *p = Hdr::default());
+        }
+        self.canvas.clear();
+    }
+
+    /// Whether [`Self::blend_rect`] also accumulates the light layers on
+    /// their own, for [`Self::add_bounce`].
+    pub(crate) fn track_direct(&mut self, on: bool) {
+        match (on, &self.direct) {
+            (true, None) => self.direct = Some(vec![Hdr::default(); self.accum.len()]),
+            (false, Some(_)) => self.direct = None,
+            _ => {}
+        }
+    }
+
+    /// Blend another 8-bit full-map canvas (e.g. an Ambient's) into the
+    /// accumulator. `src` must be the same `size²` layout as the composite.
+    pub(crate) fn blend_full_map(&mut self, src: &[Color], mode: BlendMode, space: ColorSpace) {
+        for (dst, &s) in self.accum.iter_mut().zip(src) {
+            *dst = mode.blend(*dst, space.lift(s));
+        }
+    }
+
+    /// Blend a `width × height` HDR canvas (a Light's) whose top-left cell
+    /// sits at world cell `origin`. Cells falling outside the map are dropped.
+    pub(crate) fn blend_rect(
+        &mut self,
+        src: &[Hdr],
+        (width, height): (usize, usize),
+        origin: (i16, i16),
+        mode: BlendMode,
+    ) {
+        let size = self.canvas.size() as i32;
+        for row in 0..height {
+            let y = origin.1 as i32 + row as i32;
+            if y < 0 || y >= size {
+                continue;
+            }
+            for col in 0..width {
+                let x = origin.0 as i32 + col as i32;
+                if x < 0 || x >= size {
+                    continue;
+                }
+                let idx = (y * size + x) as usize;
+                let light = src[row * width + col];
+                self.accum[idx] = mode.blend(self.accum[idx], light);
+                if let Some(direct) = &mut self.direct {
+                    direct[idx] = mode.blend(direct[idx], light);
+                }
+            }
+        }
+    }
+
+    /// Add one bounce of the direct light already accumulated — the light
+    /// layers only, as tracked since [`Self::track_direct`]; ambient and
+    /// emissive fill is never re-emitted. `room_of` gives each cell's room
+    /// (`None` for none) and `reflective` whether it re-emits; each room's
+    /// fill is added on top of every one of its cells. Integer sums over
+    /// whole rooms, so the result is order-independent.
+    pub(crate) fn add_bounce(
+        &mut self,
+        room_of: &[Option<usize>],
+        reflective: &[bool],
+        bounce: Bounce,
+    ) {
+        let Some(direct) = &self.direct else {
+            return;
+        };
+        // Per room: summed re-emitted light per channel, and cell count.
+        let mut rooms: HashMap<usize, ([u64; 3], u64)> = HashMap::new();
+        for (idx, room) in room_of.iter().enumerate() {
+            let Some(room) = room else {
+                continue;
+            };
+            let entry = rooms.entry(*room).or_default();
+            entry.1 += 1;
+            let hdr = direct[idx];
+            if reflective[idx] && hdr.3 > 0 {
+                entry.0[0] += hdr.0 as u64;
+                entry.0[1] += hdr.1 as u64;
+                entry.0[2] += hdr.2 as u64;
+            }
+        }
+
+        let fill: HashMap<usize, Hdr> = rooms
+            .into_iter()
+            .filter_map(|(room, (sum, count))| {
+                let share =
+                    |c: u64| (c * bounce.fraction as u64 / 255 / count).min(u16::MAX as u64) as u16;
+                let hdr = Hdr(share(sum[0]), share(sum[1]), share(sum[2]), HDR_ONE);
+                (hdr.0 | hdr.1 | hdr.2 != 0).then_some((room, hdr))
+            })
+            .collect();
+        for (dst, room) in self.accum.iter_mut().zip(room_of) {
+            if let Some(&light) = room.and_then(|r| fill.get(&r)) {
+                *dst = BlendMode::Additive.blend(*dst, light);
+            }
+        }
+    }
+
+    /// Tone-map the accumulator into the 8-bit canvas, encoding from
+    /// `space`.
+    pub(crate) fn resolve(&mut self, tone_map: ToneMap, space: ColorSpace) {
+        for (dst, &hdr) in self.canvas.cells_mut().iter_mut().zip(&self.accum) {
+            *dst = space.lower(tone_map.curve(hdr));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lift(r: u8, g: u8, b: u8) -> Hdr {
+        Hdr::from_color(Color(r, g, b, 255))
+    }
+
+    fn rgb(c: Color) -> (u8, u8, u8) {
+        (c.0, c.1, c.2)
+    }
+
+    #[test]
+    fn transparent_source_leaves_destination_untouched() {
+        let dst = lift(10, 20, 30);
+        for mode in [BlendMode::Additive, BlendMode::Max, BlendMode::Screen] {
+            let src = Hdr(3000, 3000, 3000, 0);
+            assert_eq!(mode.blend(dst, src), dst);
+        }
+    }
+
+    #[test]
+    fn blend_modes_combine_channels() {
+        let a = lift(200, 100, 0);
+        let b = lift(100, 100, 0);
+        let clamp = |h| rgb(ToneMap::Clamp.apply(h));
+        assert_eq!(clamp(BlendMode::Additive.blend(a, b)), (255, 200, 0));
+        assert_eq!(clamp(BlendMode::Max.blend(a, b)), (200, 100, 0));
+        // 200 + 100 - 200·100/255 ≈ 221.6; 100 + 100 - 100·100/255 ≈ 160.8.
+        assert_eq!(clamp(BlendMode::Screen.blend(a, b)), (221, 160, 0));
+    }
+
+    #[test]
+    fn additive_accumulates_past_one_before_tone_mapping() {
+        let sum = BlendMode::Additive.blend(lift(200, 0, 0), lift(200, 0, 0));
+        assert!(sum.0 > HDR_ONE, "HDR accumulation must not clip at 1.0");
+        assert_eq!(ToneMap::Clamp.apply(sum).0, 255);
+        // Reinhard keeps the two-light sum below white but above one light.
+        let one = ToneMap::Reinhard.apply(lift(200, 0, 0)).0;
+        let two = ToneMap::Reinhard.apply(sum).0;
+        assert!(one < two && two < 255, "one={one} two={two}");
+    }
+
+    #[test]
+    fn exposure_scales_before_clamping() {
+        let half = ToneMap::Exposure(128).apply(lift(200, 100, 0));
+        assert_eq!(rgb(half), (100, 50, 0));
+        let unit = ToneMap::Exposure(256).apply(lift(200, 100, 0));
+        assert_eq!(rgb(unit), (200, 100, 0));
+    }
+}
@@ -10,6 +10,8 @@
 //! - the derived cell block map (`Vec<CellDetails>`)
 //! - the collision system ([`HybridCollisionMap`] — rooms + objects)
 //! - the registry of active [`Light`]s
+//! - the emissive cells and tile types, rendered into one [`EmissiveLayer`]
+//! - the full-map [`Composite`] of every Light and Ambient
 //!
 //! Process-wide caches that are pure functions of compile-time constants — most
 //! notably the precomputed Bresenham ray table `ALL_RAYS` in [`crate::lighting`]
@@ -22,17 +24,24 @@
 //! should construct their own instance with [`LightingEngine::new`] and call
 //! methods on it directly — that's what makes parallel test execution safe.
 
-use std::collections::{HashMap, HashSet};
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 use std::sync::RwLock;
 
 use once_cell::sync::Lazy;
 
-use crate::block_map::{compute_cell_details_for_tile, CellDetails};
-use crate::collision::HybridCollisionMap;
+use crate::animation::LightAnimation;
+use crate::block_map::{compute_cell_details_for_tile, mark_wall_edges, CellDetails, Diagonal};
+use crate::collision::{Channel, Channels, HybridCollisionMap, PixelMaterial};
+use crate::composite::{BlendMode, Bounce, Composite, ToneMap};
+use crate::formats::{self, CanvasSource, Encoded};
 use crate::lighting::{
-    build_ray_table, trace_visible_cells, Ambient, Color, ColorMode, Fov, Light, RayTable,
+    build_ray_table, trace_visible_cells_for, Ambient, AmbientOcclusion, AmbientSpread, Color,
+    ColorMode, ColorSpace, Cone, Emissive, EmissiveLayer, Falloff, Fov, Hdr, Light, LightShape,
+    RayTable,
 };
 use crate::map_grid::UnionFind;
+use crate::objects::{Object, ObjectId, ObjectRegistry, ObjectShape};
 
 /// Default cell-grid subdivision per tile, used by [`LightingEngine::default`]
 /// and the WASM back-compat [`DEFAULT_ENGINE`].
@@ -42,6 +51,96 @@ pub const DEFAULT_CELLS_PER_TILE: usize = 6;
 /// WASM back-compat [`DEFAULT_ENGINE`].
 pub const DEFAULT_TILES_PER_ROW: usize = 30;
 
+/// Identifier of a registered Light or Ambient. Lights and ambients have
+/// separate id spaces; callers pick the ids (e.g. a token's database key).
+pub type LightId = u32;
+
+/// How much light reaches one cell (or, averaged, one tile), as returned by
+/// [`LightingEngine::light_at`] and friends.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LightSample {
+    /// Tone-mapped colour, exactly as the composite would show it.
+    pub color: (u8, u8, u8),
+    /// Rec. 709 luminance of `color`, for threshold checks.
+    pub level: u8,
+    /// The blended value before tone mapping, in
+    /// [`crate::lighting::HDR_ONE`] fixed point.
+    pub hdr: Hdr,
+}
+
+/// The state of a Door edge, as recorded by [`LightingEngine::set_door_state`].
+/// Each state lets some [`Channel`]s through the Wall it sits in; none of
+/// them joins the two Rooms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DoorState {
+    /// Passes everything, both ways.
+    Open,
+    /// Passes nothing.
+    Closed,
+    /// Passes nothing, like [`DoorState::Closed`], but reported separately.
+    Locked,
+    /// Passes light and sight both ways, but movement only out of tile
+    /// `from`. Cell-level movement queries ([`LightingEngine::is_blocked_for`])
+    /// treat it as closed.
+    OneWay { from: usize },
+    /// Passes movement but not light or sight.
+    Curtain,
+    /// Passes nothing until `discovered`, then everything.
+    Secret { discovered: bool },
+}
+
+impl DoorState {
+    /// Decode the WASM-facing numeric form: `0` closed, `1` open, `2`
+    /// locked, `3` one-way (`from` = the tile it opens from), `4` curtain,
+    /// `5` undiscovered secret, `6` discovered secret. Unknown kinds fall
+    /// back to [`DoorState::Closed`].
+    pub fn from_u8(kind: u8, from: usize) -> Self {
+        match kind {
+            1 => DoorState::Open,
+            2 => DoorState::Locked,
+            3 => DoorState::OneWay { from },
+            4 => DoorState::Curtain,
+            5 => DoorState::Secret { discovered: false },
+            6 => DoorState::Secret { discovered: true },
+            _ => DoorState::Closed,
+        }
+    }
+
+    /// The numeric form [`Self::from_u8`] decodes; a one-way door's `from`
+    /// tile is not included.
+    pub fn kind(self) -> u8 {
+        match self {
+            DoorState::Closed => 0,
+            DoorState::Open => 1,
+            DoorState::Locked => 2,
+            DoorState::OneWay { .. } => 3,
+            DoorState::Curtain => 4,
+            DoorState::Secret { discovered: false } => 5,
+            DoorState::Secret { discovered: true } => 6,
+        }
+    }
+
+    /// The channels this door lets through in both directions.
+    pub fn channels(self) -> Channels {
+        match self {
+            DoorState::Open | DoorState::Secret { discovered: true } => Channels::ALL,
+            DoorState::Closed | DoorState::Locked | DoorState::Secret { discovered: false } => {
+                Channels::NONE
+            }
+            DoorState::OneWay { .. } => Channels::LIGHT | Channels::SIGHT,
+            DoorState::Curtain => Channels::MOVEMENT,
+        }
+    }
+
+    /// Whether `channel` crosses this door when leaving tile `from`.
+    pub fn passes(self, channel: Channel, from: usize) -> bool {
+        match self {
+            DoorState::OneWay { from: exit } if channel == Channel::Movement => from == exit,
+            _ => self.channels().contains(channel),
+        }
+    }
+}
+
 /// Owned instance of the lighting engine's mutable runtime state.
 ///
 /// Construct one per scenario. Multiple instances coexist freely — they share
@@ -54,21 +153,71 @@ pub struct LightingEngine {
     tiles: Vec<u8>,
     cells: Vec<CellDetails>,
     collision: HybridCollisionMap,
-    lights: HashMap<u8, Light>,
+    /// Registered Objects, whose footprints are kept written into the static
+    /// bitmap of `collision`.
+    objects: ObjectRegistry,
+    lights: HashMap<LightId, Light>,
     /// Registry of active room-bounded ambient emitters, parallel to `lights`.
     /// Each entry owns a full-map canvas flooded by `update_or_add_ambient`.
-    ambients: HashMap<u8, Ambient>,
+    ambients: HashMap<LightId, Ambient>,
     /// Lazily-allocated full-map FOV canvas, reused across `compute_fov` calls
     /// so the pointer handed to JS stays valid between frames. The engine holds
     /// no fog/explored memory (ADR-0006) — this is the live mask only.
     fov: Option<Fov>,
-    /// Open door edges as canonical `(lo, hi)` tile-index pairs. An entry's
-    /// presence = door open (tiles joined for lighting and pathfinding);
-    /// absence = closed (room boundary stands). See ADR-0003.
-    door_edges: HashSet<(usize, usize)>,
-    /// Tile-resolution room graph, kept in sync with `tiles` + `door_edges`.
+    /// How `compute_composite` combines overlapping Lights and Ambients.
+    blend_mode: BlendMode,
+    /// How `compute_composite` maps the HDR accumulator to 8-bit output.
+    tone_map: ToneMap,
+    /// Optional single-bounce indirect pass applied by `compute_composite`.
+    bounce: Option<Bounce>,
+    /// Whether lights are shaded and layers blended in sRGB bytes (the
+    /// historical output) or in linear light.
+    color_space: ColorSpace,
+    /// Lazily-allocated full-map composite of every Light and Ambient, reused
+    /// across `compute_composite` calls for the same pointer-stability reason
+    /// as `fov`.
+    composite: Option<Composite>,
+    /// Door edges as canonical `(lo, hi)` tile-index pairs, with the state
+    /// that decides which channels cross them (and, for one-way doors, in
+    /// which direction). No entry = no door (room boundary stands). See
+    /// ADR-0003.
+    door_edges: HashMap<(usize, usize), DoorState>,
+    /// Window edges as canonical `(lo, hi)` tile-index pairs: the Wall there
+    /// passes light and sight but still blocks movement.
+    window_edges: HashSet<(usize, usize)>,
+    /// Thin walls as canonical `(lo, hi)` **cell**-index pairs, authored
+    /// directly rather than derived from tile types.
+    wall_edges: HashSet<(usize, usize)>,
+    /// Diagonal walls by tile index. Each cuts its tile's cells in two along
+    /// [`Diagonal::cut_edges`], both for rays and for the cell Room graph.
+    diagonals: HashMap<usize, Diagonal>,
+    /// Tile-resolution room graph, kept in sync with `tiles`.
     /// Pathfinding (`path`, `cast_ray`, `neighbours`) reads this.
     tile_uf: UnionFind,
+    /// Channels a Wall blocks, per tile type; absent types block all of
+    /// them. A Wall between two types lets a channel through unless both
+    /// block it.
+    tile_type_channels: HashMap<u8, Channels>,
+    /// Lights whose canvases went stale because a world edit touched their
+    /// bounding square since they were last traced. Drained by
+    /// `update_dirty_lights`.
+    dirty_lights: BTreeSet<LightId>,
+    /// Ambients whose room may have changed since their last flood.
+    dirty_ambients: BTreeSet<LightId>,
+    /// Engine clock in milliseconds, advanced only by `tick`. Animated lights
+    /// are sampled at this time whenever they render.
+    time_ms: u64,
+    /// Glowing cells keyed by world cell. Override `emissive_tile_types`.
+    emissive_cells: HashMap<(u16, u16), Emissive>,
+    /// Glow applied to every cell of every tile of a given type.
+    emissive_tile_types: HashMap<u8, Emissive>,
+    /// Lazily-allocated glow of every emissive cell, reused for the same
+    /// pointer-stability reason as `fov`.
+    emissive: Option<EmissiveLayer>,
+    /// Whether an emissive setting or a world edit has made `emissive` stale.
+    emissive_dirty: bool,
+    /// Reusable buffers behind the `canvas_*` format encoders.
+    encoded: Encoded,
 }
 
 impl Default for LightingEngine {
@@ -105,11 +254,29 @@ impl LightingEngine {
             tiles,
             cells,
             collision,
+            objects: ObjectRegistry::new(cells_per_row as u16),
             lights: HashMap::new(),
             ambients: HashMap::new(),
             fov: None,
-            door_edges: HashSet::new(),
+            blend_mode: BlendMode::default(),
+            tone_map: ToneMap::default(),
+            bounce: None,
+            color_space: ColorSpace::default(),
+            composite: None,
+            door_edges: HashMap::new(),
+            window_edges: HashSet::new(),
+            wall_edges: HashSet::new(),
+            diagonals: HashMap::new(),
             tile_uf,
+            tile_type_channels: HashMap::new(),
+            dirty_lights: BTreeSet::new(),
+            dirty_ambients: BTreeSet::new(),
+            time_ms: 0,
+            emissive_cells: HashMap::new(),
+            emissive_tile_types: HashMap::new(),
+            emissive: None,
+            emissive_dirty: false,
+            encoded: Encoded::default(),
         }
     }
 
@@ -152,9 +319,16 @@ impl LightingEngine {
         self.collision.is_blocked(x0, y0, x1, y1)
     }
 
+    /// [`Self::is_blocked`] for one collision channel: light, sight or
+    /// movement.
+    pub fn is_blocked_for(&self, channel: Channel, x0: i16, y0: i16, x1: i16, y1: i16) -> bool {
+        self.collision.is_blocked_for(channel, x0, y0, x1, y1)
+    }
+
     /// Set a single tile type. Out-of-range coordinates are ignored.
     /// Recomputes the affected cell edge flags and refreshes the room
-    /// union-find from the new tile map.
+    /// union-find from the new tile map. Lights reaching the tile are marked
+    /// dirty (see [`Self::update_dirty_lights`]).
     pub fn set_tile(&mut self, x: u32, y: u32, tile: u8) {
         let tiles_per_row = self.tiles_per_row;
         let index = (x as usize) + (y as usize * tiles_per_row);
@@ -165,6 +339,8 @@ impl LightingEngine {
         self.recompute_block_map();
         self.refresh_collision_from_tiles();
         self.refresh_tile_uf_from_tiles();
+        self.mark_tile_dirty(index);
+        self.dirty_ambients.extend(self.ambients.keys().copied());
     }
 
     /// Overwrite the entire tile map. Length must match `tiles_per_row²`;
@@ -177,6 +353,7 @@ impl LightingEngine {
         self.recompute_block_map();
         self.refresh_collision_from_tiles();
         self.refresh_tile_uf_from_tiles();
+        self.mark_all_dirty();
     }
 
     /// Replace the room map data of the broad-phase collision detector.
@@ -184,11 +361,30 @@ impl LightingEngine {
     /// room layout without going through the tile setter.
     pub fn update_map_data(&mut self, map_data: Vec<i32>, map_size: usize) {
         self.collision.update_map_data(map_data, map_size);
+        self.mark_all_dirty();
     }
 
-    /// Mark a single cell as blocking (an Object cell) or not.
+    /// Mark a single cell as blocking (an Object cell) or not. Lights reaching
+    /// the cell are marked dirty. A cell covered by a registered Object stays
+    /// blocked until the last such Object leaves it.
     pub fn set_pixel(&mut self, x: u16, y: u16, blocked: bool) {
-        self.collision.pixel_map_mut().set_pixel(x, y, blocked);
+        let channels = if blocked {
+            Channels::ALL
+        } else {
+            Channels::NONE
+        };
+        self.set_pixel_channels(x, y, channels);
+    }
+
+    /// [`Self::set_pixel`] for some channels only: the cell blocks exactly
+    /// `channels` — a low crate blocks movement, a hologram blocks sight.
+    pub fn set_pixel_channels(&mut self, x: u16, y: u16, channels: Channels) {
+        let before = self.collision.pixel_map().get_pixel(x, y);
+        self.objects
+            .write_loose(x, y, channels, &mut self.collision);
+        if self.collision.pixel_map().get_pixel(x, y) != before {
+            self.mark_cells_dirty(x as i32, y as i32, x as i32, y as i32);
+        }
     }
 
     /// Batched form of [`set_pixel`].
@@ -196,28 +392,160 @@ impl LightingEngine {
     where
         I: IntoIterator<Item = (u16, u16, bool)>,
     {
-        self.collision.pixel_map_mut().set_pixel_batch(pixels);
+        for (x, y, blocked) in pixels {
+            self.set_pixel(x, y, blocked);
+        }
+    }
+
+    /// Register an Object — a chair, a barrel, a table — covering `shape`
+    /// anchored at cell `(x, y)`, or replace the Object with the same id.
+    /// Cells off the map are ignored. Lights reaching a cell whose blocked
+    /// state changed are marked dirty.
+    pub fn update_or_add_object(&mut self, id: ObjectId, x: i16, y: i16, shape: ObjectShape) {
+        let channels = self.objects.get(id).map_or(Channels::ALL, Object::channels);
+        self.place_object(id, Object::new((x, y), shape).with_channels(channels));
+    }
+
+    /// Move an Object, keeping its shape. Returns `false` if the id is
+    /// unknown.
+    pub fn move_object(&mut self, id: ObjectId, x: i16, y: i16) -> bool {
+        let Some(shape) = self.objects.get(id).map(|o| o.shape().clone()) else {
+            return false;
+        };
+        self.update_or_add_object(id, x, y, shape);
+        true
+    }
+
+    /// Give an Object a new shape in place. Returns `false` if the id is
+    /// unknown.
+    pub fn set_object_shape(&mut self, id: ObjectId, shape: ObjectShape) -> bool {
+        let Some((x, y)) = self.objects.get(id).map(Object::position) else {
+            return false;
+        };
+        self.update_or_add_object(id, x, y, shape);
+        true
+    }
+
+    /// Choose which channels an Object blocks (all of them by default).
+    /// Returns `false` if the id is unknown.
+    pub fn set_object_channels(&mut self, id: ObjectId, channels: Channels) -> bool {
+        let Some(object) = self.objects.get(id).cloned() else {
+            return false;
+        };
+        self.place_object(id, object.with_channels(channels));
+        true
+    }
+
+    /// Unregister an Object, clearing the cells no other Object covers.
+    /// Returns `false` if the id is unknown.
+    pub fn remove_object(&mut self, id: ObjectId) -> bool {
+        match self.objects.remove(id, &mut self.collision) {
+            Some(flipped) => {
+                self.mark_light_flips_dirty(&flipped);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn object(&self, id: ObjectId) -> Option<&Object> {
+        self.objects.get(id)
+    }
+
+    /// Registered Object ids, ascending.
+    pub fn object_ids(&self) -> Vec<ObjectId> {
+        self.objects.ids()
+    }
+
+    /// Replace the transient occluder layer — moving characters and the like
+    /// — with exactly `cells`. Call it every frame with the current set; the
+    /// static Object cells from [`Self::set_pixel`] are left alone, and both
+    /// layers block rays. Lights reaching a cell that was added or removed
+    /// are marked dirty.
+    pub fn set_dynamic_occluders(&mut self, cells: &[(u16, u16)]) {
+        let previous = self.collision.dynamic_occluders().to_vec();
+        self.collision.set_dynamic_occluders(cells);
+        let before: HashSet<(u16, u16)> = previous.into_iter().collect();
+        let after: HashSet<(u16, u16)> = cells.iter().copied().collect();
+        for &(x, y) in before.symmetric_difference(&after) {
+            self.emissive_dirty = true;
+            self.mark_lights_dirty_in(x as i32, y as i32, x as i32, y as i32);
+        }
+    }
+
+    /// The transient occluder cells, as last set.
+    pub fn dynamic_occluders(&self) -> &[(u16, u16)] {
+        self.collision.dynamic_occluders()
+    }
+
+    /// Give a cell a translucent material (stained glass, water, smoke), or
+    /// remove it with `None`. Rays passing through are attenuated and tinted
+    /// instead of stopped; see [`PixelMaterial`]. Lights reaching the cell
+    /// are marked dirty if its material changed.
+    pub fn set_pixel_material(&mut self, x: u16, y: u16, material: Option<PixelMaterial>) {
+        if self.pixel_material(x, y).copied() == material {
+            return;
+        }
+        self.collision.set_pixel_material(x, y, material);
+        self.mark_cells_dirty(x as i32, y as i32, x as i32, y as i32);
+    }
+
+    /// The material of cell `(x, y)`, if any.
+    pub fn pixel_material(&self, x: u16, y: u16) -> Option<&PixelMaterial> {
+        self.collision.pixel_material(x, y)
     }
 
     /// Record (or remove) a door edge between two tiles. Per ADR-0003, doors
-    /// are room-graph edges: open = the two tiles are joined for both
-    /// pathfinding and lighting; closed = the room boundary stands.
+    /// are room-graph edges; what crosses one depends on its
+    /// [`DoorState`] (see [`LightingEngine::set_door_state`]).
     ///
-    /// `open=true` records the edge and unions the cells across the shared
-    /// tile boundary in the cell-resolution room graph; `open=false` removes
-    /// it and rebuilds the room graph from tiles. Out-of-range tile indices
-    /// are stored as-is and ignored when applied.
+    /// `open=true` records a [`DoorState::Open`] door, which lets every ray
+    /// and path cross the shared tile boundary; `open=false` removes the
+    /// door whatever its state. Out-of-range tile indices are stored as-is
+    /// and ignored when applied.
     pub fn set_door_edge(&mut self, t1_idx: usize, t2_idx: usize, open: bool) {
         let pair = canonical_edge(t1_idx, t2_idx);
         if open {
-            if !self.door_edges.insert(pair) {
+            self.set_door_state(t1_idx, t2_idx, DoorState::Open);
+        } else if self.door_edges.remove(&pair).is_some() {
+            self.door_changed(pair);
+        }
+    }
+
+    /// Record a door between two tiles in the given state, replacing any
+    /// previous one: lights, `compute_fov` and `cast_ray` cross it when the
+    /// state passes light or sight, and `path` / `neighbours` when it passes
+    /// movement out of the tile they step from.
+    ///
+    /// A [`DoorState::OneWay`] whose `from` is neither tile is ignored.
+    pub fn set_door_state(&mut self, t1_idx: usize, t2_idx: usize, state: DoorState) {
+        if let DoorState::OneWay { from } = state {
+            if from != t1_idx && from != t2_idx {
                 return;
             }
-        } else if !self.door_edges.remove(&pair) {
-            return;
         }
+        let pair = canonical_edge(t1_idx, t2_idx);
+        if self.door_edges.insert(pair, state) != Some(state) {
+            self.door_changed(pair);
+        }
+    }
+
+    /// The state of the door between `t1_idx` and `t2_idx`, or `None` if
+    /// there is none. Order-insensitive.
+    pub fn door_state(&self, t1_idx: usize, t2_idx: usize) -> Option<DoorState> {
+        self.door_edges
+            .get(&canonical_edge(t1_idx, t2_idx))
+            .copied()
+    }
+
+    /// Republish the door overlays and mark what the door on `pair` touches
+    /// dirty.
+    fn door_changed(&mut self, pair: (usize, usize)) {
         self.refresh_collision_from_tiles();
         self.refresh_tile_uf_from_tiles();
+        self.mark_tile_dirty(pair.0);
+        self.mark_tile_dirty(pair.1);
+        self.mark_spilling_ambients_dirty();
     }
 
     /// Forget every recorded door edge and rebuild the room graphs from the
@@ -228,64 +556,326 @@ impl LightingEngine {
         if self.door_edges.is_empty() {
             return;
         }
-        self.door_edges.clear();
+        for (a, b) in std::mem::take(&mut self.door_edges).into_keys() {
+            self.mark_tile_dirty(a);
+            self.mark_tile_dirty(b);
+        }
         self.refresh_collision_from_tiles();
         self.refresh_tile_uf_from_tiles();
+        self.mark_spilling_ambients_dirty();
     }
 
-    /// All currently-open door edges as canonical `(lo, hi)` tile-index pairs.
-    pub fn door_edges(&self) -> &HashSet<(usize, usize)> {
+    /// All recorded door edges as canonical `(lo, hi)` tile-index pairs,
+    /// with their states.
+    pub fn door_edges(&self) -> &HashMap<(usize, usize), DoorState> {
         &self.door_edges
     }
 
-    /// Whether a door edge between `t1_idx` and `t2_idx` is currently recorded.
-    /// Order-insensitive.
+    /// Whether a door edge between `t1_idx` and `t2_idx` is currently recorded,
+    /// in any state. Order-insensitive.
     pub fn has_door_edge(&self, t1_idx: usize, t2_idx: usize) -> bool {
-        self.door_edges.contains(&canonical_edge(t1_idx, t2_idx))
+        self.door_edges
+            .contains_key(&canonical_edge(t1_idx, t2_idx))
+    }
+
+    /// Record (or remove) a window between two adjacent tiles. The Wall
+    /// along their shared boundary then lets light and sight through —
+    /// lights, `compute_fov` and `cast_ray` see across it — while `path` and
+    /// `neighbours` still treat it as a wall. Unlike a door, a window never
+    /// joins the two Rooms, so Ambients stay on their own side.
+    pub fn set_window_edge(&mut self, t1_idx: usize, t2_idx: usize, window: bool) {
+        let pair = canonical_edge(t1_idx, t2_idx);
+        if window {
+            if !self.window_edges.insert(pair) {
+                return;
+            }
+        } else if !self.window_edges.remove(&pair) {
+            return;
+        }
+        self.publish_door_cell_edges();
+        self.mark_tile_dirty(pair.0);
+        self.mark_tile_dirty(pair.1);
+    }
+
+    /// Forget every recorded window edge.
+    pub fn clear_window_edges(&mut self) {
+        if self.window_edges.is_empty() {
+            return;
+        }
+        for (a, b) in std::mem::take(&mut self.window_edges) {
+            self.mark_tile_dirty(a);
+            self.mark_tile_dirty(b);
+        }
+        self.publish_door_cell_edges();
+    }
+
+    /// All window edges as canonical `(lo, hi)` tile-index pairs.
+    pub fn window_edges(&self) -> &HashSet<(usize, usize)> {
+        &self.window_edges
+    }
+
+    /// Whether a window edge between `t1_idx` and `t2_idx` is recorded.
+    /// Order-insensitive.
+    pub fn has_window_edge(&self, t1_idx: usize, t2_idx: usize) -> bool {
+        self.window_edges.contains(&canonical_edge(t1_idx, t2_idx))
+    }
+
+    /// Add (or remove) a thin wall along the whole boundary between two
+    /// adjacent tiles, without changing either tile's type: it blocks every
+    /// ray and `path` / `neighbours` / `cast_ray`, and raises the cells'
+    /// edge flags, but both tiles stay in the same Room (and Ambient).
+    /// Non-adjacent tiles are ignored.
+    pub fn set_wall_edge(&mut self, t1_idx: usize, t2_idx: usize, wall: bool) {
+        if t1_idx >= self.tiles.len() || t2_idx >= self.tiles.len() {
+            return;
+        }
+        let mut edges = HashSet::new();
+        self.tile_edge_cells(t1_idx, t2_idx, &mut edges);
+        self.set_wall_cell_edges(edges.into_iter().collect(), wall);
+    }
+
+    /// Add (or remove) a thin wall on the edge between two 4-adjacent cells
+    /// — a partition finer than a tile. Tile-level pathing only treats a
+    /// tile boundary as walled once every cell edge along it is.
+    /// Non-adjacent or off-map cells are ignored.
+    pub fn set_cell_wall_edge(&mut self, x0: u16, y0: u16, x1: u16, y1: u16, wall: bool) {
+        let cells_per_row = self.cells_per_row();
+        let in_map = |x: u16, y: u16| (x as usize) < cells_per_row && (y as usize) < cells_per_row;
+        if !in_map(x0, y0) || !in_map(x1, y1) || x0.abs_diff(x1) + y0.abs_diff(y1) != 1 {
+            return;
+        }
+        let a = y0 as usize * cells_per_row + x0 as usize;
+        let b = y1 as usize * cells_per_row + x1 as usize;
+        self.set_wall_cell_edges(vec![canonical_edge(a, b)], wall);
+    }
+
+    /// Remove every thin wall.
+    pub fn clear_wall_edges(&mut self) {
+        let edges: Vec<(usize, usize)> = self.wall_edges.iter().copied().collect();
+        self.set_wall_cell_edges(edges, false);
+    }
+
+    /// All thin walls as canonical `(lo, hi)` cell-index pairs.
+    pub fn wall_edges(&self) -> &HashSet<(usize, usize)> {
+        &self.wall_edges
+    }
+
+    /// Whether a thin wall sits between cells `(x0, y0)` and `(x1, y1)`.
+    /// Order-insensitive.
+    pub fn has_cell_wall_edge(&self, x0: u16, y0: u16, x1: u16, y1: u16) -> bool {
+        let cells_per_row = self.cells_per_row();
+        let a = y0 as usize * cells_per_row + x0 as usize;
+        let b = y1 as usize * cells_per_row + x1 as usize;
+        self.wall_edges.contains(&canonical_edge(a, b))
+    }
+
+    /// Lay (or with `None`, remove) a diagonal wall across tile `tile_idx`.
+    /// Rays cannot cross it, the cells' edge flags follow its staircase, and
+    /// each half-triangle joins the Room on its own side — so the two halves
+    /// share a Room only if that Room reaches around the tile, and an
+    /// Ambient fills only the halves its Room reaches. Tile-level pathing is
+    /// unaffected. Off-map tiles are ignored.
+    pub fn set_tile_diagonal(&mut self, tile_idx: usize, diagonal: Option<Diagonal>) {
+        if tile_idx >= self.tiles.len() {
+            return;
+        }
+        let previous = match diagonal {
+            Some(d) => self.diagonals.insert(tile_idx, d),
+            None => self.diagonals.remove(&tile_idx),
+        };
+        if previous == diagonal {
+            return;
+        }
+        self.refresh_collision_from_tiles();
+        self.recompute_block_map();
+        self.mark_tile_dirty(tile_idx);
+        self.dirty_ambients.extend(self.ambients.keys().copied());
+    }
+
+    /// The diagonal wall across tile `tile_idx`, if any.
+    pub fn tile_diagonal(&self, tile_idx: usize) -> Option<Diagonal> {
+        self.diagonals.get(&tile_idx).copied()
+    }
+
+    /// Remove every diagonal wall.
+    pub fn clear_tile_diagonals(&mut self) {
+        let tiles: Vec<usize> = self.diagonals.keys().copied().collect();
+        for tile_idx in tiles {
+            self.set_tile_diagonal(tile_idx, None);
+        }
+    }
+
+    /// Choose which channels the Walls of tile type `tile` block (all of
+    /// them by default). A Wall between two types lets a channel through
+    /// unless both types block it, so a glass-case type that blocks only
+    /// movement is lit and seen through, while a curtain type that blocks
+    /// light and sight can be walked through. Rooms themselves — and so
+    /// Ambients — are unchanged.
+    pub fn set_tile_type_channels(&mut self, tile: u8, channels: Channels) {
+        let previous = if channels == Channels::ALL {
+            self.tile_type_channels.remove(&tile)
+        } else {
+            self.tile_type_channels.insert(tile, channels)
+        };
+        if previous.unwrap_or(Channels::ALL) == channels {
+            return;
+        }
+        self.publish_door_cell_edges();
+        self.mark_all_dirty();
+    }
+
+    /// The channels the Walls of tile type `tile` block.
+    pub fn tile_type_channels(&self, tile: u8) -> Channels {
+        self.tile_type_channels
+            .get(&tile)
+            .copied()
+            .unwrap_or(Channels::ALL)
     }
 
-    /// Clear all object cells (does not touch the tile map).
+    /// Clear all object cells and their materials, unregistering every
+    /// Object. The tile map, rooms, walls, doors and windows are untouched.
     pub fn clear_pixel_collisions(&mut self) {
-        use crate::collision::CollisionDetector;
-        self.collision.clear();
+        self.collision.clear_objects();
+        self.objects.clear();
+        self.mark_all_dirty();
     }
 
     /// Create or update a rainbow light. Returns a pointer to the rendered
     /// canvas (used by the WASM shim). Rust callers should prefer
     /// [`Self::light_canvas`] after this call.
-    pub fn update_or_add_light(&mut self, id: u8, r: i16, x: i16, y: i16) -> *const Color {
-        self.update_light_with_color_mode(id, r, x, y, None)
+    pub fn update_or_add_light(&mut self, id: LightId, r: i16, x: i16, y: i16) -> *const Color {
+        self.update_light_with(id, r, x, y, |l| l.set_color_mode(None))
     }
 
     /// Create or update a solid-color light.
     pub fn update_or_add_light_with_solid_color(
         &mut self,
-        id: u8,
+        id: LightId,
         r: i16,
         x: i16,
         y: i16,
         hue: u8,
     ) -> *const Color {
-        self.update_light_with_color_mode(id, r, x, y, Some(ColorMode::Solid(hue)))
+        self.update_light_with(id, r, x, y, |l| {
+            l.set_color_mode(Some(ColorMode::Solid(hue)))
+        })
     }
 
     /// Create or update a custom-HSV light.
     pub fn update_or_add_light_with_custom_color(
         &mut self,
-        id: u8,
+        id: LightId,
         r: i16,
         x: i16,
         y: i16,
         hue: u8,
         saturation: u8,
     ) -> *const Color {
-        self.update_light_with_color_mode(
-            id,
-            r,
-            x,
-            y,
-            Some(ColorMode::Custom { hue, saturation }),
-        )
+        self.update_light_with(id, r, x, y, |l| {
+            l.set_color_mode(Some(ColorMode::Custom { hue, saturation }))
+        })
+    }
+
+    /// Create or update a light with an exact RGB colour (no HSV round-trip).
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_or_add_light_with_rgb(
+        &mut self,
+        id: LightId,
+        r: i16,
+        x: i16,
+        y: i16,
+        red: u8,
+        green: u8,
+        blue: u8,
+    ) -> *const Color {
+        self.update_light_with(id, r, x, y, |l| {
+            l.set_color_mode(Some(ColorMode::Rgb(red, green, blue)))
+        })
+    }
+
+    /// Create or update a light coloured by black-body temperature `kelvin`.
+    pub fn update_or_add_light_with_kelvin(
+        &mut self,
+        id: LightId,
+        r: i16,
+        x: i16,
+        y: i16,
+        kelvin: u16,
+    ) -> *const Color {
+        self.update_light_with(id, r, x, y, |l| {
+            l.set_color_mode(Some(ColorMode::Kelvin(kelvin)))
+        })
+    }
+
+    /// Create or update a light with a brightness multiplier in 8.8 fixed
+    /// point (`256` = 1.0, `64` = a quarter-strength candle, `1024` = 4×).
+    /// The light's own canvas clamps at 255; the composite keeps the full
+    /// range until tone mapping.
+    pub fn update_or_add_light_with_intensity(
+        &mut self,
+        id: LightId,
+        r: i16,
+        x: i16,
+        y: i16,
+        intensity: u16,
+    ) -> *const Color {
+        self.update_light_with(id, r, x, y, |l| l.set_intensity(intensity))
+    }
+
+    /// Create or update a spotlight: the light only reaches cells inside
+    /// `cone`, reusing the shared ray table and skipping angles outside it.
+    /// `None` turns the light back into an omnidirectional one.
+    pub fn update_or_add_light_with_cone(
+        &mut self,
+        id: LightId,
+        r: i16,
+        x: i16,
+        y: i16,
+        cone: Option<Cone>,
+    ) -> *const Color {
+        self.update_light_with(id, r, x, y, |l| l.set_cone(cone))
+    }
+
+    /// Create or update a light with an emitting disc of `source_radius`
+    /// cells. Larger sources cast soft-edged shadows; `0` restores a point
+    /// source with hard shadows.
+    pub fn update_or_add_light_with_source_radius(
+        &mut self,
+        id: LightId,
+        r: i16,
+        x: i16,
+        y: i16,
+        source_radius: u8,
+    ) -> *const Color {
+        self.update_light_with(id, r, x, y, |l| l.set_source_radius(source_radius))
+    }
+
+    /// Create or update a light with the given emitter geometry. `(x, y)`
+    /// anchors the shape: a segment runs from it to `end`, a rectangle has it
+    /// as its top-left cell. The canvas covers the shape's bounding box grown
+    /// by `r`; see [`Self::light_canvas_rect`].
+    pub fn update_or_add_light_with_shape(
+        &mut self,
+        id: LightId,
+        r: i16,
+        x: i16,
+        y: i16,
+        shape: LightShape,
+    ) -> *const Color {
+        self.update_light_with(id, r, x, y, |l| l.set_shape(shape))
+    }
+
+    /// Create or update a light with the given brightness [`Falloff`] curve.
+    /// The light's colour mode is left as it was (rainbow for a new light).
+    pub fn update_or_add_light_with_falloff(
+        &mut self,
+        id: LightId,
+        r: i16,
+        x: i16,
+        y: i16,
+        falloff: Falloff,
+    ) -> *const Color {
+        self.update_light_with(id, r, x, y, |l| l.set_falloff(falloff))
     }
 
     /// Create or update a room-bounded ambient emitter and return a pointer to
@@ -296,31 +886,48 @@ impl LightingEngine {
     /// is filled with `Color(r, g, b, 255)`; everything else stays transparent
     /// `(0, 0, 0, 0)`. Because the room is the `tile_uf` partition (which is
     /// door-agnostic, per ADR-0003), the fill never crosses a Door, open or
-    /// closed. An emitter on a non-floor tile (`tile <= 0`) or out of range
+    /// closed — unless [`Self::set_ambient_spread`] lets it spill through
+    /// open ones. An emitter on a non-floor tile (`tile <= 0`) or out of range
     /// emits an empty (fully transparent) canvas.
     pub fn update_or_add_ambient(
         &mut self,
-        id: u8,
+        id: LightId,
         tile_x: i16,
         tile_y: i16,
         r: u8,
         g: u8,
         b: u8,
     ) -> *const Color {
+        let cells_per_row = self.cells_per_row();
+        self.ambients
+            .entry(id)
+            .or_insert_with(|| Ambient::new(cells_per_row))
+            .set_emitter((tile_x, tile_y), Color(r, g, b, 255));
+        self.refresh_ambient(id)
+    }
+
+    /// Re-flood ambient `id` from its stored emitter, or clear it if it is
+    /// disabled. Returns its canvas pointer (null if `id` is unknown).
+    fn refresh_ambient(&mut self, id: LightId) -> *const Color {
         let tiles_per_row = self.tiles_per_row;
         let cells_per_tile = self.cells_per_tile;
-        let cells_per_row = self.cells_per_row();
-        let color = Color(r, g, b, 255);
+        let Some(ambient) = self.ambients.get(&id) else {
+            return std::ptr::null();
+        };
+        self.dirty_ambients.remove(&id);
+        let ((tile_x, tile_y), color) = ambient.emitter();
+        let enabled = ambient.enabled();
+        let spread = ambient.spread();
 
         // Resolve the emitter tile's room first (needs `&mut tile_uf` for
         // find()), then collect every tile in that room. Done before borrowing
-        // `ambients` so the two mutable borrows of `self` don't overlap.
+        // `ambients` mutably so the two mutable borrows of `self` don't overlap.
         let in_range = tile_x >= 0
             && tile_y >= 0
             && (tile_x as usize) < tiles_per_row
             && (tile_y as usize) < tiles_per_row;
         let mut room_tiles: Vec<usize> = Vec::new();
-        if in_range {
+        if in_range && enabled {
             let index = (tile_x as usize) + (tile_y as usize) * tiles_per_row;
             if self.tile_at(index) > 0 {
                 let room = self.tile_uf.find(index);
@@ -331,993 +938,3676 @@ impl LightingEngine {
                 }
             }
         }
+        let lit_tiles: Vec<(usize, u8)> = if spread == AmbientSpread::Room {
+            room_tiles.into_iter().map(|t| (t, 255)).collect()
+        } else {
+            self.spill_through_doors(room_tiles, spread)
+        };
 
-        let ambient = self
-            .ambients
-            .entry(id)
-            .or_insert_with(|| Ambient::new(cells_per_row));
+        // A tile cut by a Diagonal is filled only in the halves whose cell
+        // Room the fill reaches through an uncut tile (or whole, if it
+        // reaches none — an ambient in a lone diagonal tile).
+        let collision = &self.collision;
+        let diagonals = &self.diagonals;
+        let cell_room = |ti: usize, dx: usize, dy: usize| {
+            let cx = (ti % tiles_per_row) * cells_per_tile + dx;
+            let cy = (ti / tiles_per_row) * cells_per_tile + dy;
+            collision.cell_room(cx as u16, cy as u16)
+        };
+        let reached: HashSet<usize> = lit_tiles
+            .iter()
+            .filter(|(ti, _)| !diagonals.is_empty() && !diagonals.contains_key(ti))
+            .filter_map(|&(ti, _)| cell_room(ti, 0, 0))
+            .collect();
+
+        let ambient = self.ambients.get_mut(&id).expect("checked above");
         ambient.clear();
-        for &ti in &room_tiles {
+        for (ti, brightness) in lit_tiles {
+            let scale = |c: u8| (c as u16 * brightness as u16 / 255) as u8;
+            let in_reach = |dx, dy| cell_room(ti, dx, dy).is_some_and(|r| reached.contains(&r));
+            let split = diagonals.contains_key(&ti)
+                && (0..cells_per_tile * cells_per_tile)
+                    .any(|i| in_reach(i % cells_per_tile, i / cells_per_tile));
             ambient.fill_tile(
                 ti % tiles_per_row,
                 ti / tiles_per_row,
                 cells_per_tile,
-                color,
+                Color(scale(color.0), scale(color.1), scale(color.2), 255),
+                |dx, dy| !split || in_reach(dx, dy),
             );
         }
+        ambient.apply_occlusion(&self.cells, self.collision.pixel_map());
         ambient.canvas().as_ptr()
     }
 
-    /// Borrow an ambient emitter's full-map canvas if one with the given id
-    /// exists.
-    pub fn ambient_canvas(&self, id: u8) -> Option<&[Color]> {
-        self.ambients.get(&id).map(|a| a.canvas())
+    /// Walk outwards from the tiles of an ambient's room through the room
+    /// graph — same-room tile steps and open `door_edges` that pass light,
+    /// but not Walls whose tile types let light through — and return every
+    /// floor tile reached with its brightness.
+    /// Doors always cost a step; moving within a room costs one only for
+    /// [`AmbientSpread::Geodesic`] (a 0-1 BFS, so each tile gets its
+    /// shortest step count).
+    fn spill_through_doors(
+        &mut self,
+        room_tiles: Vec<usize>,
+        spread: AmbientSpread,
+    ) -> Vec<(usize, u8)> {
+        let in_room_cost = u32::from(matches!(spread, AmbientSpread::Geodesic { .. }));
+        let mut steps = vec![u32::MAX; self.tiles.len()];
+        let mut queue = VecDeque::new();
+        for t in room_tiles {
+            steps[t] = 0;
+            queue.push_back(t);
+        }
+        while let Some(t) = queue.pop_front() {
+            let room = self.tile_uf.find(t);
+            for n in self.neighbours_for(Channel::Light, t, false) {
+                if self.tile_at(n) <= 0 {
+                    continue;
+                }
+                let cost = if self.tile_uf.find(n) == room {
+                    in_room_cost
+                } else if self.door_passes(t, n, Channel::Light) {
+                    1
+                } else {
+                    continue;
+                };
+                let next = steps[t] + cost;
+                if next >= steps[n] || spread.brightness(next) == 0 {
+                    continue;
+                }
+                steps[n] = next;
+                if cost == 0 {
+                    queue.push_front(n);
+                } else {
+                    queue.push_back(n);
+                }
+            }
+        }
+        steps
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, s)| s != u32::MAX)
+            .map(|(t, s)| (t, spread.brightness(s)))
+            .collect()
     }
 
-    /// Compute the live field-of-view mask for a set of viewer points and
-    /// return a pointer to the resulting full-map **FOV canvas**
-    /// (`cells_per_row²` RGBA cells in wasm linear memory).
-    ///
-    /// `viewers` is a flat array of viewer positions in cell coords
-    /// `[x0, y0, x1, y1, …]`; a trailing odd element (if any) is ignored. Each
-    /// viewer casts rays out to the ray table's max distance through the same
-    /// Room + Object collision as [`Light::update`] (minus colour and falloff);
-    /// every cell a ray reaches is marked opaque white `(255, 255, 255, 255)`
-    /// and everything else stays transparent `(0, 0, 0, 0)`. Results union
-    /// across viewers (marking is idempotent). An empty `viewers` array yields a
-    /// fully-transparent canvas.
-    ///
-    /// Pure compute: the engine stores no explored/fog memory (ADR-0006). The
-    /// returned canvas is overwritten on the next call.
-    pub fn compute_fov(&mut self, viewers: &[i16]) -> *const Color {
-        let cells_per_row = self.cells_per_row();
-        let max_dist = self.max_dist;
+    /// Choose how far ambient `id` reaches past its own room (see
+    /// [`AmbientSpread`]) and re-flood it. Returns `false` if no ambient has
+    /// that id. Spilling ambients are re-flooded by
+    /// [`Self::update_dirty_lights`] whenever a door opens or closes.
+    pub fn set_ambient_spread(&mut self, id: LightId, spread: AmbientSpread) -> bool {
+        let Some(ambient) = self.ambients.get_mut(&id) else {
+            return false;
+        };
+        ambient.set_spread(spread);
+        self.refresh_ambient(id);
+        true
+    }
 
-        // Disjoint field borrows: `collision` + `all_rays` immutably, `fov`
-        // mutably. Bind each field directly so the borrow checker sees them as
-        // non-overlapping.
-        let collision = &self.collision;
-        let rays = &self.all_rays;
-        let fov = self.fov.get_or_insert_with(|| Fov::new(cells_per_row));
+    /// An ambient's spread mode, or `None` if no ambient has that id.
+    pub fn ambient_spread(&self, id: LightId) -> Option<AmbientSpread> {
+        self.ambients.get(&id).map(|a| a.spread())
+    }
 
-        fov.clear();
-        for pair in viewers.chunks_exact(2) {
-            let pos = (pair[0], pair[1]);
-            trace_visible_cells(pos, collision, rays, max_dist, |offset, _angle, _d| {
-                fov.mark(pos.0 + offset.0, pos.1 + offset.1);
-            });
-        }
-        fov.canvas().as_ptr()
+    /// Darken ambient `id` near Walls and Objects (see [`AmbientOcclusion`]),
+    /// or restore its flat fill with `None`, and re-flood it. Returns `false`
+    /// if no ambient has that id. Occluded ambients are re-flooded by
+    /// [`Self::update_dirty_lights`] after Object edits.
+    pub fn set_ambient_occlusion(
+        &mut self,
+        id: LightId,
+        occlusion: Option<AmbientOcclusion>,
+    ) -> bool {
+        let Some(ambient) = self.ambients.get_mut(&id) else {
+            return false;
+        };
+        ambient.set_occlusion(occlusion);
+        self.refresh_ambient(id);
+        true
     }
 
-    /// Borrow the most recently computed FOV canvas, if [`Self::compute_fov`]
-    /// has been called at least once.
-    pub fn fov_canvas(&self) -> Option<&[Color]> {
-        self.fov.as_ref().map(|f| f.canvas())
+    /// An ambient's occlusion setting, or `None` if it has none or the id is
+    /// unknown.
+    pub fn ambient_occlusion(&self, id: LightId) -> Option<AmbientOcclusion> {
+        self.ambients.get(&id).and_then(|a| a.occlusion())
     }
 
-    /// Borrow a light's canvas if one with the given id exists.
-    pub fn light_canvas(&self, id: u8) -> Option<&[Color]> {
-        self.lights.get(&id).map(|l| l.canvas())
+    /// Borrow an ambient emitter's full-map canvas if one with the given id
+    /// exists.
+    pub fn ambient_canvas(&self, id: LightId) -> Option<&[Color]> {
+        self.ambients.get(&id).map(|a| a.canvas())
     }
 
-    /// Side length (in cells) of a light's square canvas.
-    pub fn light_canvas_size(&self, id: u8) -> Option<usize> {
-        self.lights.get(&id).map(|l| l.canvas_size())
+    /// Unregister a light and free its canvas. Returns `false` if no light
+    /// has that id. Any pointer previously returned for it becomes dangling.
+    pub fn remove_light(&mut self, id: LightId) -> bool {
+        self.dirty_lights.remove(&id);
+        self.lights.remove(&id).is_some()
     }
 
-    /// Light position in cell coords.
-    pub fn light_position(&self, id: u8) -> Option<(i16, i16)> {
-        self.lights.get(&id).map(|l| l.pos())
+    /// Unregister an ambient emitter and free its canvas. Returns `false` if
+    /// no ambient has that id.
+    pub fn remove_ambient(&mut self, id: LightId) -> bool {
+        self.dirty_ambients.remove(&id);
+        self.ambients.remove(&id).is_some()
     }
 
-    /// Light radius in cells.
-    pub fn light_radius(&self, id: u8) -> Option<i16> {
-        self.lights.get(&id).map(|l| l.radius())
+    /// Ids of every registered light, ascending.
+    pub fn light_ids(&self) -> Vec<LightId> {
+        let mut ids: Vec<LightId> = self.lights.keys().copied().collect();
+        ids.sort_unstable();
+        ids
     }
 
-    fn update_light_with_color_mode(
-        &mut self,
-        id: u8,
-        r: i16,
-        x: i16,
-        y: i16,
-        color_mode: Option<ColorMode>,
-    ) -> *const Color {
-        let clamped_r = r.min(self.max_dist as i16).max(1);
-
-        let needs_new = match self.lights.get(&id) {
-            Some(existing) => existing.radius() != clamped_r || existing.color_mode() != &color_mode,
-            None => true,
-        };
-        if needs_new {
-            self.lights
-                .insert(id, Light::new((x, y), clamped_r, color_mode.clone()));
-        }
+    /// Ids of every registered ambient emitter, ascending.
+    pub fn ambient_ids(&self) -> Vec<LightId> {
+        let mut ids: Vec<LightId> = self.ambients.keys().copied().collect();
+        ids.sort_unstable();
+        ids
+    }
 
-        // Disjoint borrows: `lights` mutably, `collision`+`all_rays` immutably.
+    /// Switch a light on or off without unregistering it. A disabled light
+    /// keeps its settings and its canvas allocation (so pointers held by JS
+    /// stay valid) but renders transparent and is skipped by
+    /// [`Self::compute_composite`]; re-enabling re-traces it. Returns `false`
+    /// if no light has that id.
+    pub fn set_light_enabled(&mut self, id: LightId, enabled: bool) -> bool {
         let collision = &self.collision;
         let all_rays = &self.all_rays;
         let max_dist = self.max_dist;
-        let light = self
-            .lights
-            .get_mut(&id)
-            .expect("just inserted or known to exist");
-        light.set_state((x, y), clamped_r, color_mode);
-        light.update(collision, all_rays, max_dist)
+        let Some(light) = self.lights.get_mut(&id) else {
+            return false;
+        };
+        if light.enabled() != enabled {
+            light.set_enabled(enabled);
+            light.update(collision, all_rays, max_dist, self.time_ms);
+            self.dirty_lights.remove(&id);
+        }
+        true
     }
 
-    fn recompute_block_map(&mut self) {
-        let tiles_total = self.tiles.len();
-        for tile_index in 0..tiles_total {
-            compute_cell_details_for_tile(
-                tile_index,
-                &self.tiles,
-                &mut self.cells,
-                self.cells_per_tile,
-                self.tiles_per_row,
-            );
+    /// Switch an ambient emitter on or off without unregistering it; the
+    /// ambient counterpart of [`Self::set_light_enabled`].
+    pub fn set_ambient_enabled(&mut self, id: LightId, enabled: bool) -> bool {
+        let Some(ambient) = self.ambients.get_mut(&id) else {
+            return false;
+        };
+        if ambient.enabled() != enabled {
+            ambient.set_enabled(enabled);
+            self.refresh_ambient(id);
         }
+        true
     }
 
-    fn refresh_collision_from_tiles(&mut self) {
-        // The broad-phase room graph is queried in **cell coordinates** by
-        // [`crate::lighting::Light::update`] (ray walks are at cell resolution),
-        // so the union-find must be built at cell resolution as well. Expand
-        // the tile map: every cell inherits the type of its containing tile.
-        // Two cells share a room iff their tiles share a type AND are
-        // 4-connected via same-type tiles — the union-find's adjacency union
-        // takes care of that automatically.
-        let cells_per_tile = self.cells_per_tile;
-        let tiles_per_row = self.tiles_per_row;
-        let cells_per_row = cells_per_tile * tiles_per_row;
-        let mut cell_map = vec![0i32; cells_per_row * cells_per_row];
-        for tile_y in 0..tiles_per_row {
-            for tile_x in 0..tiles_per_row {
-                let tile = self.tiles[tile_y * tiles_per_row + tile_x] as i32;
-                let cy0 = tile_y * cells_per_tile;
-                let cx0 = tile_x * cells_per_tile;
-                for dy in 0..cells_per_tile {
-                    let row = (cy0 + dy) * cells_per_row;
-                    for dx in 0..cells_per_tile {
-                        cell_map[row + cx0 + dx] = tile;
-                    }
-                }
-            }
-        }
-        self.collision.update_map_data(cell_map, cells_per_row);
-        self.publish_door_cell_edges();
+    /// Whether a light is enabled, or `None` if no light has that id.
+    pub fn light_enabled(&self, id: LightId) -> Option<bool> {
+        self.lights.get(&id).map(|l| l.enabled())
     }
 
-    /// Rebuild the tile-resolution room graph from the current tile map.
-    /// Doors are *not* unioned into the UF — a UF union closes transitively
-    /// and would dissolve the entire room boundary the moment one door
-    /// opened. Door connectivity is consulted edge-by-edge in `neighbours`
-    /// / `cast_ray` instead.
-    fn refresh_tile_uf_from_tiles(&mut self) {
-        let tiles: Vec<i32> = self.tiles.iter().map(|&t| t as i32).collect();
-        self.tile_uf = UnionFind::new(tiles, self.tiles_per_row);
+    /// Whether an ambient is enabled, or `None` if no ambient has that id.
+    pub fn ambient_enabled(&self, id: LightId) -> Option<bool> {
+        self.ambients.get(&id).map(|a| a.enabled())
     }
 
-    /// Compute the cell-edge overlay corresponding to today's open door
-    /// tile-edges and hand it to the collision detector. Each open door (a
-    /// pair of adjacent tiles) becomes `cells_per_tile` cell-pair entries
-    /// along the shared tile boundary; the broad-phase walk consults the
-    /// overlay only when it would otherwise reject a step.
-    fn publish_door_cell_edges(&mut self) {
-        let cells_per_tile = self.cells_per_tile;
-        let tiles_per_row = self.tiles_per_row;
-        let cells_per_row = cells_per_tile * tiles_per_row;
-        let mut edges: HashSet<(usize, usize)> = HashSet::new();
-        for &(a, b) in &self.door_edges {
-            let (a_x, a_y) = (a % tiles_per_row, a / tiles_per_row);
-            let (b_x, b_y) = (b % tiles_per_row, b / tiles_per_row);
-            if a_y == b_y && a_x.abs_diff(b_x) == 1 {
-                let left_tx = a_x.min(b_x);
-                let cy0 = a_y * cells_per_tile;
-                let cx_left = left_tx * cells_per_tile + cells_per_tile - 1;
-                let cx_right = cx_left + 1;
-                for dy in 0..cells_per_tile {
-                    let li = (cy0 + dy) * cells_per_row + cx_left;
-                    let ri = (cy0 + dy) * cells_per_row + cx_right;
-                    edges.insert(canonical_edge(li, ri));
-                }
-            } else if a_x == b_x && a_y.abs_diff(b_y) == 1 {
-                let top_ty = a_y.min(b_y);
-                let cx0 = a_x * cells_per_tile;
-                let cy_top = top_ty * cells_per_tile + cells_per_tile - 1;
-                let cy_bot = cy_top + 1;
-                for dx in 0..cells_per_tile {
-                    let ti = cy_top * cells_per_row + cx0 + dx;
-                    let bi = cy_bot * cells_per_row + cx0 + dx;
-                    edges.insert(canonical_edge(ti, bi));
-                }
+    /// Lights whose canvases are stale because the tile map, an Object cell,
+    /// a material or a door changed under them since their last trace,
+    /// ascending.
+    pub fn dirty_lights(&self) -> Vec<LightId> {
+        self.dirty_lights.iter().copied().collect()
+    }
+
+    /// Re-trace only the lights invalidated by world edits since their last
+    /// render, and re-flood any ambient whose room may have changed. Returns
+    /// the ids of the re-traced lights, ascending, so callers know which
+    /// canvases to re-upload. Moving or re-configuring a light through
+    /// `update_or_add_*` already re-traces it (and only it) immediately. A
+    /// stale emissive canvas is re-rendered too, if one has been computed.
+    pub fn update_dirty_lights(&mut self) -> Vec<LightId> {
+        if self.emissive.is_some() {
+            self.compute_emissive();
+        }
+        for id in std::mem::take(&mut self.dirty_ambients) {
+            self.refresh_ambient(id);
+        }
+        let ids: Vec<LightId> = std::mem::take(&mut self.dirty_lights).into_iter().collect();
+        for id in &ids {
+            if let Some(light) = self.lights.get_mut(id) {
+                light.update(&self.collision, &self.all_rays, self.max_dist, self.time_ms);
             }
         }
-        self.collision.set_door_cell_edges(edges);
+        ids
     }
 
-    fn has_open_door_between(&self, a: usize, b: usize) -> bool {
-        self.door_edges.contains(&canonical_edge(a, b))
+    /// Attach a time-driven [`LightAnimation`] to a light, or remove it with
+    /// `None`, and re-render the light at the current engine time. Returns
+    /// `false` if no light has that id.
+    pub fn set_light_animation(&mut self, id: LightId, animation: Option<LightAnimation>) -> bool {
+        let Some(light) = self.lights.get_mut(&id) else {
+            return false;
+        };
+        light.set_animation(animation);
+        light.update(&self.collision, &self.all_rays, self.max_dist, self.time_ms);
+        self.dirty_lights.remove(&id);
+        true
     }
 
-    /// Tile-coord BFS pathfinder. Returns the chain of tile indices from
-    /// `(x1,y1)` to `(x2,y2)` inclusive, or empty if no route exists or
-    /// either endpoint is a wall tile. Walks `neighbours()` so it respects
-    /// the room graph (and door overlays that join rooms across boundaries).
-    pub fn path(&mut self, x1: i32, y1: i32, x2: i32, y2: i32) -> Vec<usize> {
-        let tpr = self.tiles_per_row;
-        let total = tpr * tpr;
-        if x1 < 0 || y1 < 0 || x2 < 0 || y2 < 0 {
-            return Vec::new();
+    /// A light's animation, or `None` if it has none or the id is unknown.
+    pub fn light_animation(&self, id: LightId) -> Option<&LightAnimation> {
+        self.lights.get(&id).and_then(|l| l.animation())
+    }
+
+    /// Advance the engine clock by `dt_ms` and re-render every enabled
+    /// animated light at the new time. Returns the re-rendered ids, ascending.
+    ///
+    /// Animations are pure functions of the clock, so engines that receive the
+    /// same lights and the same sequence of ticks render bit-identical
+    /// canvases. Dirty lights that are not animated are left for
+    /// [`Self::update_dirty_lights`].
+    pub fn tick(&mut self, dt_ms: u32) -> Vec<LightId> {
+        self.time_ms += dt_ms as u64;
+        let mut ids: Vec<LightId> = self
+            .lights
+            .iter()
+            .filter(|(_, l)| l.enabled() && l.animation().is_some())
+            .map(|(&id, _)| id)
+            .collect();
+        ids.sort_unstable();
+        for id in &ids {
+            let light = self.lights.get_mut(id).expect("collected above");
+            light.update(&self.collision, &self.all_rays, self.max_dist, self.time_ms);
+            self.dirty_lights.remove(id);
         }
-        let start = (x1 as usize) + (y1 as usize) * tpr;
-        let goal = (x2 as usize) + (y2 as usize) * tpr;
-        if start >= total || goal >= total {
-            return Vec::new();
+        ids
+    }
+
+    /// Milliseconds the engine clock has advanced through [`Self::tick`].
+    pub fn time_ms(&self) -> u64 {
+        self.time_ms
+    }
+
+    /// Make cell `(x, y)` glow, or stop it glowing with `None`. A cell setting
+    /// overrides its tile type's (see [`Self::set_emissive_tile_type`]).
+    pub fn set_emissive_cell(&mut self, x: u16, y: u16, emissive: Option<Emissive>) {
+        match emissive {
+            Some(e) => self.emissive_cells.insert((x, y), e),
+            None => self.emissive_cells.remove(&(x, y)),
+        };
+        self.emissive_dirty = true;
+    }
+
+    /// The glow set on cell `(x, y)` itself, if any.
+    pub fn emissive_cell(&self, x: u16, y: u16) -> Option<&Emissive> {
+        self.emissive_cells.get(&(x, y))
+    }
+
+    /// Make every cell of every tile of type `tile` glow, or stop it with
+    /// `None`. Follows the tile map: a [`Self::set_tile`] that changes a
+    /// tile's type moves the glow with it.
+    pub fn set_emissive_tile_type(&mut self, tile: u8, emissive: Option<Emissive>) {
+        match emissive {
+            Some(e) => self.emissive_tile_types.insert(tile, e),
+            None => self.emissive_tile_types.remove(&tile),
+        };
+        self.emissive_dirty = true;
+    }
+
+    /// The glow set on tile type `tile`, if any.
+    pub fn emissive_tile_type(&self, tile: u8) -> Option<&Emissive> {
+        self.emissive_tile_types.get(&tile)
+    }
+
+    /// Re-render the full-map emissive canvas if an emissive setting or a
+    /// world edit has made it stale, and return a pointer to it
+    /// (`cells_per_row²` RGBA cells). [`Self::compute_composite`] and
+    /// [`Self::update_dirty_lights`] call this themselves.
+    pub fn compute_emissive(&mut self) -> *const Color {
+        let cells_per_row = self.cells_per_row();
+        if self.emissive.is_none() {
+            self.emissive = Some(EmissiveLayer::new(cells_per_row));
+            self.emissive_dirty = true;
         }
-        if self.tile_at(start) <= 0 || self.tile_at(goal) <= 0 {
-            return Vec::new();
+        if self.emissive_dirty {
+            let sources = self.emissive_sources();
+            let layer = self.emissive.as_mut().expect("allocated above");
+            layer.render(
+                &sources,
+                &self.collision,
+                &self.all_rays,
+                self.max_dist,
+                self.color_space,
+            );
+            self.emissive_dirty = false;
         }
+        self.emissive
+            .as_ref()
+            .expect("allocated above")
+            .canvas()
+            .as_ptr()
+    }
 
-        let mut came_from: HashMap<usize, Option<usize>> = HashMap::new();
-        let mut frontier: std::collections::VecDeque<usize> = std::collections::VecDeque::new();
-        frontier.push_back(start);
-        came_from.insert(start, None);
-        let mut found = start == goal;
+    /// Borrow the most recently rendered emissive canvas, if
+    /// [`Self::compute_emissive`] has run at least once.
+    pub fn emissive_canvas(&self) -> Option<&[Color]> {
+        self.emissive.as_ref().map(|e| e.canvas())
+    }
 
-        while let Some(current) = frontier.pop_front() {
-            if current == goal {
-                found = true;
-                break;
-            }
-            for next in self.neighbours(current, false) {
-                if !came_from.contains_key(&next) && self.tile_at(next) > 0 {
-                    came_from.insert(next, Some(current));
-                    frontier.push_back(next);
+    /// Every glowing cell: the cells of emissive tile types, then the
+    /// per-cell settings on top.
+    fn emissive_sources(&self) -> HashMap<(i16, i16), Emissive> {
+        let mut sources = HashMap::new();
+        if !self.emissive_tile_types.is_empty() {
+            let cpt = self.cells_per_tile;
+            for (idx, tile) in self.tiles.iter().enumerate() {
+                let Some(&emissive) = self.emissive_tile_types.get(tile) else {
+                    continue;
+                };
+                let tx = (idx % self.tiles_per_row) * cpt;
+                let ty = (idx / self.tiles_per_row) * cpt;
+                for dy in 0..cpt {
+                    for dx in 0..cpt {
+                        sources.insert(((tx + dx) as i16, (ty + dy) as i16), emissive);
+                    }
                 }
             }
         }
+        for (&(x, y), &emissive) in &self.emissive_cells {
+            sources.insert((x as i16, y as i16), emissive);
+        }
+        sources
+    }
 
-        if !found {
-            return Vec::new();
+    /// Compute the live field-of-view mask for a set of viewer points and
+    /// return a pointer to the resulting full-map **FOV canvas**
+    /// (`cells_per_row²` RGBA cells in wasm linear memory).
+    ///
+    /// `viewers` is a flat array of viewer positions in cell coords
+    /// `[x0, y0, x1, y1, …]`; a trailing odd element (if any) is ignored. Each
+    /// viewer casts rays out to the ray table's max distance through the same
+    /// Room + Object collision as [`Light::update`] (minus colour and falloff);
+    /// every cell a ray reaches is marked opaque white `(255, 255, 255, 255)`
+    /// and everything else stays transparent `(0, 0, 0, 0)`. Results union
+    /// across viewers (marking is idempotent). An empty `viewers` array yields a
+    /// fully-transparent canvas.
+    ///
+    /// Pure compute: the engine stores no explored/fog memory (ADR-0006). The
+    /// returned canvas is overwritten on the next call.
+    pub fn compute_fov(&mut self, viewers: &[i16]) -> *const Color {
+        let cells_per_row = self.cells_per_row();
+        let max_dist = self.max_dist;
+
+        // Disjoint field borrows: `collision` + `all_rays` immutably, `fov`
+        // mutably. Bind each field directly so the borrow checker sees them as
+        // non-overlapping.
+        let collision = &self.collision;
+        let rays = &self.all_rays;
+        let fov = self.fov.get_or_insert_with(|| Fov::new(cells_per_row));
+
+        fov.clear();
+        for pair in viewers.chunks_exact(2) {
+            let pos = (pair[0], pair[1]);
+            trace_visible_cells_for(
+                Channel::Sight,
+                pos,
+                collision,
+                rays,
+                max_dist,
+                None,
+                |offset, _, _, _| {
+                    fov.mark(pos.0 + offset.0, pos.1 + offset.1);
+                },
+            );
         }
+        fov.canvas().as_ptr()
+    }
 
-        let mut points = Vec::new();
-        let mut current = goal;
-        while current != start {
-            points.push(current);
-            match came_from.get(&current) {
-                Some(Some(prev)) => current = *prev,
-                _ => return Vec::new(),
-            }
+    /// Borrow the most recently computed FOV canvas, if [`Self::compute_fov`]
+    /// has been called at least once.
+    pub fn fov_canvas(&self) -> Option<&[Color]> {
+        self.fov.as_ref().map(|f| f.canvas())
+    }
+
+    /// Select how [`Self::compute_composite`] combines overlapping layers.
+    pub fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.blend_mode = mode;
+    }
+
+    /// The blend mode [`Self::compute_composite`] currently uses.
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    /// Select the operator [`Self::compute_composite`] uses to map its HDR
+    /// accumulator down to 8-bit colour.
+    pub fn set_tone_map(&mut self, tone_map: ToneMap) {
+        self.tone_map = tone_map;
+    }
+
+    /// The tone-mapping operator [`Self::compute_composite`] currently uses.
+    pub fn tone_map(&self) -> ToneMap {
+        self.tone_map
+    }
+
+    /// Shade lights and blend layers in `space` from now on. Switching
+    /// re-renders every light straight away and the emissive canvas on its
+    /// next use. [`ColorSpace::Srgb`], the default, keeps the historical
+    /// output; [`ColorSpace::Linear`] is gamma-correct.
+    pub fn set_color_space(&mut self, space: ColorSpace) {
+        if space == self.color_space {
+            return;
+        }
+        self.color_space = space;
+        self.emissive_dirty = true;
+        for (id, light) in self.lights.iter_mut() {
+            light.set_color_space(space);
+            light.update(&self.collision, &self.all_rays, self.max_dist, self.time_ms);
+            self.dirty_lights.remove(id);
         }
-        points.push(start);
-        points.reverse();
-        points
     }
 
-    /// Tile-coord line-of-sight check. `true` if every step of the
-    /// Bresenham walk stays inside the same room, or — when crossing a
-    /// room boundary — that boundary has an open door registered between
-    /// the two tiles being stepped across. Door overlays are checked
-    /// per-step, never via union-find merges, so opening one door does
-    /// not silently dissolve the rest of the wall.
-    pub fn cast_ray(&mut self, x1: i32, y1: i32, x2: i32, y2: i32) -> bool {
-        let tpr = self.tiles_per_row as i32;
-        let in_bounds = |x: i32, y: i32| x >= 0 && y >= 0 && x < tpr && y < tpr;
-        if !in_bounds(x1, y1) || !in_bounds(x2, y2) {
-            return true;
+    /// The space lights are shaded and layers blended in.
+    pub fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+
+    /// Turn the single-bounce indirect pass of [`Self::compute_composite`] on
+    /// (`Some`) or off (`None`, the default). See [`Bounce`].
+    pub fn set_bounce(&mut self, bounce: Option<Bounce>) {
+        self.bounce = bounce;
+    }
+
+    /// The bounce setting [`Self::compute_composite`] currently uses.
+    pub fn bounce(&self) -> Option<Bounce> {
+        self.bounce
+    }
+
+    /// Composite every registered Ambient and Light into one full-map canvas
+    /// and return a pointer to it (`cells_per_row²` RGBA cells in wasm linear
+    /// memory).
+    ///
+    /// Each layer is blended with the engine's [`BlendMode`] in a fixed order:
+    /// ambients by ascending id, then the emissive canvas (re-rendered first
+    /// if stale), then lights by ascending id. A light's square
+    /// canvas is placed centred on its position; cells that fall off the map
+    /// are dropped. Blending happens in 16-bit fixed point (so intensities
+    /// above 1.0 survive overlaps) and the engine's [`ToneMap`] produces the
+    /// final colour. With [`Self::set_bounce`] on, one bounce of that direct
+    /// light is added per Room before tone mapping. Layers are read as last
+    /// rendered — this call does not re-trace any light. The canvas is
+    /// overwritten on the next call.
+    pub fn compute_composite(&mut self) -> *const Color {
+        let has_emissive = !self.emissive_cells.is_empty() || !self.emissive_tile_types.is_empty();
+        if has_emissive {
+            self.compute_emissive();
         }
-        let dx = x2 - x1;
-        let dy = y2 - y1;
-        let nx = dx.abs();
-        let ny = dy.abs();
-        let sx = if dx > 0 { 1 } else { -1 };
-        let sy = if dy > 0 { 1 } else { -1 };
-        let mut px = x1;
-        let mut py = y1;
-        let mut ix = 0;
-        let mut iy = 0;
-        let mut current_idx = (py * tpr + px) as usize;
-        let mut current_room = self.tile_uf.find(current_idx);
+        let bounce = self.bounce.map(|b| (b, self.bounce_surfaces()));
+        let cells_per_row = self.cells_per_row();
+        let mode = self.blend_mode;
+        let tone_map = self.tone_map;
+        let space = self.color_space;
+        let ambient_ids = self.ambient_ids();
+        let light_ids = self.light_ids();
+        let composite = self
+            .composite
+            .get_or_insert_with(|| Composite::new(cells_per_row));
+        composite.track_direct(bounce.is_some());
+        composite.clear();
 
-        while ix < nx || iy < ny {
-            let prev_idx = current_idx;
-            if (ix as f32 + 0.5) / (nx as f32) < (iy as f32 + 0.5) / (ny as f32) {
-                px += sx;
-                ix += 1;
-            } else {
-                py += sy;
-                iy += 1;
-            }
-            if !in_bounds(px, py) {
-                return true;
+        for id in ambient_ids {
+            let ambient = &self.ambients[&id];
+            if ambient.enabled() {
+                composite.blend_full_map(ambient.canvas(), mode, space);
             }
-            let next_idx = (py * tpr + px) as usize;
-            let next_room = self.tile_uf.find(next_idx);
-            if next_room != current_room && !self.has_open_door_between(prev_idx, next_idx) {
-                return false;
+        }
+
+        if let Some(emissive) = self.emissive.as_ref().filter(|_| has_emissive) {
+            composite.blend_full_map(emissive.canvas(), mode, space);
+        }
+
+        for id in light_ids {
+            let light = &self.lights[&id];
+            if !light.enabled() {
+                continue;
             }
-            current_idx = next_idx;
-            current_room = next_room;
+            let (origin, width, height) = light.canvas_rect();
+            composite.blend_rect(light.hdr_canvas(), (width, height), origin, mode);
         }
-        true
+
+        if let Some((bounce, (room_of, reflective))) = bounce {
+            composite.add_bounce(&room_of, &reflective, bounce);
+        }
+        composite.resolve(tone_map, space);
+        composite.canvas().as_ptr()
     }
 
-    /// Read-only access to the tile-resolution room id of `tile_idx`.
-    pub fn tile_find(&mut self, tile_idx: usize) -> usize {
-        self.tile_uf.find(tile_idx)
+    /// The light at cell `(cx, cy)`: every enabled ambient, the emissive
+    /// canvas and every enabled light blended there exactly as
+    /// [`Self::compute_composite`] would, without building the full-map
+    /// canvas. Layers are read as last rendered; the bounce fill, being a
+    /// whole-room pass, is not included. Off-map cells are dark.
+    pub fn light_at(&self, cx: i16, cy: i16) -> LightSample {
+        self.sample(self.hdr_at(cx, cy))
     }
 
-    /// Tile type at `tile_idx` (or `-1` for out-of-range).
-    pub fn tile_at(&self, tile_idx: usize) -> i32 {
-        if tile_idx < self.tiles.len() {
-            self.tiles[tile_idx] as i32
-        } else {
-            -1
+    /// The average light over the cells of tile `(tx, ty)`; see
+    /// [`Self::light_at`]. Off-map tiles are dark.
+    pub fn light_at_tile(&self, tx: i16, ty: i16) -> LightSample {
+        let tiles = self.tiles_per_row as i32;
+        if tx < 0 || ty < 0 || tx as i32 >= tiles || ty as i32 >= tiles {
+            return self.sample(Hdr::default());
+        }
+        let cpt = self.cells_per_tile as i32;
+        let (cx0, cy0) = (tx as i32 * cpt, ty as i32 * cpt);
+        let mut sum = [0u32; 4];
+        for dy in 0..cpt {
+            for dx in 0..cpt {
+                let hdr = self.hdr_at((cx0 + dx) as i16, (cy0 + dy) as i16);
+                for (total, v) in sum.iter_mut().zip([hdr.0, hdr.1, hdr.2, hdr.3]) {
+                    *total += v as u32;
+                }
+            }
         }
+        let mean = |total: u32| (total / (cpt as u32 * cpt as u32)) as u16;
+        self.sample(Hdr(mean(sum[0]), mean(sum[1]), mean(sum[2]), mean(sum[3])))
     }
 
-    /// 4- or 8-connected tile neighbours of `tile_idx` reachable in one
-    /// step: either they share a room (same `tile_uf` root) or an open
-    /// door is registered between this exact tile-pair. Diagonals are
-    /// reachable only if at least one of the two cardinal steps that lead
-    /// to the diagonal is itself reachable (no cutting corners through
-    /// closed walls).
-    pub fn neighbours(&mut self, tile_idx: usize, include_diagonal: bool) -> Vec<usize> {
+    /// [`Self::light_at`] for many cells at once.
+    pub fn light_at_cells(&self, cells: &[(i16, i16)]) -> Vec<LightSample> {
+        cells
+            .iter()
+            .map(|&(cx, cy)| self.light_at(cx, cy))
+            .collect()
+    }
+
+    /// Blend every layer at one cell, before tone mapping.
+    fn hdr_at(&self, cx: i16, cy: i16) -> Hdr {
+        let size = self.cells_per_row() as i16;
+        if cx < 0 || cy < 0 || cx >= size || cy >= size {
+            return Hdr::default();
+        }
+        let idx = cx as usize + cy as usize * size as usize;
+        let (mode, space) = (self.blend_mode, self.color_space);
+        let mut hdr = Hdr::default();
+        for id in self.ambient_ids() {
+            let ambient = &self.ambients[&id];
+            if ambient.enabled() {
+                hdr = mode.blend(hdr, space.lift(ambient.canvas()[idx]));
+            }
+        }
+        let has_emissive = !self.emissive_cells.is_empty() || !self.emissive_tile_types.is_empty();
+        if let Some(emissive) = self.emissive.as_ref().filter(|_| has_emissive) {
+            hdr = mode.blend(hdr, space.lift(emissive.canvas()[idx]));
+        }
+        for id in self.light_ids() {
+            let light = &self.lights[&id];
+            if let Some(lit) = light.hdr_at((cx, cy)).filter(|_| light.enabled()) {
+                hdr = mode.blend(hdr, lit);
+            }
+        }
+        hdr
+    }
+
+    fn sample(&self, hdr: Hdr) -> LightSample {
+        let color = self.color_space.lower(self.tone_map.curve(hdr));
+        LightSample {
+            color: (color.0, color.1, color.2),
+            level: formats::luma(color),
+            hdr,
+        }
+    }
+
+    /// Borrow the most recently computed composite canvas, if
+    /// [`Self::compute_composite`] has been called at least once.
+    pub fn composite_canvas(&self) -> Option<&[Color]> {
+        self.composite.as_ref().map(|c| c.canvas())
+    }
+
+    /// Borrow the HDR accumulator behind the most recent composite (before
+    /// tone mapping), in [`crate::lighting::HDR_ONE`] fixed point.
+    pub fn composite_hdr_canvas(&self) -> Option<&[Hdr]> {
+        self.composite.as_ref().map(|c| c.hdr_canvas())
+    }
+
+    /// Re-encode a rendered canvas as one byte of luminance per cell, into a
+    /// buffer reused across calls. `None` if the source does not exist (or,
+    /// for the full-map canvases, has never been computed). See
+    /// [`crate::formats`].
+    pub fn canvas_luma8(&mut self, source: CanvasSource) -> Option<&[u8]> {
+        let mut out = std::mem::take(&mut self.encoded.luma8);
+        let found = self
+            .source_canvas(source)
+            .map(|src| formats::encode_luma8(src, &mut out));
+        self.encoded.luma8 = out;
+        found.map(|_| self.encoded.luma8.as_slice())
+    }
+
+    /// Re-encode a rendered canvas as a packed bitset, one bit per cell set
+    /// where the cell is non-transparent — the natural form of the FOV mask.
+    pub fn canvas_bits(&mut self, source: CanvasSource) -> Option<&[u8]> {
+        let mut out = std::mem::take(&mut self.encoded.bits);
+        let found = self
+            .source_canvas(source)
+            .map(|src| formats::encode_bits(src, &mut out));
+        self.encoded.bits = out;
+        found.map(|_| self.encoded.bits.as_slice())
+    }
+
+    /// Re-encode a canvas as interleaved RGBA `u16` in
+    /// [`crate::lighting::HDR_ONE`] fixed point. Lights and the composite
+    /// export their unclamped HDR values; 8-bit canvases are lifted in the
+    /// engine's [`ColorSpace`].
+    pub fn canvas_rgba16(&mut self, source: CanvasSource) -> Option<&[u16]> {
+        let mut out = std::mem::take(&mut self.encoded.rgba16);
+        let found = self
+            .source_hdr(source)
+            .map(|src| formats::encode_rgba16(&src, &mut out));
+        self.encoded.rgba16 = out;
+        found.map(|_| self.encoded.rgba16.as_slice())
+    }
+
+    /// As [`Self::canvas_rgba16`], as `f32` with `1.0` = `HDR_ONE`.
+    pub fn canvas_f32(&mut self, source: CanvasSource) -> Option<&[f32]> {
+        let mut out = std::mem::take(&mut self.encoded.f32);
+        let found = self
+            .source_hdr(source)
+            .map(|src| formats::encode_f32(&src, &mut out));
+        self.encoded.f32 = out;
+        found.map(|_| self.encoded.f32.as_slice())
+    }
+
+    fn source_canvas(&self, source: CanvasSource) -> Option<&[Color]> {
+        match source {
+            CanvasSource::Light(id) => self.light_canvas(id),
+            CanvasSource::Ambient(id) => self.ambient_canvas(id),
+            CanvasSource::Emissive => self.emissive_canvas(),
+            CanvasSource::Fov => self.fov_canvas(),
+            CanvasSource::Composite => self.composite_canvas(),
+        }
+    }
+
+    fn source_hdr(&self, source: CanvasSource) -> Option<Cow<'_, [Hdr]>> {
+        match source {
+            CanvasSource::Light(id) => self.lights.get(&id).map(|l| Cow::Borrowed(l.hdr_canvas())),
+            CanvasSource::Composite => self.composite_hdr_canvas().map(Cow::Borrowed),
+            _ => self
+                .source_canvas(source)
+                .map(|cells| Cow::Owned(cells.iter().map(|&c| self.color_space.lift(c)).collect())),
+        }
+    }
+
+    /// Borrow a light's canvas if one with the given id exists.
+    pub fn light_canvas(&self, id: LightId) -> Option<&[Color]> {
+        self.lights.get(&id).map(|l| l.canvas())
+    }
+
+    /// Side length (in cells) of a light's square canvas, or `None` if the
+    /// id is unknown or the canvas is not square — segment and rectangle
+    /// emitters usually are not; see [`Self::light_canvas_rect`].
+    pub fn light_canvas_size(&self, id: LightId) -> Option<usize> {
+        let (_, width, height) = self.lights.get(&id)?.canvas_rect();
+        (width == height).then_some(width)
+    }
+
+    /// A light's canvas placement: the world cell of its top-left cell, its
+    /// width and its height. Point lights get a `(2r+1)²` square centred on
+    /// their position; shaped emitters get their bounding box grown by `r`.
+    pub fn light_canvas_rect(&self, id: LightId) -> Option<((i16, i16), usize, usize)> {
+        self.lights.get(&id).map(|l| l.canvas_rect())
+    }
+
+    /// A light's emitter geometry.
+    pub fn light_shape(&self, id: LightId) -> Option<LightShape> {
+        self.lights.get(&id).map(|l| l.shape())
+    }
+
+    /// Light position in cell coords.
+    pub fn light_position(&self, id: LightId) -> Option<(i16, i16)> {
+        self.lights.get(&id).map(|l| l.pos())
+    }
+
+    /// Light radius in cells.
+    pub fn light_radius(&self, id: LightId) -> Option<i16> {
+        self.lights.get(&id).map(|l| l.radius())
+    }
+
+    /// Light colour mode (`None` = rainbow).
+    pub fn light_color_mode(&self, id: LightId) -> Option<&Option<ColorMode>> {
+        self.lights.get(&id).map(|l| l.color_mode())
+    }
+
+    /// Spotlight cone, or `None` for an omnidirectional light.
+    pub fn light_cone(&self, id: LightId) -> Option<Option<&Cone>> {
+        self.lights.get(&id).map(|l| l.cone())
+    }
+
+    /// Radius of the light's emitting disc in cells (`0` = point source).
+    pub fn light_source_radius(&self, id: LightId) -> Option<u8> {
+        self.lights.get(&id).map(|l| l.source_radius())
+    }
+
+    /// Light intensity in 8.8 fixed point (`256` = 1.0).
+    pub fn light_intensity(&self, id: LightId) -> Option<u16> {
+        self.lights.get(&id).map(|l| l.intensity())
+    }
+
+    /// Light falloff curve.
+    pub fn light_falloff(&self, id: LightId) -> Option<&Falloff> {
+        self.lights.get(&id).map(|l| l.falloff())
+    }
+
+    /// Create or update light `id` at `(x, y)` with radius `r`, let
+    /// `configure` adjust whichever property the calling variant names, then
+    /// re-render it. Properties a variant does not name (e.g. the falloff when
+    /// `put_solid_color` is called) keep their current value, or their default
+    /// for a new light.
+    fn update_light_with<F>(
+        &mut self,
+        id: LightId,
+        r: i16,
+        x: i16,
+        y: i16,
+        configure: F,
+    ) -> *const Color
+    where
+        F: FnOnce(&mut Light),
+    {
+        let clamped_r = r.min(self.max_dist as i16).max(1);
+
+        // Disjoint borrows: `lights` mutably, `collision`+`all_rays` immutably.
+        let collision = &self.collision;
+        let all_rays = &self.all_rays;
+        let max_dist = self.max_dist;
+        let light = self
+            .lights
+            .entry(id)
+            .or_insert_with(|| Light::new((x, y), clamped_r, None));
+        light.set_state((x, y), clamped_r);
+        light.set_color_space(self.color_space);
+        configure(light);
+        self.dirty_lights.remove(&id);
+        light.update(collision, all_rays, max_dist, self.time_ms)
+    }
+
+    /// Mark every light whose canvas rect (grown by its source radius)
+    /// overlaps the inclusive cell rect `(x0, y0)..=(x1, y1)` as dirty.
+    /// The emissive layer spans the whole map, so any edit stales it, and so
+    /// does every ambient darkened by occlusion.
+    fn mark_cells_dirty(&mut self, x0: i32, y0: i32, x1: i32, y1: i32) {
+        self.emissive_dirty = true;
+        for (&id, ambient) in &self.ambients {
+            if ambient.occlusion().is_some() {
+                self.dirty_ambients.insert(id);
+            }
+        }
+        self.mark_lights_dirty_in(x0, y0, x1, y1);
+    }
+
+    fn place_object(&mut self, id: ObjectId, object: Object) {
+        let flipped = self.objects.insert(id, object, &mut self.collision);
+        self.mark_light_flips_dirty(&flipped);
+    }
+
+    /// [`Self::mark_cells_dirty`] over the bounding box of the
+    /// [`Channel::Light`] bits in `flipped`, if any. Sight and movement are
+    /// only queried on demand, so their flips leave nothing stale.
+    fn mark_light_flips_dirty(&mut self, flipped: &[(Channel, u16, u16)]) {
+        let mut light = flipped.iter().filter(|f| f.0 == Channel::Light);
+        let Some(&(_, x, y)) = light.next() else {
+            return;
+        };
+        let (x0, y0, x1, y1) = light.fold((x, y, x, y), |(x0, y0, x1, y1), &(_, x, y)| {
+            (x0.min(x), y0.min(y), x1.max(x), y1.max(y))
+        });
+        self.mark_cells_dirty(x0 as i32, y0 as i32, x1 as i32, y1 as i32);
+    }
+
+    /// The light half of [`Self::mark_cells_dirty`], for edits that cannot
+    /// affect ambients.
+    fn mark_lights_dirty_in(&mut self, x0: i32, y0: i32, x1: i32, y1: i32) {
+        for (&id, light) in &self.lights {
+            let ((ox, oy), width, height) = light.canvas_rect();
+            let grow = light.source_radius() as i32;
+            let (lx0, ly0) = (ox as i32 - grow, oy as i32 - grow);
+            let (lx1, ly1) = (
+                lx0 + width as i32 + 2 * grow - 1,
+                ly0 + height as i32 + 2 * grow - 1,
+            );
+            if lx1 >= x0 && lx0 <= x1 && ly1 >= y0 && ly0 <= y1 {
+                self.dirty_lights.insert(id);
+            }
+        }
+    }
+
+    /// Mark lights touching tile `tile_idx` dirty. The rect is grown by one
+    /// cell because a tile edit also moves the walls on its neighbours' side
+    /// of each shared edge.
+    fn mark_tile_dirty(&mut self, tile_idx: usize) {
+        let cpt = self.cells_per_tile as i32;
+        let tx = (tile_idx % self.tiles_per_row) as i32;
+        let ty = (tile_idx / self.tiles_per_row) as i32;
+        self.mark_cells_dirty(tx * cpt - 1, ty * cpt - 1, (tx + 1) * cpt, (ty + 1) * cpt);
+    }
+
+    /// Door changes re-route every ambient that spills through doors.
+    fn mark_spilling_ambients_dirty(&mut self) {
+        for (&id, ambient) in &self.ambients {
+            if ambient.spread() != AmbientSpread::Room {
+                self.dirty_ambients.insert(id);
+            }
+        }
+    }
+
+    fn mark_all_dirty(&mut self) {
+        self.emissive_dirty = true;
+        self.dirty_lights.extend(self.lights.keys().copied());
+        self.dirty_ambients.extend(self.ambients.keys().copied());
+    }
+
+    /// Per cell, the cell-level Room it belongs to — so the two halves of a
+    /// Diagonal-split tile stay apart — and whether it reflects light for
+    /// the bounce pass: it has a Wall edge or an Object 4-neighbour.
+    fn bounce_surfaces(&self) -> (Vec<Option<usize>>, Vec<bool>) {
+        let cells_per_row = self.cells_per_row();
+        let collision = &self.collision;
+        let object = |x: usize, y: usize| collision.is_occupied(x as u16, y as u16);
+        let mut room_of = Vec::with_capacity(cells_per_row * cells_per_row);
+        let mut reflective = Vec::with_capacity(cells_per_row * cells_per_row);
+        for y in 0..cells_per_row {
+            for x in 0..cells_per_row {
+                let edges = self.cells[y * cells_per_row + x];
+                let walled =
+                    edges.n_blocked || edges.e_blocked || edges.s_blocked || edges.w_blocked;
+                let near_object = (x > 0 && object(x - 1, y))
+                    || (x + 1 < cells_per_row && object(x + 1, y))
+                    || (y > 0 && object(x, y - 1))
+                    || (y + 1 < cells_per_row && object(x, y + 1));
+                let room = collision.cell_room(x as u16, y as u16);
+                room_of.push(room.filter(|_| !object(x, y)));
+                reflective.push(walled || near_object);
+            }
+        }
+        (room_of, reflective)
+    }
+
+    fn recompute_block_map(&mut self) {
+        let tiles_total = self.tiles.len();
+        for tile_index in 0..tiles_total {
+            compute_cell_details_for_tile(
+                tile_index,
+                &self.tiles,
+                &mut self.cells,
+                self.cells_per_tile,
+                self.tiles_per_row,
+            );
+        }
+        let cells_per_row = self.cells_per_row();
+        mark_wall_edges(&mut self.cells, &self.wall_edges, cells_per_row);
+        if !self.diagonals.is_empty() {
+            let cuts = self.diagonal_cell_edges();
+            mark_wall_edges(&mut self.cells, &cuts, cells_per_row);
+        }
+    }
+
+    /// [`Self::recompute_block_map`] for the cells of `tiles` only: after a
+    /// thin-wall edit, the flags elsewhere are unchanged.
+    fn recompute_block_map_tiles(&mut self, tiles: &BTreeSet<usize>) {
+        for &tile_index in tiles {
+            compute_cell_details_for_tile(
+                tile_index,
+                &self.tiles,
+                &mut self.cells,
+                self.cells_per_tile,
+                self.tiles_per_row,
+            );
+        }
+        let touches = |&(lo, hi): &(usize, usize)| {
+            tiles.contains(&self.cell_tile(lo)) || tiles.contains(&self.cell_tile(hi))
+        };
+        let mut edges: HashSet<(usize, usize)> =
+            self.wall_edges.iter().copied().filter(touches).collect();
+        for &tile_idx in tiles {
+            if let Some(&diagonal) = self.diagonals.get(&tile_idx) {
+                edges.extend(self.diagonal_cuts(tile_idx, diagonal));
+            }
+        }
+        let cells_per_row = self.cells_per_row();
+        mark_wall_edges(&mut self.cells, &edges, cells_per_row);
+    }
+
+    /// Index of the tile containing cell index `cell`.
+    fn cell_tile(&self, cell: usize) -> usize {
+        let cells_per_row = self.cells_per_row();
+        let (x, y) = (cell % cells_per_row, cell / cells_per_row);
+        (y / self.cells_per_tile) * self.tiles_per_row + x / self.cells_per_tile
+    }
+
+    /// Every cell edge cut by a diagonal wall, as canonical `(lo, hi)`
+    /// cell-index pairs.
+    fn diagonal_cell_edges(&self) -> HashSet<(usize, usize)> {
+        self.diagonals
+            .iter()
+            .flat_map(|(&tile_idx, &diagonal)| self.diagonal_cuts(tile_idx, diagonal))
+            .collect()
+    }
+
+    /// The cell edges `diagonal` cuts in tile `tile_idx`, as canonical
+    /// `(lo, hi)` cell-index pairs.
+    fn diagonal_cuts(
+        &self,
+        tile_idx: usize,
+        diagonal: Diagonal,
+    ) -> impl Iterator<Item = (usize, usize)> {
+        let cpt = self.cells_per_tile;
+        let cells_per_row = self.cells_per_row();
+        let cx0 = (tile_idx % self.tiles_per_row) * cpt;
+        let cy0 = (tile_idx / self.tiles_per_row) * cpt;
+        let cell = move |(x, y): (usize, usize)| (cy0 + y) * cells_per_row + cx0 + x;
+        diagonal
+            .cut_edges(cpt)
+            .into_iter()
+            .map(move |(a, b)| canonical_edge(cell(a), cell(b)))
+    }
+
+    /// Hand the thin walls and diagonal cuts to the collision detector.
+    fn publish_wall_cell_edges(&mut self) {
+        let mut edges = self.wall_edges.clone();
+        edges.extend(self.diagonal_cell_edges());
+        self.collision.set_wall_cell_edges(edges);
+    }
+
+    /// Add or remove thin-wall cell edges, then refresh the edge flags and
+    /// the collision overlay and mark what they touch dirty.
+    fn set_wall_cell_edges(&mut self, edges: Vec<(usize, usize)>, wall: bool) {
+        let changed: Vec<(usize, usize)> = edges
+            .into_iter()
+            .filter(|&pair| {
+                if wall {
+                    self.wall_edges.insert(pair)
+                } else {
+                    self.wall_edges.remove(&pair)
+                }
+            })
+            .collect();
+        if changed.is_empty() {
+            return;
+        }
+        for &pair in &changed {
+            // A diagonal cut on the same edge keeps it walled.
+            let cut = || {
+                let tile_idx = self.cell_tile(pair.0);
+                self.diagonals
+                    .get(&tile_idx)
+                    .is_some_and(|&d| self.diagonal_cuts(tile_idx, d).any(|c| c == pair))
+            };
+            if wall || !cut() {
+                self.collision.set_wall_cell_edge(pair, wall);
+            }
+        }
+        let tiles: BTreeSet<usize> = changed
+            .iter()
+            .flat_map(|&(lo, hi)| [self.cell_tile(lo), self.cell_tile(hi)])
+            .collect();
+        self.recompute_block_map_tiles(&tiles);
+        let cells_per_row = self.cells_per_row();
+        let (mut x0, mut y0, mut x1, mut y1) = (usize::MAX, usize::MAX, 0, 0);
+        for &(lo, hi) in &changed {
+            for cell in [lo, hi] {
+                let (x, y) = (cell % cells_per_row, cell / cells_per_row);
+                (x0, y0, x1, y1) = (x0.min(x), y0.min(y), x1.max(x), y1.max(y));
+            }
+        }
+        self.mark_cells_dirty(x0 as i32, y0 as i32, x1 as i32, y1 as i32);
+    }
+
+    /// Whether a thin wall runs along the whole boundary between adjacent
+    /// tiles `a` and `b`.
+    fn tile_edge_walled(&self, a: usize, b: usize) -> bool {
+        if self.wall_edges.is_empty() {
+            return false;
+        }
+        let mut pairs = self.tile_edge_pairs(a, b).peekable();
+        pairs.peek().is_some() && pairs.all(|pair| self.wall_edges.contains(&pair))
+    }
+
+    fn refresh_collision_from_tiles(&mut self) {
+        // The broad-phase room graph is queried in **cell coordinates** by
+        // [`crate::lighting::Light::update`] (ray walks are at cell resolution),
+        // so the union-find must be built at cell resolution as well. Expand
+        // the tile map: every cell inherits the type of its containing tile.
+        // Two cells share a room iff their tiles share a type AND are
+        // 4-connected via same-type tiles — the union-find's adjacency union
+        // takes care of that automatically, except across the edges a
+        // Diagonal cuts.
+        let cells_per_tile = self.cells_per_tile;
         let tiles_per_row = self.tiles_per_row;
-        let total = tiles_per_row * tiles_per_row;
-        if tile_idx >= total {
+        let cells_per_row = cells_per_tile * tiles_per_row;
+        let mut cell_map = vec![0i32; cells_per_row * cells_per_row];
+        for tile_y in 0..tiles_per_row {
+            for tile_x in 0..tiles_per_row {
+                let tile = self.tiles[tile_y * tiles_per_row + tile_x] as i32;
+                let cy0 = tile_y * cells_per_tile;
+                let cx0 = tile_x * cells_per_tile;
+                for dy in 0..cells_per_tile {
+                    let row = (cy0 + dy) * cells_per_row;
+                    for dx in 0..cells_per_tile {
+                        cell_map[row + cx0 + dx] = tile;
+                    }
+                }
+            }
+        }
+        let cuts = self.diagonal_cell_edges();
+        self.collision
+            .update_map_data_with_cuts(cell_map, cells_per_row, &cuts);
+        self.publish_door_cell_edges();
+        self.publish_wall_cell_edges();
+    }
+
+    /// Rebuild the tile-resolution room graph from the current tile map.
+    /// Doors are *not* unioned into the UF — a UF union closes transitively
+    /// and would dissolve the entire room boundary the moment one door
+    /// opened. Door connectivity is consulted edge-by-edge in `neighbours`
+    /// / `cast_ray` instead.
+    fn refresh_tile_uf_from_tiles(&mut self) {
+        let tiles: Vec<i32> = self.tiles.iter().map(|&t| t as i32).collect();
+        self.tile_uf = UnionFind::new(tiles, self.tiles_per_row);
+    }
+
+    /// Compute the cell-edge overlay corresponding to today's open door
+    /// tile-edges and hand it to the collision detector. Each door that
+    /// passes every channel (a pair of adjacent tiles) becomes
+    /// `cells_per_tile` cell-pair entries along the shared tile boundary;
+    /// the broad-phase walk consults the overlay only when it would
+    /// otherwise reject a step. Doors that pass only some channels, Windows,
+    /// and Walls whose tile types let a channel through, are published the
+    /// same way, per channel.
+    fn publish_door_cell_edges(&mut self) {
+        let mut edges: HashSet<(usize, usize)> = HashSet::new();
+        for (&(a, b), state) in &self.door_edges {
+            if state.channels() == Channels::ALL {
+                self.tile_edge_cells(a, b, &mut edges);
+            }
+        }
+        self.collision.set_door_cell_edges(edges);
+
+        let tiles_per_row = self.tiles_per_row;
+        for channel in Channel::ALL {
+            let mut edges: HashSet<(usize, usize)> = HashSet::new();
+            for (&(a, b), state) in &self.door_edges {
+                let channels = state.channels();
+                if channels != Channels::ALL && channels.contains(channel) {
+                    self.tile_edge_cells(a, b, &mut edges);
+                }
+            }
+            if WINDOW_CHANNELS.contains(channel) {
+                for &(a, b) in &self.window_edges {
+                    self.tile_edge_cells(a, b, &mut edges);
+                }
+            }
+            if !self.tile_type_channels.is_empty() {
+                for a in 0..self.tiles.len() {
+                    let east = (a % tiles_per_row + 1 < tiles_per_row).then_some(a + 1);
+                    let south = Some(a + tiles_per_row).filter(|&b| b < self.tiles.len());
+                    for b in east.into_iter().chain(south) {
+                        if self.wall_passes(a, b, channel) {
+                            self.tile_edge_cells(a, b, &mut edges);
+                        }
+                    }
+                }
+            }
+            self.collision.set_channel_cell_edges(channel, edges);
+        }
+    }
+
+    /// Add the `cells_per_tile` canonical cell pairs straddling the boundary
+    /// between adjacent tiles `a` and `b` to `edges`. Non-adjacent pairs add
+    /// nothing.
+    fn tile_edge_cells(&self, a: usize, b: usize, edges: &mut HashSet<(usize, usize)>) {
+        edges.extend(self.tile_edge_pairs(a, b));
+    }
+
+    /// The canonical cell pairs straddling the boundary between adjacent
+    /// tiles `a` and `b`, without allocating; none if they are not adjacent.
+    fn tile_edge_pairs(&self, a: usize, b: usize) -> impl Iterator<Item = (usize, usize)> {
+        let cells_per_tile = self.cells_per_tile;
+        let tiles_per_row = self.tiles_per_row;
+        let cells_per_row = cells_per_tile * tiles_per_row;
+        let (a_x, a_y) = (a % tiles_per_row, a / tiles_per_row);
+        let (b_x, b_y) = (b % tiles_per_row, b / tiles_per_row);
+        // First low cell, stride between low cells, and low-to-high offset.
+        let (count, start, stride, across) = if a_y == b_y && a_x.abs_diff(b_x) == 1 {
+            let cx_left = a_x.min(b_x) * cells_per_tile + cells_per_tile - 1;
+            let start = a_y * cells_per_tile * cells_per_row + cx_left;
+            (cells_per_tile, start, cells_per_row, 1)
+        } else if a_x == b_x && a_y.abs_diff(b_y) == 1 {
+            let cy_top = a_y.min(b_y) * cells_per_tile + cells_per_tile - 1;
+            let start = cy_top * cells_per_row + a_x * cells_per_tile;
+            (cells_per_tile, start, 1, cells_per_row)
+        } else {
+            (0, 0, 0, 0)
+        };
+        (0..count).map(move |k| {
+            let lo = start + k * stride;
+            (lo, lo + across)
+        })
+    }
+
+    /// Whether a door between adjacent tiles `a` and `b` lets `channel`
+    /// through when leaving `a`.
+    fn door_passes(&self, a: usize, b: usize, channel: Channel) -> bool {
+        door_passes(&self.door_edges, a, b, channel)
+    }
+
+    /// Whether the Wall between adjacent tiles `a` and `b` (if any) lets
+    /// `channel` through because of their types' channel masks.
+    fn wall_passes(&self, a: usize, b: usize, channel: Channel) -> bool {
+        wall_passes(
+            &self.tile_type_channels,
+            self.tiles[a],
+            self.tiles[b],
+            channel,
+        )
+    }
+
+    /// Tile-coord BFS pathfinder. Returns the chain of tile indices from
+    /// `(x1,y1)` to `(x2,y2)` inclusive, or empty if no route exists or
+    /// either endpoint is a wall tile. Walks `neighbours()` so it respects
+    /// the room graph (and door overlays that join rooms across boundaries)
+    /// on the [`Channel::Movement`] channel.
+    pub fn path(&mut self, x1: i32, y1: i32, x2: i32, y2: i32) -> Vec<usize> {
+        let tpr = self.tiles_per_row;
+        let total = tpr * tpr;
+        if x1 < 0 || y1 < 0 || x2 < 0 || y2 < 0 {
             return Vec::new();
         }
-        let row = tile_idx / tiles_per_row;
-        let col = tile_idx % tiles_per_row;
-        let room = self.tile_uf.find(tile_idx);
-        let mut out = Vec::with_capacity(if include_diagonal { 8 } else { 4 });
+        let start = (x1 as usize) + (y1 as usize) * tpr;
+        let goal = (x2 as usize) + (y2 as usize) * tpr;
+        if start >= total || goal >= total {
+            return Vec::new();
+        }
+        if self.tile_at(start) <= 0 || self.tile_at(goal) <= 0 {
+            return Vec::new();
+        }
+
+        let mut came_from: HashMap<usize, Option<usize>> = HashMap::new();
+        let mut frontier: std::collections::VecDeque<usize> = std::collections::VecDeque::new();
+        frontier.push_back(start);
+        came_from.insert(start, None);
+        let mut found = start == goal;
+
+        while let Some(current) = frontier.pop_front() {
+            if current == goal {
+                found = true;
+                break;
+            }
+            for next in self.neighbours(current, false) {
+                if !came_from.contains_key(&next) && self.tile_at(next) > 0 {
+                    came_from.insert(next, Some(current));
+                    frontier.push_back(next);
+                }
+            }
+        }
+
+        if !found {
+            return Vec::new();
+        }
+
+        let mut points = Vec::new();
+        let mut current = goal;
+        while current != start {
+            points.push(current);
+            match came_from.get(&current) {
+                Some(Some(prev)) => current = *prev,
+                _ => return Vec::new(),
+            }
+        }
+        points.push(start);
+        points.reverse();
+        points
+    }
+
+    /// Tile-coord line-of-sight check. `false` if a step crosses a tile
+    /// boundary walled off by thin walls; otherwise `true` if every step of the
+    /// Bresenham walk stays inside the same room, or — when crossing a
+    /// room boundary — that boundary has an open door registered between
+    /// the two tiles being stepped across, a window, or tile types that let
+    /// [`Channel::Sight`] through. Door overlays are checked
+    /// per-step, never via union-find merges, so opening one door does
+    /// not silently dissolve the rest of the wall.
+    pub fn cast_ray(&mut self, x1: i32, y1: i32, x2: i32, y2: i32) -> bool {
+        let tpr = self.tiles_per_row as i32;
+        let in_bounds = |x: i32, y: i32| x >= 0 && y >= 0 && x < tpr && y < tpr;
+        if !in_bounds(x1, y1) || !in_bounds(x2, y2) {
+            return true;
+        }
+        let dx = x2 - x1;
+        let dy = y2 - y1;
+        let nx = dx.abs();
+        let ny = dy.abs();
+        let sx = if dx > 0 { 1 } else { -1 };
+        let sy = if dy > 0 { 1 } else { -1 };
+        let mut px = x1;
+        let mut py = y1;
+        let mut ix = 0;
+        let mut iy = 0;
+        let mut current_idx = (py * tpr + px) as usize;
+        let mut current_room = self.tile_uf.find(current_idx);
+
+        while ix < nx || iy < ny {
+            let prev_idx = current_idx;
+            if (ix as f32 + 0.5) / (nx as f32) < (iy as f32 + 0.5) / (ny as f32) {
+                px += sx;
+                ix += 1;
+            } else {
+                py += sy;
+                iy += 1;
+            }
+            if !in_bounds(px, py) {
+                return true;
+            }
+            let next_idx = (py * tpr + px) as usize;
+            let next_room = self.tile_uf.find(next_idx);
+            if self.tile_edge_walled(prev_idx, next_idx) {
+                return false;
+            }
+            if next_room != current_room
+                && !self.door_passes(prev_idx, next_idx, Channel::Sight)
+                && !self
+                    .window_edges
+                    .contains(&canonical_edge(prev_idx, next_idx))
+                && !self.wall_passes(prev_idx, next_idx, Channel::Sight)
+            {
+                return false;
+            }
+            current_idx = next_idx;
+            current_room = next_room;
+        }
+        true
+    }
+
+    /// Read-only access to the tile-resolution room id of `tile_idx`.
+    pub fn tile_find(&mut self, tile_idx: usize) -> usize {
+        self.tile_uf.find(tile_idx)
+    }
+
+    /// Tile type at `tile_idx` (or `-1` for out-of-range).
+    pub fn tile_at(&self, tile_idx: usize) -> i32 {
+        if tile_idx < self.tiles.len() {
+            self.tiles[tile_idx] as i32
+        } else {
+            -1
+        }
+    }
+
+    /// 4- or 8-connected tile neighbours of `tile_idx` reachable in one
+    /// step: no thin wall runs along their whole boundary, and either they
+    /// share a room (same `tile_uf` root), a door registered between this
+    /// exact tile-pair lets [`Channel::Movement`] out of `tile_idx`, or
+    /// their tile types let it through. Diagonals are
+    /// reachable only if at least one of the two cardinal steps that lead
+    /// to the diagonal is itself reachable (no cutting corners through
+    /// closed walls).
+    pub fn neighbours(&mut self, tile_idx: usize, include_diagonal: bool) -> Vec<usize> {
+        self.neighbours_for(Channel::Movement, tile_idx, include_diagonal)
+    }
+
+    /// [`Self::neighbours`], with doors and tile types consulted for
+    /// `channel` instead of movement.
+    fn neighbours_for(
+        &mut self,
+        channel: Channel,
+        tile_idx: usize,
+        include_diagonal: bool,
+    ) -> Vec<usize> {
+        let tiles_per_row = self.tiles_per_row;
+        let total = tiles_per_row * tiles_per_row;
+        if tile_idx >= total {
+            return Vec::new();
+        }
+        let row = tile_idx / tiles_per_row;
+        let col = tile_idx % tiles_per_row;
+        let room = self.tile_uf.find(tile_idx);
+        let mut out = Vec::with_capacity(if include_diagonal { 8 } else { 4 });
+
+        let north = if row > 0 { Some(tile_idx - tiles_per_row) } else { None };
+        let south = if row + 1 < tiles_per_row { Some(tile_idx + tiles_per_row) } else { None };
+        let west = if col > 0 { Some(tile_idx - 1) } else { None };
+        let east = if col + 1 < tiles_per_row { Some(tile_idx + 1) } else { None };
+
+        let walled: Vec<usize> = [north, south, west, east]
+            .into_iter()
+            .flatten()
+            .filter(|&ni| self.tile_edge_walled(tile_idx, ni))
+            .collect();
+        let (tiles, masks, doors) = (&self.tiles, &self.tile_type_channels, &self.door_edges);
+        let reachable = |ni: Option<usize>, uf: &mut UnionFind| -> Option<usize> {
+            let ni = ni?;
+            if walled.contains(&ni) {
+                None
+            } else if uf.find(ni) == room
+                || door_passes(doors, tile_idx, ni, channel)
+                || wall_passes(masks, tiles[tile_idx], tiles[ni], channel)
+            {
+                Some(ni)
+            } else {
+                None
+            }
+        };
+
+        let n_ok = reachable(north, &mut self.tile_uf);
+        let s_ok = reachable(south, &mut self.tile_uf);
+        let w_ok = reachable(west, &mut self.tile_uf);
+        let e_ok = reachable(east, &mut self.tile_uf);
+
+        if let Some(i) = n_ok { out.push(i); }
+        if let Some(i) = w_ok { out.push(i); }
+        if let Some(i) = s_ok { out.push(i); }
+        if let Some(i) = e_ok { out.push(i); }
+
+        if include_diagonal {
+            let diag = |dr: i32, dc: i32| -> Option<usize> {
+                let nr = row as i32 + dr;
+                let nc = col as i32 + dc;
+                if nr < 0 || nc < 0 || nr >= tiles_per_row as i32 || nc >= tiles_per_row as i32 {
+                    return None;
+                }
+                Some(nr as usize * tiles_per_row + nc as usize)
+            };
+            let push_diag = |out: &mut Vec<usize>, idx: Option<usize>, gate_a: bool, gate_b: bool| {
+                if !(gate_a || gate_b) {
+                    return;
+                }
+                if let Some(d) = idx {
+                    out.push(d);
+                }
+            };
+            push_diag(&mut out, diag(-1, -1), n_ok.is_some(), w_ok.is_some());
+            push_diag(&mut out, diag(-1, 1), n_ok.is_some(), e_ok.is_some());
+            push_diag(&mut out, diag(1, -1), s_ok.is_some(), w_ok.is_some());
+            push_diag(&mut out, diag(1, 1), s_ok.is_some(), e_ok.is_some());
+        }
+
+        out
+    }
+}
+
+/// The channels a window edge lets through.
+const WINDOW_CHANNELS: Channels = Channels::LIGHT.union(Channels::SIGHT);
+
+/// Whether a Wall between tile types `a` and `b` lets `channel` through: it
+/// does unless both types block it. Same-type tiles have no Wall.
+fn wall_passes(masks: &HashMap<u8, Channels>, a: u8, b: u8, channel: Channel) -> bool {
+    let blocks = |tile| {
+        masks
+            .get(&tile)
+            .is_none_or(|m: &Channels| m.contains(channel))
+    };
+    a != b && !(blocks(a) && blocks(b))
+}
+
+/// Whether a door between adjacent tiles `a` and `b` in `doors` lets
+/// `channel` through when leaving `a`.
+fn door_passes(
+    doors: &HashMap<(usize, usize), DoorState>,
+    a: usize,
+    b: usize,
+    channel: Channel,
+) -> bool {
+    doors
+        .get(&canonical_edge(a, b))
+        .is_some_and(|door| door.passes(channel, a))
+}
+
+/// Canonicalise an unordered tile-index pair so `(a, b)` and `(b, a)` map
+/// to the same `HashSet` entry.
+fn canonical_edge(a: usize, b: usize) -> (usize, usize) {
+    if a <= b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+/// Default process-wide engine instance, used as a back-compat shim for the
+/// WASM/JS API. Rust callers should prefer constructing their own
+/// [`LightingEngine`] via [`LightingEngine::new`].
+pub static DEFAULT_ENGINE: Lazy<RwLock<LightingEngine>> =
+    Lazy::new(|| RwLock::new(LightingEngine::default()));
+
+/// Gradient used by [`LightingEngine::render_canvas_text`], from darkest to
+/// brightest. Ten characters → integer division by 255 maps cleanly.
+const ASCII_GRADIENT: &[u8; 10] = b" .:-=+*#%@";
+
+impl LightingEngine {
+    /// Render a light's canvas as a multiline ASCII matrix.
+    ///
+    /// One character per cell, one line per row, trailing `\n` after each
+    /// row. Returns `None` if no light with the given id exists.
+    ///
+    /// This is the agent-readable debug primitive used by the exploration
+    /// loop (printed unconditionally) and the regression loop (embedded in
+    /// panic messages on assertion failure) so a failing test produces
+    /// self-explanatory output.
+    pub fn render_canvas_text(&self, id: LightId) -> Option<String> {
+        let light = self.lights.get(&id)?;
+        let canvas = light.canvas();
+        let (_, width, height) = light.canvas_rect();
+        let mut out = String::with_capacity((width + 1) * height);
+        for row in 0..height {
+            for col in 0..width {
+                let pixel = canvas[row * width + col];
+                let brightness = pixel.0.max(pixel.1).max(pixel.2);
+                let idx = (brightness as usize * (ASCII_GRADIENT.len() - 1)) / 255;
+                out.push(ASCII_GRADIENT[idx] as char);
+            }
+            out.push('\n');
+        }
+        Some(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_canvas_text_is_none_for_missing_light() {
+        let engine = LightingEngine::new(6, 30);
+        assert!(engine.render_canvas_text(0).is_none());
+    }
+
+    #[test]
+    fn render_canvas_text_dimensions_match_canvas() {
+        let mut engine = LightingEngine::new(6, 30);
+        engine.update_or_add_light(1, 3, 5, 5);
+        let s = engine.render_canvas_text(1).expect("light exists");
+        let lines: Vec<&str> = s.lines().collect();
+        assert_eq!(lines.len(), 7, "radius-3 light renders 7 rows, got: {}\n{}", lines.len(), s);
+        for (i, line) in lines.iter().enumerate() {
+            assert_eq!(
+                line.chars().count(),
+                7,
+                "row {} expected 7 cols, got {}: {:?}",
+                i,
+                line.chars().count(),
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn render_canvas_text_center_brighter_than_corner() {
+        let mut engine = LightingEngine::new(6, 30);
+        engine.update_or_add_light(1, 3, 5, 5);
+        let s = engine.render_canvas_text(1).unwrap();
+        let lines: Vec<&str> = s.lines().collect();
+        let center = lines[3].chars().nth(3).unwrap();
+        let corner = lines[0].chars().nth(0).unwrap();
+        let g = std::str::from_utf8(ASCII_GRADIENT).unwrap();
+        let center_idx = g.find(center).unwrap_or_else(|| panic!("center {:?} not in gradient\n{}", center, s));
+        let corner_idx = g.find(corner).unwrap_or_else(|| panic!("corner {:?} not in gradient\n{}", corner, s));
+        assert!(
+            center_idx >= corner_idx,
+            "center {:?} (idx {}) should be at least as bright as corner {:?} (idx {}). Canvas:\n{}",
+            center, center_idx, corner, corner_idx, s
+        );
+    }
+
+    #[test]
+    fn independent_engines_do_not_share_lights() {
+        let mut a = LightingEngine::new(6, 30);
+        let b = LightingEngine::new(6, 30);
+        a.update_or_add_light(7, 2, 4, 4);
+        assert!(a.light_canvas(7).is_some());
+        assert!(b.light_canvas(7).is_none());
+    }
+
+    #[test]
+    fn set_tile_at_non_default_resolution_marks_cell_edges() {
+        // Engine with explicit non-default resolution (4 cells per tile, 5x5 tiles).
+        let mut engine = LightingEngine::new(4, 5);
+        // Empty world: all tiles type 0 → no internal boundaries.
+        // Flip tile (2,2) to type 1, leaving its neighbours type 0.
+        engine.set_tile(2, 2, 1);
+        let cells = engine.block_map();
+        let cells_per_row = engine.cells_per_row();
+        // Top-left cell of tile (2,2) sits at cell (8,8). With cells_per_tile=4
+        // its edges are at cell rows/cols 8..=11. The north edge should be
+        // blocked (different from tile above), as should the west edge.
+        let nw = cells[8 * cells_per_row + 8];
+        assert!(nw.n_blocked, "NW cell should have north edge blocked");
+        assert!(nw.w_blocked, "NW cell should have west edge blocked");
+        // Inner cell (9,9) sits inside tile (2,2) — no edges blocked.
+        let inner = cells[9 * cells_per_row + 9];
+        assert!(!inner.n_blocked && !inner.s_blocked && !inner.e_blocked && !inner.w_blocked);
+    }
+
+    #[test]
+    fn engine_exposes_resolution() {
+        let e = LightingEngine::new(6, 30);
+        assert_eq!(e.cells_per_tile(), 6);
+        assert_eq!(e.tiles_per_row(), 30);
+
+        let e2 = LightingEngine::new(9, 20);
+        assert_eq!(e2.cells_per_tile(), 9);
+        assert_eq!(e2.tiles_per_row(), 20);
+    }
+
+    #[test]
+    fn path_returns_indices_between_two_passable_tiles() {
+        let mut e = LightingEngine::new(2, 5);
+        e.set_tile_map(vec![1u8; 25]);
+        let p = e.path(0, 0, 4, 0);
+        assert!(!p.is_empty(), "expected non-empty path, got {:?}", p);
+        assert_eq!(p[0], 0, "path should start at start index");
+        assert_eq!(p[p.len() - 1], 4, "path should end at goal index");
+    }
+
+    #[test]
+    fn set_door_edge_records_canonical_pair() {
+        let mut e = LightingEngine::default();
+        assert!(e.door_edges().is_empty());
+
+        // open=true records the edge; the pair is order-insensitive.
+        e.set_door_edge(5, 7, true);
+        assert!(e.has_door_edge(5, 7));
+        assert!(e.has_door_edge(7, 5));
+        assert_eq!(e.door_edges().len(), 1);
+
+        // open=false removes it.
+        e.set_door_edge(7, 5, false);
+        assert!(!e.has_door_edge(5, 7));
+        assert!(e.door_edges().is_empty());
+    }
+
+    #[test]
+    fn closed_door_keeps_rooms_split_for_pathfinding() {
+        let mut e = LightingEngine::new(2, 5);
+        // Two rooms separated by a column of type-2 tiles at x=2.
+        let mut tiles = vec![1u8; 25];
+        for y in 0..5 {
+            tiles[y * 5 + 2] = 2;
+        }
+        e.set_tile_map(tiles);
+        // No door — different tile types ⇒ different rooms ⇒ no path.
+        assert!(
+            e.path(0, 1, 4, 1).is_empty(),
+            "expected empty path across closed wall"
+        );
+    }
+
+    #[test]
+    fn open_door_joins_rooms_for_pathfinding() {
+        let mut e = LightingEngine::new(2, 5);
+        let mut tiles = vec![1u8; 25];
+        for y in 0..5 {
+            tiles[y * 5 + 2] = 2;
+        }
+        e.set_tile_map(tiles);
+        // Open the door across the wall column at row 1.
+        // Tile (1,1) and tile (3,1) are non-adjacent so the door is between
+        // (1,1) and (2,1) and another between (2,1) and (3,1). But a single
+        // door usually bridges directly between the two passable tiles, so
+        // model it as: door between (1,1) and (3,1)? That isn't adjacent.
+        // Use two doors that each step into the wall tile, then out.
+        let idx = |x: usize, y: usize| -> usize { y * 5 + x };
+        e.set_door_edge(idx(1, 1), idx(2, 1), true);
+        e.set_door_edge(idx(2, 1), idx(3, 1), true);
+        let p = e.path(0, 1, 4, 1);
+        assert!(!p.is_empty(), "expected non-empty path through open door");
+        assert_eq!(p[0], idx(0, 1));
+        assert_eq!(p[p.len() - 1], idx(4, 1));
+    }
+
+    #[test]
+    fn closed_door_blocks_light_into_adjacent_room() {
+        // Two rooms separated by a column of wall tiles. Light placed inside
+        // the west room must not leak into the east room when no door is open.
+        let mut e = LightingEngine::new(4, 8);
+        let tpr = e.tiles_per_row();
+        let cpt = e.cells_per_tile();
+        let mut tiles = vec![1u8; tpr * tpr];
+        for y in 0..tpr {
+            tiles[y * tpr + tpr / 2] = 0; // wall tiles
+        }
+        e.set_tile_map(tiles);
+
+        let light_tx = tpr / 2 - 1;
+        let light_ty = tpr / 2;
+        let light_cx = (light_tx * cpt + cpt / 2) as i16;
+        let light_cy = (light_ty * cpt + cpt / 2) as i16;
+        e.update_or_add_light(1, (cpt * 4) as i16, light_cx, light_cy);
+
+        // Sample a cell deep inside the east room — must be dark.
+        let probe_cx = ((tpr / 2 + 1) * cpt + cpt / 2) as i16;
+        let probe_cy = light_cy;
+        let is_lit = !e.is_blocked(light_cx, light_cy, probe_cx, probe_cy);
+        assert!(!is_lit, "closed wall should block ray to east room");
+    }
+
+    #[test]
+    fn open_door_lets_light_cross_into_adjacent_room() {
+        let mut e = LightingEngine::new(4, 8);
+        let tpr = e.tiles_per_row();
+        let cpt = e.cells_per_tile();
+        let mut tiles = vec![1u8; tpr * tpr];
+        for y in 0..tpr {
+            tiles[y * tpr + tpr / 2] = 0;
+        }
+        e.set_tile_map(tiles);
+
+        let light_ty = tpr / 2;
+        let light_tx = tpr / 2 - 1;
+        let wall_tx = tpr / 2;
+        let east_tx = tpr / 2 + 1;
+        e.set_door_edge(
+            light_ty * tpr + light_tx,
+            light_ty * tpr + wall_tx,
+            true,
+        );
+        e.set_door_edge(
+            light_ty * tpr + wall_tx,
+            light_ty * tpr + east_tx,
+            true,
+        );
+
+        let light_cx = (light_tx * cpt + cpt / 2) as i16;
+        let light_cy = (light_ty * cpt + cpt / 2) as i16;
+        // Probe a cell one cell east of the door's east boundary so it sits
+        // squarely inside the east room.
+        let probe_cx = (east_tx * cpt + cpt / 2) as i16;
+        let probe_cy = light_cy;
+        assert!(
+            !e.is_blocked(light_cx, light_cy, probe_cx, probe_cy),
+            "open door should join rooms — ray must not be blocked"
+        );
+    }
+
+    #[test]
+    fn open_door_does_not_dissolve_rest_of_wall_for_lighting() {
+        // Two rooms of different tile types share a horizontal boundary. A
+        // single door opens between (3,3) and (3,4). Rays crossing the
+        // boundary elsewhere (e.g. through (1,3)→(1,4)) must remain blocked.
+        let mut e = LightingEngine::new(4, 8);
+        let tpr = e.tiles_per_row();
+        let cpt = e.cells_per_tile();
+        let mut tiles = vec![0u8; tpr * tpr];
+        for y in 0..tpr {
+            for x in 0..tpr {
+                tiles[y * tpr + x] = if y < 4 { 1 } else { 2 };
+            }
+        }
+        e.set_tile_map(tiles);
+        // Door joins exactly tile (3,3) and (3,4).
+        e.set_door_edge(3 * tpr + 3, 4 * tpr + 3, true);
+
+        // Probe across the boundary at a column far from the door (col 1).
+        // Cell coords: x=1*cpt+cpt/2, y just above and just below the boundary.
+        let cx = (1 * cpt + cpt / 2) as i16;
+        let cy_upper = (3 * cpt + cpt / 2) as i16;
+        let cy_lower = (4 * cpt + cpt / 2) as i16;
+        assert!(
+            e.is_blocked(cx, cy_upper, cx, cy_lower),
+            "ray crossing the boundary far from the door must stay blocked"
+        );
+    }
+
+    #[test]
+    fn open_door_does_not_dissolve_rest_of_wall_for_pathfinding() {
+        // Same shape as the lighting test: one door at column 3 must not let
+        // pathfinding cross the boundary at column 1.
+        let mut e = LightingEngine::new(2, 8);
+        let tpr = e.tiles_per_row();
+        let mut tiles = vec![0u8; tpr * tpr];
+        for y in 0..tpr {
+            for x in 0..tpr {
+                tiles[y * tpr + x] = if y < 4 { 1 } else { 2 };
+            }
+        }
+        e.set_tile_map(tiles);
+        e.set_door_edge(3 * tpr + 3, 4 * tpr + 3, true);
+
+        // Path from (1, 3) to (1, 4) — different columns from the door —
+        // would have to detour through (3, 3)→(3, 4) (the door) and back.
+        // We don't assert path length, only that the path stays *off* the
+        // boundary at non-door columns. Easier: assert that the boundary at
+        // col 1 is NOT itself traversable as a direct step.
+        let row3_col1 = 3 * tpr + 1;
+        let row4_col1 = 4 * tpr + 1;
+        assert!(
+            !e.neighbours(row3_col1, false).contains(&row4_col1),
+            "non-door boundary must not be a neighbour"
+        );
+    }
+
+    // --- Ambient emitter ---------------------------------------------------
+
+    /// Helper: is the cell at the centre of tile `(tx, ty)` opaque (in-room)?
+    fn ambient_cell_opaque(e: &LightingEngine, id: LightId, tx: usize, ty: usize) -> bool {
+        let cpt = e.cells_per_tile();
+        let cpr = e.cells_per_row();
+        let cx = tx * cpt + cpt / 2;
+        let cy = ty * cpt + cpt / 2;
+        let canvas = e.ambient_canvas(id).expect("ambient exists");
+        canvas[cy * cpr + cx].3 != 0
+    }
+
+    #[test]
+    fn ambient_floods_only_its_room() {
+        // Two rooms separated by a column of wall tiles (type 0) at x=2.
+        let mut e = LightingEngine::new(2, 5);
+        let mut tiles = vec![1u8; 25];
+        for y in 0..5 {
+            tiles[y * 5 + 2] = 0; // wall column
+        }
+        e.set_tile_map(tiles);
+
+        // Emitter in the west room (tile (1,1)).
+        e.update_or_add_ambient(0, 1, 1, 140, 130, 120);
+
+        // West-room cells are filled with the authored colour...
+        let cpr = e.cells_per_row();
+        let cpt = e.cells_per_tile();
+        let west = e.ambient_canvas(0).unwrap()[(1 * cpt + 1) * cpr + (1 * cpt + 1)];
+        assert_eq!((west.0, west.1, west.2, west.3), (140, 130, 120, 255));
+        assert!(ambient_cell_opaque(&e, 0, 0, 0), "same-room tile filled");
+
+        // ...east-room cells (x>=3) stay transparent.
+        assert!(!ambient_cell_opaque(&e, 0, 3, 1), "east room must be dark");
+        assert!(!ambient_cell_opaque(&e, 0, 4, 0), "east room must be dark");
+        // Wall column itself stays transparent.
+        assert!(!ambient_cell_opaque(&e, 0, 2, 1), "wall tile must be dark");
+    }
+
+    #[test]
+    fn ambient_does_not_cross_door_open_or_closed() {
+        // Two same-... no: two rooms of different tile types share a vertical
+        // boundary at column x=2 (west type 1, east type 2). A door joins them
+        // for pathfinding/lighting, but ambient is `tile_uf`-bounded and must
+        // not cross regardless of door state.
+        let mut e = LightingEngine::new(2, 5);
+        let mut tiles = vec![1u8; 25];
+        for y in 0..5 {
+            for x in 2..5 {
+                tiles[y * 5 + x] = 2;
+            }
+        }
+        e.set_tile_map(tiles);
+
+        // Closed door: east room dark.
+        e.update_or_add_ambient(0, 1, 1, 100, 100, 100);
+        assert!(ambient_cell_opaque(&e, 0, 1, 1), "west room filled");
+        assert!(!ambient_cell_opaque(&e, 0, 3, 1), "closed door: east dark");
+
+        // Open the door between (1,1) and (2,1); re-flood. Still east dark.
+        e.set_door_edge(1 * 5 + 1, 1 * 5 + 2, true);
+        e.update_or_add_ambient(0, 1, 1, 100, 100, 100);
+        assert!(ambient_cell_opaque(&e, 0, 1, 1), "west room still filled");
+        assert!(
+            !ambient_cell_opaque(&e, 0, 3, 1),
+            "open door must NOT let ambient cross (tile_uf is door-agnostic)"
+        );
+    }
+
+    #[test]
+    fn ambient_on_non_floor_tile_is_empty() {
+        let mut e = LightingEngine::new(2, 5);
+        // Whole map is floor except tile (1,1) which is a wall (type 0).
+        let mut tiles = vec![1u8; 25];
+        tiles[1 * 5 + 1] = 0;
+        e.set_tile_map(tiles);
+
+        e.update_or_add_ambient(0, 1, 1, 200, 50, 50);
+        let canvas = e.ambient_canvas(0).unwrap();
+        assert!(
+            canvas.iter().all(|c| c.3 == 0),
+            "emitter on a non-floor tile emits a fully transparent canvas"
+        );
+    }
+
+    #[test]
+    fn ambient_out_of_range_is_empty() {
+        let mut e = LightingEngine::new(2, 5);
+        e.set_tile_map(vec![1u8; 25]);
+        e.update_or_add_ambient(0, -1, 99, 10, 20, 30);
+        let canvas = e.ambient_canvas(0).unwrap();
+        assert!(canvas.iter().all(|c| c.3 == 0), "out-of-range → empty canvas");
+    }
+
+    #[test]
+    fn two_ambient_emitters_fill_their_respective_rooms() {
+        // Two rooms split by a wall column at x=2; an emitter in each.
+        let mut e = LightingEngine::new(2, 5);
+        let mut tiles = vec![1u8; 25];
+        for y in 0..5 {
+            tiles[y * 5 + 2] = 0;
+        }
+        e.set_tile_map(tiles);
+
+        e.update_or_add_ambient(0, 1, 1, 100, 0, 0); // west
+        e.update_or_add_ambient(1, 3, 1, 0, 0, 100); // east
+
+        assert!(ambient_cell_opaque(&e, 0, 1, 1) && !ambient_cell_opaque(&e, 0, 3, 1));
+        assert!(ambient_cell_opaque(&e, 1, 3, 1) && !ambient_cell_opaque(&e, 1, 1, 1));
+    }
+
+    #[test]
+    fn ambient_reflood_tracks_room_after_tile_edit() {
+        // An emitter floods the whole one-room map; painting a wall that splits
+        // the room (and re-flooding) leaves only the emitter's half lit.
+        let mut e = LightingEngine::new(2, 5);
+        e.set_tile_map(vec![1u8; 25]);
+        e.update_or_add_ambient(0, 0, 1, 80, 80, 80);
+        assert!(ambient_cell_opaque(&e, 0, 4, 1), "single room: far tile lit");
+
+        // Split with a wall column at x=2.
+        for y in 0..5 {
+            e.set_tile(2, y as u32, 0);
+        }
+        e.update_or_add_ambient(0, 0, 1, 80, 80, 80);
+        assert!(ambient_cell_opaque(&e, 0, 0, 1), "emitter half stays lit");
+        assert!(!ambient_cell_opaque(&e, 0, 4, 1), "far half now dark after split");
+    }
+
+    // --- FOV canvas --------------------------------------------------------
+
+    /// Helper: is cell `(cx, cy)` opaque (visible) in the last FOV canvas?
+    fn fov_visible(e: &LightingEngine, cx: usize, cy: usize) -> bool {
+        let cpr = e.cells_per_row();
+        e.fov_canvas().expect("compute_fov was called")[cy * cpr + cx].3 != 0
+    }
+
+    /// Helper: count visible cells whose `cx` falls in `[cx_lo, cx_hi)`.
+    /// The low-res test ray table (36 angles / dist 10) paints a holey disc, so
+    /// region counts are robust where single-cell sampling is flaky.
+    fn fov_count_in_columns(e: &LightingEngine, cx_lo: usize, cx_hi: usize) -> usize {
+        let cpr = e.cells_per_row();
+        let canvas = e.fov_canvas().expect("compute_fov was called");
+        let mut n = 0;
+        for cy in 0..cpr {
+            for cx in cx_lo..cx_hi {
+                if canvas[cy * cpr + cx].3 != 0 {
+                    n += 1;
+                }
+            }
+        }
+        n
+    }
+
+    #[test]
+    fn fov_single_viewer_in_open_room() {
+        // Empty world = one big room, no walls. A viewer sees its own cell and a
+        // fan of nearby cells, but nothing past the ray table's max distance.
+        let mut e = LightingEngine::new(2, 30);
+        e.compute_fov(&[20, 20]);
+        let cpr = e.cells_per_row();
+        // The viewer's own cell is opaque white — binary alpha, no falloff.
+        let here = e.fov_canvas().expect("computed")[20 * cpr + 20];
+        assert_eq!((here.0, here.1, here.2, here.3), (255, 255, 255, 255));
+        // Rays fan out, so many cells around the viewer are lit.
+        assert!(
+            fov_count_in_columns(&e, 0, cpr) > 20,
+            "an open-room viewer lights a fan of cells"
+        );
+        // Sight is capped at the ray table's max distance (10 cells in test
+        // builds): a cell well beyond it stays dark.
+        assert!(!fov_visible(&e, 20, 35), "a far cell is out of sight");
+    }
+
+    #[test]
+    fn fov_blocked_by_wall() {
+        // Two rooms split by a wall column (type 0) at tile x=4; rest type 1.
+        // Without the wall the viewer would see east-room cells; the wall must
+        // occlude every one of them.
+        let mut e = LightingEngine::new(4, 8);
+        let tpr = e.tiles_per_row();
+        let cpt = e.cells_per_tile();
+        let wall_cx = (tpr / 2) * cpt; // first east-of-wall cell column
+        let vx = (3 * cpt + cpt / 2) as i16;
+        let vy = (4 * cpt + cpt / 2) as i16;
+        let cpr = e.cells_per_row();
+
+        // Baseline: open room, viewer sees across into the (future) east columns.
+        e.set_tile_map(vec![1u8; tpr * tpr]);
+        e.compute_fov(&[vx, vy]);
+        assert!(
+            fov_count_in_columns(&e, wall_cx, cpr) > 0,
+            "without a wall the viewer sees east-of-boundary cells"
+        );
+
+        // Now raise the wall column and re-cast: the east room goes fully dark.
+        let mut tiles = vec![1u8; tpr * tpr];
+        for y in 0..tpr {
+            tiles[y * tpr + tpr / 2] = 0;
+        }
+        e.set_tile_map(tiles);
+        e.compute_fov(&[vx, vy]);
+        assert!(fov_visible(&e, vx as usize, vy as usize), "viewer cell still lit");
+        assert_eq!(
+            fov_count_in_columns(&e, wall_cx, cpr),
+            0,
+            "wall must occlude all sight into the east room"
+        );
+    }
+
+    #[test]
+    fn fov_blocked_without_door_visible_with_open_door() {
+        // West tiles type 1 (x<4), east tiles type 2 (x>=4): a room boundary at
+        // the tile-3 / tile-4 edge. A viewer in the west reaches the east room
+        // only when a door is open across that exact edge.
+        let mut e = LightingEngine::new(4, 8);
+        let tpr = e.tiles_per_row();
+        let cpt = e.cells_per_tile();
+        let mut tiles = vec![1u8; tpr * tpr];
+        for y in 0..tpr {
+            for x in (tpr / 2)..tpr {
+                tiles[y * tpr + x] = 2;
+            }
+        }
+        e.set_tile_map(tiles);
+
+        let vx = (3 * cpt + cpt / 2) as i16;
+        let vy = (4 * cpt + cpt / 2) as i16;
+        let east_cx = (tpr / 2) * cpt; // first east-room cell column
+        let cpr = e.cells_per_row();
+
+        // No door: the boundary occludes sight into the east room.
+        e.compute_fov(&[vx, vy]);
+        assert_eq!(
+            fov_count_in_columns(&e, east_cx, cpr),
+            0,
+            "closed boundary blocks all sight into the east room"
+        );
+
+        // Open the door between tile (3,4) and (4,4): sight crosses there.
+        e.set_door_edge(4 * tpr + 3, 4 * tpr + 4, true);
+        e.compute_fov(&[vx, vy]);
+        assert!(
+            fov_count_in_columns(&e, east_cx, cpr) > 0,
+            "open door lets sight cross into the east room"
+        );
+    }
+
+    #[test]
+    fn fov_multi_viewer_union() {
+        // Two viewers far enough apart that neither alone covers the other. The
+        // single FOV canvas is the union — both viewer cells are lit at once.
+        let mut e = LightingEngine::new(2, 30);
+        e.compute_fov(&[10, 10, 50, 50]);
+        assert!(fov_visible(&e, 10, 10), "viewer A's cell visible");
+        assert!(fov_visible(&e, 50, 50), "viewer B's cell visible");
+        // A cell far from both viewers stays dark (out of range of either).
+        assert!(!fov_visible(&e, 30, 30), "midpoint out of both viewers' range");
+    }
+
+    #[test]
+    fn fov_empty_viewers_is_fully_transparent() {
+        let mut e = LightingEngine::new(2, 30);
+        e.compute_fov(&[]);
+        let canvas = e.fov_canvas().expect("computed");
+        assert!(
+            canvas.iter().all(|c| c.3 == 0),
+            "no viewers → fully transparent canvas"
+        );
+    }
+
+    // --- Falloff -----------------------------------------------------------
+
+    /// Brightness (max channel) of the canvas cell `dx` cells east of centre.
+    fn brightness_east_of_centre(e: &LightingEngine, id: LightId, dx: usize) -> u8 {
+        let side = e.light_canvas_size(id).unwrap();
+        let c = e.light_canvas(id).unwrap()[(side / 2) * side + side / 2 + dx];
+        c.0.max(c.1).max(c.2)
+    }
+
+    #[test]
+    fn falloff_curves_peak_at_centre_and_never_increase() {
+        let curves = [
+            Falloff::Linear,
+            Falloff::Quadratic,
+            Falloff::InverseSquare,
+            Falloff::Smoothstep,
+            Falloff::FlatCore(128),
+        ];
+        for curve in curves {
+            let table = curve.table();
+            assert_eq!(table[0], 255, "{:?} must peak at the centre", curve);
+            assert!(
+                table.windows(2).all(|w| w[0] >= w[1]),
+                "{:?} must be non-increasing",
+                curve
+            );
+        }
+    }
+
+    #[test]
+    fn linear_falloff_matches_historical_formula() {
+        let table = Falloff::Linear.table();
+        for (t, &v) in table.iter().enumerate() {
+            assert_eq!(v as usize, 255 - t);
+        }
+    }
+
+    #[test]
+    fn flat_core_is_brighter_than_linear_mid_radius() {
+        let mut e = LightingEngine::new(2, 30);
+        e.update_or_add_light_with_solid_color(1, 8, 20, 20, 0);
+        let linear = brightness_east_of_centre(&e, 1, 4);
+        e.update_or_add_light_with_falloff(1, 8, 20, 20, Falloff::FlatCore(192));
+        let flat = brightness_east_of_centre(&e, 1, 4);
+        assert_eq!(flat, 255, "mid-radius cell sits inside the flat core");
+        assert!(
+            linear < flat,
+            "linear={linear} should be dimmer than flat={flat}"
+        );
+    }
+
+    #[test]
+    fn falloff_table_drives_brightness_and_survives_colour_changes() {
+        let mut e = LightingEngine::new(2, 30);
+        let table = Falloff::from_table(&[100u8; 256]).expect("256 entries");
+        e.update_or_add_light_with_falloff(1, 6, 20, 20, table.clone());
+        assert_eq!(brightness_east_of_centre(&e, 1, 3), 100);
+
+        // Re-putting with a colour keeps the authored falloff.
+        e.update_or_add_light_with_solid_color(1, 6, 20, 20, 0);
+        assert_eq!(e.light_falloff(1), Some(&table));
+        assert_eq!(brightness_east_of_centre(&e, 1, 3), 100);
+    }
+
+    #[test]
+    fn falloff_table_requires_256_entries() {
+        assert!(Falloff::from_table(&[0u8; 255]).is_none());
+        assert!(Falloff::from_table(&[0u8; 257]).is_none());
+    }
+
+    // --- Exact colour modes ------------------------------------------------
+
+    #[test]
+    fn rgb_light_centre_is_exact_authored_colour() {
+        let mut e = LightingEngine::new(2, 30);
+        e.update_or_add_light_with_rgb(1, 5, 20, 20, 255, 147, 41);
+        let side = e.light_canvas_size(1).unwrap();
+        let c = e.light_canvas(1).unwrap()[(side / 2) * side + side / 2];
+        assert_eq!((c.0, c.1, c.2, c.3), (255, 147, 41, 255));
+        assert_eq!(
+            e.light_color_mode(1),
+            Some(&Some(ColorMode::Rgb(255, 147, 41)))
+        );
+    }
+
+    #[test]
+    fn kelvin_light_is_warm_at_low_and_cool_at_high_temperature() {
+        let mut e = LightingEngine::new(2, 30);
+        let centre = |e: &LightingEngine| {
+            let side = e.light_canvas_size(1).unwrap();
+            e.light_canvas(1).unwrap()[(side / 2) * side + side / 2]
+        };
+        e.update_or_add_light_with_kelvin(1, 5, 20, 20, 1900);
+        let candle = centre(&e);
+        assert!(
+            candle.0 > candle.2,
+            "candle should be red-heavy: {:?}",
+            candle
+        );
+        e.update_or_add_light_with_kelvin(1, 5, 20, 20, 10000);
+        let sky = centre(&e);
+        assert!(sky.2 > sky.0, "10000 K should be blue-heavy: {:?}", sky);
+    }
+
+    #[test]
+    fn kelvin_conversion_hits_table_samples_and_clamps() {
+        use crate::lighting::kelvin2rgb;
+        assert_eq!(kelvin2rgb(6500), (255, 249, 253));
+        assert_eq!(kelvin2rgb(500), kelvin2rgb(1000));
+        assert_eq!(kelvin2rgb(40000), kelvin2rgb(12000));
+        // Halfway between two samples interpolates each channel.
+        let (_, g, _) = kelvin2rgb(1250);
+        assert_eq!(g, (56 + 109) / 2);
+    }
+
+    // --- Spotlights --------------------------------------------------------
+
+    /// Number of lit cells in the light's canvas east / west of its centre
+    /// column.
+    fn lit_east_west(e: &LightingEngine, id: LightId) -> (usize, usize) {
+        let side = e.light_canvas_size(id).unwrap();
+        let canvas = e.light_canvas(id).unwrap();
+        let (mut east, mut west) = (0, 0);
+        for row in 0..side {
+            for col in 0..side {
+                if canvas[row * side + col].3 == 0 {
+                    continue;
+                }
+                if col > side / 2 {
+                    east += 1;
+                } else if col < side / 2 {
+                    west += 1;
+                }
+            }
+        }
+        (east, west)
+    }
+
+    #[test]
+    fn spotlight_only_lights_its_cone() {
+        let mut e = LightingEngine::new(2, 30);
+        let cone = Cone {
+            heading: 0,
+            half_width: 30,
+            soft_edge: 0,
+        };
+        e.update_or_add_light_with_cone(1, 8, 30, 30, Some(cone));
+        let (east, west) = lit_east_west(&e, 1);
+        assert!(east > 0, "east-facing spotlight lights cells to the east");
+        assert_eq!(west, 0, "nothing behind the spotlight is lit");
+        let side = e.light_canvas_size(1).unwrap();
+        assert_ne!(
+            e.light_canvas(1).unwrap()[(side / 2) * side + side / 2].3,
+            0,
+            "origin lit"
+        );
+
+        // Clearing the cone restores an omnidirectional light.
+        e.update_or_add_light_with_cone(1, 8, 30, 30, None);
+        let (_, west) = lit_east_west(&e, 1);
+        assert!(west > 0);
+    }
+
+    #[test]
+    fn spotlight_heading_follows_ray_table_convention() {
+        // 90° points +y (screen-down / south).
+        let mut e = LightingEngine::new(2, 30);
+        let cone = Cone {
+            heading: 90,
+            half_width: 20,
+            soft_edge: 0,
+        };
+        e.update_or_add_light_with_cone(1, 8, 30, 30, Some(cone));
+        let side = e.light_canvas_size(1).unwrap();
+        let canvas = e.light_canvas(1).unwrap();
+        let lit_rows = |rows: std::ops::Range<usize>| {
+            rows.flat_map(|row| (0..side).map(move |col| row * side + col))
+                .filter(|&i| canvas[i].3 != 0)
+                .count()
+        };
+        assert!(lit_rows(side / 2 + 1..side) > 0);
+        assert_eq!(lit_rows(0..side / 2), 0);
+    }
+
+    #[test]
+    fn spotlight_soft_edge_dims_towards_boundary() {
+        let cone = Cone {
+            heading: 0,
+            half_width: 40,
+            soft_edge: 20,
+        };
+        assert_eq!(cone.edge_factor(0), 255);
+        assert_eq!(cone.edge_factor(20), 255);
+        assert_eq!(cone.edge_factor(30), 127);
+        assert_eq!(cone.edge_factor(40), 0);
+        assert!(cone.contains(350) && !cone.contains(300), "wraps around 0°");
+    }
+
+    // --- Soft shadows ------------------------------------------------------
+
+    fn brightness_at(e: &LightingEngine, id: LightId, dx: i16, dy: i16) -> u8 {
+        let side = e.light_canvas_size(id).unwrap() as i16;
+        let idx = (side / 2 + dy) * side + side / 2 + dx;
+        let c = e.light_canvas(id).unwrap()[idx as usize];
+        c.0.max(c.1).max(c.2)
+    }
+
+    #[test]
+    fn area_source_casts_penumbra_behind_object() {
+        let mut e = LightingEngine::new(2, 30);
+        e.set_pixel(33, 30, true);
+
+        e.update_or_add_light_with_solid_color(1, 8, 30, 30, 0);
+        assert_eq!(brightness_at(&e, 1, 6, 0), 0, "point source: hard umbra");
+
+        e.update_or_add_light_with_source_radius(1, 8, 30, 30, 2);
+        let penumbra = brightness_at(&e, 1, 6, 0);
+        let open = brightness_at(&e, 1, -6, 0);
+        assert!(penumbra > 0, "rim samples see past the object");
+        assert!(
+            penumbra < open,
+            "penumbra {penumbra} must be dimmer than open {open}"
+        );
+        assert!(brightness_at(&e, 1, 0, 0) > open, "centre stays brightest");
+    }
+
+    #[test]
+    fn zero_source_radius_matches_point_light() {
+        let mut e = LightingEngine::new(2, 30);
+        e.set_pixel(33, 30, true);
+        e.update_or_add_light_with_solid_color(1, 8, 30, 30, 0);
+        let before: Vec<u8> = e.light_canvas(1).unwrap().iter().map(|c| c.0).collect();
+        e.update_or_add_light_with_source_radius(1, 8, 30, 30, 0);
+        let after: Vec<u8> = e.light_canvas(1).unwrap().iter().map(|c| c.0).collect();
+        assert_eq!(before, after);
+        assert_eq!(e.light_source_radius(1), Some(0));
+    }
+
+    // --- Translucent materials ---------------------------------------------
+
+    #[test]
+    fn tinted_material_colours_light_behind_it() {
+        let mut e = LightingEngine::new(2, 30);
+        let glass = PixelMaterial {
+            transmittance: 255,
+            tint: (255, 0, 0),
+        };
+        e.set_pixel_material(33, 30, Some(glass));
+        e.update_or_add_light_with_rgb(1, 8, 30, 30, 255, 255, 255);
+        let side = e.light_canvas_size(1).unwrap();
+        let row = &e.light_canvas(1).unwrap()[(side / 2) * side..(side / 2 + 1) * side];
+        let (front, behind) = (row[side / 2 + 2], row[side / 2 + 5]);
+        assert!(front.1 > 0 && front.2 > 0, "white in front of the glass");
+        assert!(behind.0 > 0, "red passes through");
+        assert_eq!(
+            (behind.1, behind.2),
+            (0, 0),
+            "green and blue are filtered out"
+        );
+    }
 
-        let north = if row > 0 { Some(tile_idx - tiles_per_row) } else { None };
-        let south = if row + 1 < tiles_per_row { Some(tile_idx + tiles_per_row) } else { None };
-        let west = if col > 0 { Some(tile_idx - 1) } else { None };
-        let east = if col + 1 < tiles_per_row { Some(tile_idx + 1) } else { None };
+    #[test]
+    fn rewriting_the_same_material_leaves_lights_clean() {
+        let mut e = LightingEngine::new(2, 30);
+        let glass = PixelMaterial {
+            transmittance: 128,
+            tint: (255, 0, 0),
+        };
+        e.update_or_add_light(1, 8, 30, 30);
+        e.set_pixel_material(33, 30, Some(glass));
+        assert_eq!(e.dirty_lights(), vec![1]);
+        e.update_dirty_lights();
 
-        let reachable = |ni: Option<usize>, uf: &mut UnionFind| -> Option<usize> {
-            let ni = ni?;
-            if uf.find(ni) == room || self.door_edges.contains(&canonical_edge(tile_idx, ni)) {
-                Some(ni)
-            } else {
-                None
-            }
+        e.set_pixel_material(33, 30, Some(glass));
+        e.set_pixel_material(34, 30, None);
+        assert!(e.dirty_lights().is_empty());
+        e.set_pixel_material(33, 30, None);
+        assert_eq!(e.dirty_lights(), vec![1]);
+    }
+
+    #[test]
+    fn opaque_material_blocks_and_clear_removes_it() {
+        let mut e = LightingEngine::new(2, 30);
+        let curtain = PixelMaterial {
+            transmittance: 0,
+            tint: (255, 255, 255),
         };
+        e.set_pixel_material(33, 30, Some(curtain));
+        e.update_or_add_light_with_solid_color(1, 8, 30, 30, 0);
+        assert_eq!(brightness_at(&e, 1, 5, 0), 0);
 
-        let n_ok = reachable(north, &mut self.tile_uf);
-        let s_ok = reachable(south, &mut self.tile_uf);
-        let w_ok = reachable(west, &mut self.tile_uf);
-        let e_ok = reachable(east, &mut self.tile_uf);
+        e.clear_pixel_collisions();
+        assert!(e.pixel_material(33, 30).is_none());
+        e.update_or_add_light_with_solid_color(1, 8, 30, 30, 0);
+        assert!(brightness_at(&e, 1, 5, 0) > 0);
+    }
 
-        if let Some(i) = n_ok { out.push(i); }
-        if let Some(i) = w_ok { out.push(i); }
-        if let Some(i) = s_ok { out.push(i); }
-        if let Some(i) = e_ok { out.push(i); }
+    // --- Light lifecycle ---------------------------------------------------
 
-        if include_diagonal {
-            let diag = |dr: i32, dc: i32| -> Option<usize> {
-                let nr = row as i32 + dr;
-                let nc = col as i32 + dc;
-                if nr < 0 || nc < 0 || nr >= tiles_per_row as i32 || nc >= tiles_per_row as i32 {
-                    return None;
-                }
-                Some(nr as usize * tiles_per_row + nc as usize)
+    #[test]
+    fn lights_can_be_listed_and_removed_with_wide_ids() {
+        let mut e = LightingEngine::new(2, 30);
+        e.update_or_add_light(70_000, 4, 20, 20);
+        e.update_or_add_light(3, 4, 30, 30);
+        assert_eq!(e.light_ids(), vec![3, 70_000]);
+
+        assert!(e.remove_light(70_000));
+        assert!(!e.remove_light(70_000), "second removal is a no-op");
+        assert_eq!(e.light_ids(), vec![3]);
+        assert!(e.light_canvas(70_000).is_none());
+    }
+
+    #[test]
+    fn disabled_light_keeps_canvas_but_goes_dark() {
+        let mut e = LightingEngine::new(2, 30);
+        let ptr = e.update_or_add_light_with_solid_color(1, 4, 30, 30, 0);
+        assert!(!e.set_light_enabled(2, false), "unknown id");
+
+        assert!(e.set_light_enabled(1, false));
+        assert_eq!(e.light_enabled(1), Some(false));
+        assert_eq!(e.light_canvas(1).unwrap().as_ptr(), ptr, "allocation kept");
+        assert!(e.light_canvas(1).unwrap().iter().all(|c| c.3 == 0));
+        e.compute_composite();
+        assert!(e.composite_canvas().unwrap().iter().all(|c| c.3 == 0));
+
+        // Updates while disabled are remembered, not rendered.
+        e.update_or_add_light_with_solid_color(1, 4, 32, 30, 0);
+        assert!(e.light_canvas(1).unwrap().iter().all(|c| c.3 == 0));
+        e.set_light_enabled(1, true);
+        assert_eq!(e.light_position(1), Some((32, 30)));
+        assert!(brightness_at(&e, 1, 0, 0) > 0);
+    }
+
+    #[test]
+    fn disabled_ambient_refloods_when_re_enabled() {
+        let mut e = LightingEngine::new(2, 30);
+        e.set_tile(5, 5, 1);
+        e.update_or_add_ambient(9, 5, 5, 10, 20, 30);
+        assert!(ambient_cell_opaque(&e, 9, 5, 5));
+
+        e.set_ambient_enabled(9, false);
+        assert_eq!(e.ambient_enabled(9), Some(false));
+        assert!(!ambient_cell_opaque(&e, 9, 5, 5));
+        e.set_ambient_enabled(9, true);
+        assert!(ambient_cell_opaque(&e, 9, 5, 5));
+
+        assert_eq!(e.ambient_ids(), vec![9]);
+        assert!(e.remove_ambient(9));
+        assert!(e.ambient_ids().is_empty());
+    }
+
+    // --- Dirty tracking ----------------------------------------------------
+
+    #[test]
+    fn world_edits_dirty_only_lights_that_reach_them() {
+        let mut e = LightingEngine::new(2, 30);
+        e.update_or_add_light(1, 4, 10, 10);
+        e.update_or_add_light(2, 4, 40, 40);
+        assert!(e.dirty_lights().is_empty());
+
+        e.set_pixel(12, 10, true);
+        assert_eq!(e.dirty_lights(), vec![1]);
+
+        // Tile (20, 20) covers cells 40..42 — under light 2 only.
+        e.set_tile(20, 20, 1);
+        assert_eq!(e.dirty_lights(), vec![1, 2]);
+
+        // Far away from both.
+        e.set_pixel(55, 2, true);
+        assert_eq!(e.dirty_lights(), vec![1, 2]);
+    }
+
+    #[test]
+    fn update_dirty_lights_retraces_stale_canvases() {
+        let mut e = LightingEngine::new(2, 30);
+        e.update_or_add_light_with_solid_color(1, 8, 30, 30, 0);
+        e.update_or_add_light_with_solid_color(2, 4, 10, 10, 0);
+        assert!(brightness_at(&e, 1, 5, 0) > 0);
+
+        e.set_pixel(33, 30, true);
+        assert!(brightness_at(&e, 1, 5, 0) > 0, "stale until updated");
+        assert_eq!(e.update_dirty_lights(), vec![1]);
+        assert_eq!(brightness_at(&e, 1, 5, 0), 0, "shadow now cast");
+        assert!(e.update_dirty_lights().is_empty());
+
+        // Re-putting a dirty light clears its flag.
+        e.set_pixel(33, 30, false);
+        e.update_or_add_light_with_solid_color(1, 8, 30, 30, 0);
+        assert!(e.dirty_lights().is_empty());
+    }
+
+    #[test]
+    fn door_toggle_dirties_lights_on_both_sides() {
+        let mut e = LightingEngine::new(2, 30);
+        e.set_tile(11, 10, 1);
+        e.update_or_add_light(1, 3, 21, 21);
+        e.update_or_add_light(2, 3, 50, 50);
+        e.update_dirty_lights();
+        let (a, b) = (10 * 30 + 10, 10 * 30 + 11);
+        e.set_door_edge(a, b, true);
+        assert_eq!(e.dirty_lights(), vec![1]);
+    }
+
+    #[test]
+    fn tile_edit_refloods_ambients() {
+        let mut e = LightingEngine::new(2, 30);
+        e.set_tile(5, 5, 1);
+        e.update_or_add_ambient(1, 5, 5, 255, 255, 255);
+        e.set_tile(6, 5, 1);
+        assert!(!ambient_cell_opaque(&e, 1, 6, 5), "stale until updated");
+        e.update_dirty_lights();
+        assert!(ambient_cell_opaque(&e, 1, 6, 5), "room grew");
+    }
+
+    // --- Animation ---------------------------------------------------------
+
+    fn canvas_bytes(e: &LightingEngine, id: LightId) -> Vec<(u8, u8, u8, u8)> {
+        e.light_canvas(id)
+            .unwrap()
+            .iter()
+            .map(|c| (c.0, c.1, c.2, c.3))
+            .collect()
+    }
+
+    #[test]
+    fn strobe_animation_follows_tick_clock() {
+        let mut e = LightingEngine::new(2, 30);
+        e.update_or_add_light_with_solid_color(1, 6, 30, 30, 0);
+        e.update_or_add_light_with_solid_color(2, 6, 10, 10, 0);
+        let strobe = LightAnimation::Strobe {
+            on_ms: 100,
+            off_ms: 100,
+        };
+        assert!(e.set_light_animation(1, Some(strobe)));
+        assert!(!e.set_light_animation(9, Some(strobe)));
+
+        assert_eq!(e.tick(150), vec![1], "only animated lights re-render");
+        assert_eq!(e.time_ms(), 150);
+        assert!(
+            e.light_canvas(1).unwrap().iter().all(|c| c.0 == 0),
+            "off phase"
+        );
+        e.tick(100);
+        assert!(brightness_at(&e, 1, 0, 0) > 0, "on phase");
+    }
+
+    #[test]
+    fn same_seed_and_ticks_give_identical_canvases() {
+        let run = |ticks: &[u32]| {
+            let mut e = LightingEngine::new(2, 30);
+            e.update_or_add_light_with_cone(
+                1,
+                8,
+                30,
+                30,
+                Some(Cone {
+                    heading: 0,
+                    half_width: 30,
+                    soft_edge: 10,
+                }),
+            );
+            e.set_light_animation(
+                1,
+                Some(LightAnimation::Flicker {
+                    seed: 42,
+                    min: 100,
+                    max: 300,
+                    interval_ms: 70,
+                }),
+            );
+            let narrow = Cone {
+                heading: 0,
+                half_width: 20,
+                soft_edge: 0,
             };
-            let push_diag = |out: &mut Vec<usize>, idx: Option<usize>, gate_a: bool, gate_b: bool| {
-                if !(gate_a || gate_b) {
-                    return;
-                }
-                if let Some(d) = idx {
-                    out.push(d);
-                }
+            e.update_or_add_light_with_cone(2, 8, 10, 30, Some(narrow));
+            e.set_light_animation(
+                2,
+                Some(LightAnimation::Beacon {
+                    degrees_per_second: 90,
+                }),
+            );
+            for &dt in ticks {
+                e.tick(dt);
+            }
+            (canvas_bytes(&e, 1), canvas_bytes(&e, 2))
+        };
+        assert_eq!(run(&[16, 16, 17, 500]), run(&[16, 16, 17, 500]));
+        // The clock value, not the tick split, determines the frame.
+        assert_eq!(run(&[16, 16, 17, 500]), run(&[549]));
+        assert_ne!(run(&[16]), run(&[1016]), "the beacon has turned");
+    }
+
+    #[test]
+    fn beacon_turns_headings_beyond_a_full_circle() {
+        let beacon = |heading: u16| {
+            let mut e = LightingEngine::new(2, 30);
+            let cone = Cone {
+                heading,
+                half_width: 20,
+                soft_edge: 0,
             };
-            push_diag(&mut out, diag(-1, -1), n_ok.is_some(), w_ok.is_some());
-            push_diag(&mut out, diag(-1, 1), n_ok.is_some(), e_ok.is_some());
-            push_diag(&mut out, diag(1, -1), s_ok.is_some(), w_ok.is_some());
-            push_diag(&mut out, diag(1, 1), s_ok.is_some(), e_ok.is_some());
+            e.update_or_add_light_with_cone(1, 8, 30, 30, Some(cone));
+            e.set_light_animation(
+                1,
+                Some(LightAnimation::Beacon {
+                    degrees_per_second: 90,
+                }),
+            );
+            e.tick(1000);
+            canvas_bytes(&e, 1)
+        };
+        assert_eq!(beacon(65530), beacon(65530 % 360));
+    }
+
+    #[test]
+    fn radius_oscillation_keeps_canvas_size() {
+        let mut e = LightingEngine::new(2, 30);
+        let ptr = e.update_or_add_light_with_solid_color(1, 8, 30, 30, 0);
+        let breathe = LightAnimation::RadiusOscillation {
+            min: 64,
+            period_ms: 1000,
+        };
+        e.set_light_animation(1, Some(breathe));
+        assert_eq!(e.light_canvas(1).unwrap().as_ptr(), ptr);
+        assert_eq!(brightness_at(&e, 1, 5, 0), 0, "shrunk to a quarter radius");
+        e.tick(500);
+        assert!(
+            brightness_at(&e, 1, 5, 0) > 0,
+            "full radius half-way through"
+        );
+    }
+
+    // --- Shaped emitters ----------------------------------------------------
+
+    /// Brightness of world cell `(x, y)` in a light's canvas.
+    fn brightness_at_world(e: &LightingEngine, id: LightId, x: i16, y: i16) -> u8 {
+        let ((ox, oy), width, height) = e.light_canvas_rect(id).unwrap();
+        let (cx, cy) = (x - ox, y - oy);
+        assert!(cx >= 0 && cy >= 0 && (cx as usize) < width && (cy as usize) < height);
+        let c = e.light_canvas(id).unwrap()[cy as usize * width + cx as usize];
+        c.0.max(c.1).max(c.2)
+    }
+
+    #[test]
+    fn segment_emitter_canvas_covers_its_bounding_box() {
+        let mut e = LightingEngine::new(2, 30);
+        e.update_or_add_light_with_shape(1, 4, 20, 30, LightShape::Segment { end: (35, 30) });
+        assert_eq!(e.light_canvas_rect(1), Some(((16, 26), 24, 9)));
+        assert_eq!(e.light_canvas_size(1), None, "not square");
+
+        // Evenly lit along the tube — no beading between emitter cells.
+        let along: Vec<u8> = (20..=35)
+            .map(|x| brightness_at_world(&e, 1, x, 32))
+            .collect();
+        assert!(along.windows(2).all(|w| w[0] == w[1]), "{along:?}");
+        assert!(brightness_at_world(&e, 1, 27, 30) > brightness_at_world(&e, 1, 27, 33));
+    }
+
+    #[test]
+    fn rect_emitter_lights_interior_and_respects_occluders() {
+        let mut e = LightingEngine::new(2, 30);
+        let rect = LightShape::Rect {
+            width: 6,
+            height: 4,
+        };
+        e.update_or_add_light_with_shape(1, 5, 20, 20, rect);
+        assert_eq!(
+            brightness_at_world(&e, 1, 22, 21),
+            brightness_at_world(&e, 1, 20, 20)
+        );
+
+        // A wall of object cells under the rectangle shadows the cells below.
+        for x in 16..32 {
+            e.set_pixel(x, 25, true);
         }
+        e.update_dirty_lights();
+        assert!(brightness_at_world(&e, 1, 22, 24) > 0);
+        assert_eq!(brightness_at_world(&e, 1, 22, 27), 0);
+    }
 
-        out
+    #[test]
+    fn huge_shaped_emitters_are_clipped_to_the_map() {
+        let mut e = LightingEngine::new(2, 30);
+        let huge = LightShape::Rect {
+            width: 40000,
+            height: 40000,
+        };
+        e.update_or_add_light_with_shape(1, 4, 50, 50, huge);
+        assert_eq!(e.light_canvas_rect(1), Some(((46, 46), 18, 18)));
+        assert!(brightness_at_world(&e, 1, 59, 59) > 0);
+
+        let off_map = LightShape::Rect {
+            width: 5,
+            height: 5,
+        };
+        e.update_or_add_light_with_shape(2, 4, -100, -100, off_map);
+        assert_eq!(e.light_canvas_rect(2), Some(((-104, -104), 9, 9)));
+        assert!(e.light_canvas(2).unwrap().iter().all(|c| c.3 == 0));
     }
-}
 
-/// Canonicalise an unordered tile-index pair so `(a, b)` and `(b, a)` map
-/// to the same `HashSet` entry.
-fn canonical_edge(a: usize, b: usize) -> (usize, usize) {
-    if a <= b {
-        (a, b)
-    } else {
-        (b, a)
+    #[test]
+    fn shaped_lights_composite_at_their_rect() {
+        let mut e = LightingEngine::new(2, 30);
+        e.update_or_add_light_with_shape(1, 3, 40, 10, LightShape::Segment { end: (50, 10) });
+        e.compute_composite();
+        let cpr = e.cells_per_row();
+        let composite = e.composite_canvas().unwrap();
+        assert_ne!(composite[10 * cpr + 48].3, 0);
+        assert_eq!(composite[10 * cpr + 56].3, 0);
     }
-}
 
-/// Default process-wide engine instance, used as a back-compat shim for the
-/// WASM/JS API. Rust callers should prefer constructing their own
-/// [`LightingEngine`] via [`LightingEngine::new`].
-pub static DEFAULT_ENGINE: Lazy<RwLock<LightingEngine>> =
-    Lazy::new(|| RwLock::new(LightingEngine::default()));
+    // --- Emissive cells ----------------------------------------------------
 
-/// Gradient used by [`LightingEngine::render_canvas_text`], from darkest to
-/// brightest. Ten characters → integer division by 255 maps cleanly.
-const ASCII_GRADIENT: &[u8; 10] = b" .:-=+*#%@";
+    fn emissive_at(e: &LightingEngine, x: usize, y: usize) -> (u8, u8, u8, u8) {
+        let c = e.emissive_canvas().unwrap()[y * e.cells_per_row() + x];
+        (c.0, c.1, c.2, c.3)
+    }
 
-impl LightingEngine {
-    /// Render a light's canvas as a multiline ASCII matrix.
-    ///
-    /// One character per cell, one line per row, trailing `\n` after each
-    /// row. Returns `None` if no light with the given id exists.
-    ///
-    /// This is the agent-readable debug primitive used by the exploration
-    /// loop (printed unconditionally) and the regression loop (embedded in
-    /// panic messages on assertion failure) so a failing test produces
-    /// self-explanatory output.
-    pub fn render_canvas_text(&self, id: u8) -> Option<String> {
-        let light = self.lights.get(&id)?;
-        let canvas = light.canvas();
-        let size = light.canvas_size();
-        let mut out = String::with_capacity((size + 1) * size);
-        for row in 0..size {
-            for col in 0..size {
-                let pixel = canvas[row * size + col];
-                let brightness = pixel.0.max(pixel.1).max(pixel.2);
-                let idx = (brightness as usize * (ASCII_GRADIENT.len() - 1)) / 255;
-                out.push(ASCII_GRADIENT[idx] as char);
-            }
-            out.push('\n');
+    #[test]
+    fn emissive_cell_glows_within_radius_and_is_occluded() {
+        let mut e = LightingEngine::new(2, 30);
+        let lava = Emissive {
+            color: (255, 80, 0),
+            radius: 6,
+        };
+        e.set_emissive_cell(20, 20, Some(lava));
+        for y in 14..=26 {
+            e.set_pixel(23, y, true);
         }
-        Some(out)
+        e.compute_emissive();
+        assert_eq!(emissive_at(&e, 20, 20), (255, 80, 0, 255));
+        let near = emissive_at(&e, 18, 20);
+        assert!(near.0 > 0 && near.0 < 255, "{near:?}");
+        assert_eq!(emissive_at(&e, 25, 20).3, 0, "behind the wall");
+        assert_eq!(emissive_at(&e, 20, 30).3, 0, "beyond the radius");
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn emissive_object_cell_radiates_from_its_open_neighbours() {
+        let mut e = LightingEngine::new(2, 30);
+        e.set_pixel(20, 20, true);
+        e.set_emissive_cell(
+            20,
+            20,
+            Some(Emissive {
+                color: (0, 200, 255),
+                radius: 4,
+            }),
+        );
+        e.compute_emissive();
+        assert_eq!(emissive_at(&e, 20, 20), (0, 200, 255, 255));
+        assert!(emissive_at(&e, 22, 20).2 > 0);
+        assert!(emissive_at(&e, 20, 18).2 > 0);
+    }
 
     #[test]
-    fn render_canvas_text_is_none_for_missing_light() {
-        let engine = LightingEngine::new(6, 30);
-        assert!(engine.render_canvas_text(0).is_none());
+    fn emissive_tile_type_follows_set_tile_into_the_composite() {
+        let mut e = LightingEngine::new(2, 30);
+        e.set_emissive_tile_type(
+            3,
+            Some(Emissive {
+                color: (0, 255, 0),
+                radius: 2,
+            }),
+        );
+        let lit = |e: &LightingEngine| {
+            e.composite_canvas()
+                .unwrap()
+                .iter()
+                .filter(|c| c.3 > 0)
+                .count()
+        };
+
+        e.compute_composite();
+        assert_eq!(lit(&e), 0, "no tile of type 3 yet");
+
+        e.set_tile(5, 5, 3);
+        e.compute_composite();
+        assert!(lit(&e) >= 4);
+        assert_eq!(emissive_at(&e, 10, 10), (0, 255, 0, 255));
+
+        e.set_tile(5, 5, 0);
+        e.update_dirty_lights();
+        assert_eq!(emissive_at(&e, 10, 10).3, 0);
     }
 
+    // --- Bounce --------------------------------------------------------------
+
     #[test]
-    fn render_canvas_text_dimensions_match_canvas() {
-        let mut engine = LightingEngine::new(6, 30);
-        engine.update_or_add_light(1, 3, 5, 5);
-        let s = engine.render_canvas_text(1).expect("light exists");
-        let lines: Vec<&str> = s.lines().collect();
-        assert_eq!(lines.len(), 7, "radius-3 light renders 7 rows, got: {}\n{}", lines.len(), s);
-        for (i, line) in lines.iter().enumerate() {
-            assert_eq!(
-                line.chars().count(),
-                7,
-                "row {} expected 7 cols, got {}: {:?}",
-                i,
-                line.chars().count(),
-                line
-            );
+    fn bounce_fills_the_lit_room_and_only_that_room() {
+        let mut e = LightingEngine::new(2, 30);
+        // A 10×10-tile room of type 1 in the top-left corner (cells 0..20).
+        let mut tiles = vec![0u8; 900];
+        for ty in 0..10 {
+            for tx in 0..10 {
+                tiles[ty * 30 + tx] = 1;
+            }
         }
+        e.set_tile_map(tiles);
+        e.update_or_add_light_with_rgb(1, 8, 3, 10, 255, 255, 255);
+        let at = |e: &LightingEngine, x: usize, y: usize| e.composite_canvas().unwrap()[y * 60 + x];
+
+        e.compute_composite();
+        assert_eq!(at(&e, 18, 18).3, 0, "out of direct reach");
+
+        e.set_bounce(Some(Bounce { fraction: 200 }));
+        e.compute_composite();
+        let far = at(&e, 18, 18);
+        assert!(far.0 > 0 && far.0 == far.1 && far.1 == far.2, "{far:?}");
+        assert!(at(&e, 4, 10).0 > far.0, "direct light still dominates");
+        assert_eq!(at(&e, 30, 30).3, 0, "other rooms get nothing");
+
+        let first: Vec<u8> = e.composite_canvas().unwrap().iter().map(|c| c.0).collect();
+        e.compute_composite();
+        let second: Vec<u8> = e.composite_canvas().unwrap().iter().map(|c| c.0).collect();
+        assert_eq!(first, second);
     }
 
     #[test]
-    fn render_canvas_text_center_brighter_than_corner() {
-        let mut engine = LightingEngine::new(6, 30);
-        engine.update_or_add_light(1, 3, 5, 5);
-        let s = engine.render_canvas_text(1).unwrap();
-        let lines: Vec<&str> = s.lines().collect();
-        let center = lines[3].chars().nth(3).unwrap();
-        let corner = lines[0].chars().nth(0).unwrap();
-        let g = std::str::from_utf8(ASCII_GRADIENT).unwrap();
-        let center_idx = g.find(center).unwrap_or_else(|| panic!("center {:?} not in gradient\n{}", center, s));
-        let corner_idx = g.find(corner).unwrap_or_else(|| panic!("corner {:?} not in gradient\n{}", corner, s));
-        assert!(
-            center_idx >= corner_idx,
-            "center {:?} (idx {}) should be at least as bright as corner {:?} (idx {}). Canvas:\n{}",
-            center, center_idx, corner, corner_idx, s
+    fn bounce_does_not_reflect_ambient_fill() {
+        let mut e = LightingEngine::new(2, 10);
+        let mut tiles = vec![2u8; 100];
+        for ty in 0..5 {
+            for tx in 0..5 {
+                tiles[ty * 10 + tx] = 1;
+            }
+        }
+        e.set_tile_map(tiles);
+        e.update_or_add_ambient(0, 1, 1, 60, 60, 60);
+        e.compute_composite();
+        let flat: Vec<u8> = e.composite_canvas().unwrap().iter().map(|c| c.0).collect();
+
+        e.set_bounce(Some(Bounce { fraction: 255 }));
+        e.compute_composite();
+        let bounced: Vec<u8> = e.composite_canvas().unwrap().iter().map(|c| c.0).collect();
+        assert_eq!(bounced, flat, "an ambient-only room is left as it was");
+    }
+
+    #[test]
+    fn bounce_stays_on_its_side_of_a_diagonal() {
+        // The closet tile 4 is cut NW-SE; the light sits in the corridor
+        // east of it, which the north-east half joins.
+        let mut e = LightingEngine::new(4, 3);
+        e.set_tile_map(vec![2, 2, 2, 2, 1, 1, 2, 2, 2]);
+        e.set_tile_diagonal(4, Some(Diagonal::NwSe));
+        e.update_or_add_light_with_rgb(1, 3, 10, 6, 255, 255, 255);
+        e.set_bounce(Some(Bounce { fraction: 255 }));
+        e.compute_composite();
+        let at = |e: &LightingEngine, x: usize, y: usize| e.composite_canvas().unwrap()[y * 12 + x];
+        assert!(at(&e, 7, 4).0 > 0, "the corridor's half is lit");
+        assert_eq!(at(&e, 4, 7).3, 0, "the cut-off half gets no bounce");
+    }
+
+    // --- Ambient spread ------------------------------------------------------
+
+    /// A 6×6-tile map of three two-column rooms (types 1, 2, 3 from west to
+    /// east) with open doors on row 1 between each pair.
+    fn three_rooms_with_doors() -> LightingEngine {
+        let mut e = LightingEngine::new(2, 6);
+        let tiles = (0..36).map(|i| (i % 6 / 2 + 1) as u8).collect();
+        e.set_tile_map(tiles);
+        e.set_door_edge(6 + 1, 6 + 2, true);
+        e.set_door_edge(6 + 3, 6 + 4, true);
+        e
+    }
+
+    /// Red channel of an ambient at the centre of tile `(tx, ty)`.
+    fn ambient_red(e: &LightingEngine, id: LightId, tx: usize, ty: usize) -> u8 {
+        let (cpt, cpr) = (e.cells_per_tile(), e.cells_per_row());
+        e.ambient_canvas(id).unwrap()[(ty * cpt + cpt / 2) * cpr + tx * cpt + cpt / 2].0
+    }
+
+    #[test]
+    fn door_hop_spread_dims_per_door_and_closing_cuts_it() {
+        let mut e = three_rooms_with_doors();
+        e.update_or_add_ambient(0, 0, 1, 200, 200, 200);
+        assert_eq!(ambient_red(&e, 0, 2, 1), 0, "room-only by default");
+
+        assert!(e.set_ambient_spread(0, AmbientSpread::DoorHops { factor: 128 }));
+        assert_eq!(ambient_red(&e, 0, 1, 4), 200);
+        assert_eq!(ambient_red(&e, 0, 3, 5), 100, "one door away");
+        assert_eq!(ambient_red(&e, 0, 5, 0), 50, "two doors away");
+
+        e.set_door_edge(6 + 1, 6 + 2, false);
+        assert_eq!(ambient_red(&e, 0, 3, 5), 100, "stale until the next update");
+        e.update_dirty_lights();
+        assert_eq!(ambient_red(&e, 0, 3, 5), 0);
+        assert_eq!(ambient_red(&e, 0, 5, 0), 0);
+        assert_eq!(ambient_red(&e, 0, 1, 4), 200);
+    }
+
+    #[test]
+    fn geodesic_spread_fades_with_distance_from_the_doorway() {
+        let mut e = three_rooms_with_doors();
+        e.update_or_add_ambient(0, 0, 1, 255, 0, 0);
+        e.set_ambient_spread(0, AmbientSpread::Geodesic { tiles: 3 });
+        assert_eq!(ambient_red(&e, 0, 2, 1), 170, "just through the door");
+        assert_eq!(ambient_red(&e, 0, 3, 1), 85);
+        assert_eq!(ambient_red(&e, 0, 2, 3), 0, "three steps from the door");
+        assert_eq!(ambient_red(&e, 0, 4, 1), 0, "out of reach");
+    }
+
+    // --- Door states -------------------------------------------------------
+
+    #[test]
+    fn door_states_gate_paths_and_rays_per_channel() {
+        let mut e = three_rooms_with_doors();
+        let crosses = |e: &LightingEngine, channel| !e.is_blocked_for(channel, 3, 2, 4, 2);
+
+        e.set_door_state(8, 7, DoorState::Locked);
+        assert_eq!(e.door_state(7, 8), Some(DoorState::Locked));
+        assert!(e.path(0, 1, 2, 1).is_empty());
+        assert!(!e.cast_ray(1, 1, 2, 1));
+        assert!(!crosses(&e, Channel::Light));
+
+        e.set_door_state(7, 8, DoorState::Curtain);
+        assert_eq!(
+            e.path(0, 1, 2, 1).len(),
+            3,
+            "curtains can be walked through"
         );
+        assert!(!e.cast_ray(1, 1, 2, 1));
+        assert!(!crosses(&e, Channel::Light) && !crosses(&e, Channel::Sight));
+        assert!(crosses(&e, Channel::Movement));
+
+        e.set_door_state(7, 8, DoorState::OneWay { from: 7 });
+        assert!(e.neighbours(7, false).contains(&8));
+        assert!(!e.neighbours(8, false).contains(&7));
+        assert_eq!(e.path(0, 1, 2, 1).len(), 3);
+        assert!(e.path(2, 1, 0, 1).is_empty());
+        assert!(e.cast_ray(2, 1, 1, 1), "seen through from either side");
+        assert!(crosses(&e, Channel::Light) && !crosses(&e, Channel::Movement));
+
+        e.set_door_state(7, 8, DoorState::Secret { discovered: false });
+        assert!(e.path(0, 1, 2, 1).is_empty());
+        assert!(!e.cast_ray(1, 1, 2, 1));
+        e.set_door_state(7, 8, DoorState::Secret { discovered: true });
+        assert!(!e.path(0, 1, 2, 1).is_empty());
+        assert!(e.cast_ray(1, 1, 2, 1) && crosses(&e, Channel::Light));
+
+        e.set_door_edge(7, 8, false);
+        assert_eq!(e.door_state(7, 8), None);
+        assert!(!crosses(&e, Channel::Movement));
     }
 
     #[test]
-    fn independent_engines_do_not_share_lights() {
-        let mut a = LightingEngine::new(6, 30);
-        let b = LightingEngine::new(6, 30);
-        a.update_or_add_light(7, 2, 4, 4);
-        assert!(a.light_canvas(7).is_some());
-        assert!(b.light_canvas(7).is_none());
+    fn one_way_door_from_neither_tile_is_ignored() {
+        let mut e = three_rooms_with_doors();
+        e.set_door_state(7, 8, DoorState::OneWay { from: 6 });
+        assert_eq!(e.door_state(7, 8), Some(DoorState::Open));
+        assert_eq!(e.path(2, 1, 0, 1).len(), 3);
+
+        e.set_door_edge(7, 8, false);
+        e.set_door_state(7, 8, DoorState::OneWay { from: 9 });
+        assert_eq!(e.door_state(7, 8), None);
     }
 
     #[test]
-    fn set_tile_at_non_default_resolution_marks_cell_edges() {
-        // Engine with explicit non-default resolution (4 cells per tile, 5x5 tiles).
-        let mut engine = LightingEngine::new(4, 5);
-        // Empty world: all tiles type 0 → no internal boundaries.
-        // Flip tile (2,2) to type 1, leaving its neighbours type 0.
-        engine.set_tile(2, 2, 1);
-        let cells = engine.block_map();
-        let cells_per_row = engine.cells_per_row();
-        // Top-left cell of tile (2,2) sits at cell (8,8). With cells_per_tile=4
-        // its edges are at cell rows/cols 8..=11. The north edge should be
-        // blocked (different from tile above), as should the west edge.
-        let nw = cells[8 * cells_per_row + 8];
-        assert!(nw.n_blocked, "NW cell should have north edge blocked");
-        assert!(nw.w_blocked, "NW cell should have west edge blocked");
-        // Inner cell (9,9) sits inside tile (2,2) — no edges blocked.
-        let inner = cells[9 * cells_per_row + 9];
-        assert!(!inner.n_blocked && !inner.s_blocked && !inner.e_blocked && !inner.w_blocked);
+    fn ambients_spill_through_doors_not_light_passing_walls() {
+        let mut e = three_rooms_with_doors();
+        e.set_door_edge(7, 8, false);
+        e.set_tile_type_channels(2, Channels::MOVEMENT); // glass walls
+        e.update_or_add_ambient(0, 0, 1, 200, 200, 200);
+        e.set_ambient_spread(0, AmbientSpread::DoorHops { factor: 128 });
+        assert!(!e.is_blocked_for(Channel::Light, 3, 2, 4, 2));
+        assert_eq!(ambient_red(&e, 0, 3, 5), 0, "glass is not a door");
+
+        e.set_door_edge(7, 8, true);
+        e.update_dirty_lights();
+        assert_eq!(ambient_red(&e, 0, 3, 5), 100);
     }
 
     #[test]
-    fn engine_exposes_resolution() {
-        let e = LightingEngine::new(6, 30);
-        assert_eq!(e.cells_per_tile(), 6);
-        assert_eq!(e.tiles_per_row(), 30);
+    fn curtains_stop_ambients_spilling_but_one_way_doors_do_not() {
+        let mut e = three_rooms_with_doors();
+        e.update_or_add_ambient(0, 0, 1, 200, 200, 200);
+        e.set_ambient_spread(0, AmbientSpread::DoorHops { factor: 128 });
+        assert_eq!(ambient_red(&e, 0, 3, 5), 100);
 
-        let e2 = LightingEngine::new(9, 20);
-        assert_eq!(e2.cells_per_tile(), 9);
-        assert_eq!(e2.tiles_per_row(), 20);
+        e.set_door_state(7, 8, DoorState::Curtain);
+        e.update_dirty_lights();
+        assert_eq!(ambient_red(&e, 0, 3, 5), 0);
+
+        e.set_door_state(7, 8, DoorState::OneWay { from: 8 });
+        e.update_dirty_lights();
+        assert_eq!(ambient_red(&e, 0, 3, 5), 100);
     }
 
     #[test]
-    fn path_returns_indices_between_two_passable_tiles() {
-        let mut e = LightingEngine::new(2, 5);
-        e.set_tile_map(vec![1u8; 25]);
-        let p = e.path(0, 0, 4, 0);
-        assert!(!p.is_empty(), "expected non-empty path, got {:?}", p);
-        assert_eq!(p[0], 0, "path should start at start index");
-        assert_eq!(p[p.len() - 1], 4, "path should end at goal index");
+    fn door_state_decodes_wasm_kinds() {
+        assert_eq!(DoorState::from_u8(3, 12), DoorState::OneWay { from: 12 });
+        assert_eq!(
+            DoorState::from_u8(6, 0),
+            DoorState::Secret { discovered: true }
+        );
+        assert_eq!(DoorState::from_u8(99, 0), DoorState::Closed);
+        for kind in 0..=6 {
+            assert_eq!(DoorState::from_u8(kind, 12).kind(), kind);
+        }
+        assert!(DoorState::OneWay { from: 1 }.passes(Channel::Movement, 1));
+        assert!(!DoorState::OneWay { from: 1 }.passes(Channel::Movement, 2));
     }
 
+    // --- Ambient occlusion -------------------------------------------------
+
     #[test]
-    fn set_door_edge_records_canonical_pair() {
-        let mut e = LightingEngine::default();
-        assert!(e.door_edges().is_empty());
+    fn ambient_occlusion_darkens_walls_and_corners_within_the_room() {
+        // One 3×3-tile room of type 1 (cells 0..18) in a 6-tile map.
+        let mut e = LightingEngine::new(6, 6);
+        let tiles = (0..36).map(|i| u8::from(i % 6 < 3 && i / 6 < 3)).collect();
+        e.set_tile_map(tiles);
+        e.update_or_add_ambient(0, 1, 1, 200, 200, 200);
+        let red =
+            |e: &LightingEngine, x: usize, y: usize| e.ambient_canvas(0).unwrap()[y * 36 + x].0;
+        assert_eq!(red(&e, 0, 0), 200, "flat by default");
 
-        // open=true records the edge; the pair is order-insensitive.
-        e.set_door_edge(5, 7, true);
-        assert!(e.has_door_edge(5, 7));
-        assert!(e.has_door_edge(7, 5));
-        assert_eq!(e.door_edges().len(), 1);
+        let ao = AmbientOcclusion {
+            radius: 4,
+            strength: 128,
+        };
+        assert!(e.set_ambient_occlusion(0, Some(ao)));
+        let (corner, wall, middle) = (red(&e, 0, 0), red(&e, 0, 9), red(&e, 9, 9));
+        assert_eq!(middle, 200);
+        assert!(corner < wall && wall < middle, "{corner} {wall} {middle}");
+        assert_eq!(
+            e.ambient_canvas(0).unwrap()[9 * 36 + 20].3,
+            0,
+            "stays in its room"
+        );
 
-        // open=false removes it.
-        e.set_door_edge(7, 5, false);
-        assert!(!e.has_door_edge(5, 7));
-        assert!(e.door_edges().is_empty());
+        // An Object darkens its surroundings once the ambient is refreshed.
+        e.set_pixel(9, 9, true);
+        e.update_dirty_lights();
+        assert!(red(&e, 10, 9) < middle);
     }
 
     #[test]
-    fn closed_door_keeps_rooms_split_for_pathfinding() {
-        let mut e = LightingEngine::new(2, 5);
-        // Two rooms separated by a column of type-2 tiles at x=2.
-        let mut tiles = vec![1u8; 25];
-        for y in 0..5 {
-            tiles[y * 5 + 2] = 2;
-        }
+    fn clearing_objects_lifts_their_ambient_occlusion() {
+        let mut e = LightingEngine::new(6, 6);
+        let tiles = (0..36).map(|i| u8::from(i % 6 < 3 && i / 6 < 3)).collect();
         e.set_tile_map(tiles);
-        // No door — different tile types ⇒ different rooms ⇒ no path.
-        assert!(
-            e.path(0, 1, 4, 1).is_empty(),
-            "expected empty path across closed wall"
+        e.update_or_add_ambient(0, 1, 1, 200, 200, 200);
+        let ao = AmbientOcclusion {
+            radius: 4,
+            strength: 128,
+        };
+        e.set_ambient_occlusion(0, Some(ao));
+        let red = |e: &LightingEngine| e.ambient_canvas(0).unwrap()[9 * 36 + 10].0;
+        e.update_or_add_object(
+            1,
+            9,
+            9,
+            ObjectShape::Rect {
+                width: 1,
+                height: 1,
+            },
         );
+        e.update_dirty_lights();
+        assert!(red(&e) < 200);
+
+        e.clear_pixel_collisions();
+        e.update_dirty_lights();
+        assert_eq!(
+            red(&e),
+            200,
+            "the Object no longer darkens its surroundings"
+        );
+
+        let map = (0..36 * 36).map(|i| i32::from(i % 36 < 18 && i / 36 < 18));
+        e.update_map_data(map.collect(), 36);
+        assert!(e.dirty_ambients.contains(&0));
     }
 
+    // --- Linear light --------------------------------------------------------
+
     #[test]
-    fn open_door_joins_rooms_for_pathfinding() {
+    fn linear_space_blends_overlaps_gamma_correctly() {
         let mut e = LightingEngine::new(2, 5);
-        let mut tiles = vec![1u8; 25];
-        for y in 0..5 {
-            tiles[y * 5 + 2] = 2;
-        }
-        e.set_tile_map(tiles);
-        // Open the door across the wall column at row 1.
-        // Tile (1,1) and tile (3,1) are non-adjacent so the door is between
-        // (1,1) and (2,1) and another between (2,1) and (3,1). But a single
-        // door usually bridges directly between the two passable tiles, so
-        // model it as: door between (1,1) and (3,1)? That isn't adjacent.
-        // Use two doors that each step into the wall tile, then out.
-        let idx = |x: usize, y: usize| -> usize { y * 5 + x };
-        e.set_door_edge(idx(1, 1), idx(2, 1), true);
-        e.set_door_edge(idx(2, 1), idx(3, 1), true);
-        let p = e.path(0, 1, 4, 1);
-        assert!(!p.is_empty(), "expected non-empty path through open door");
-        assert_eq!(p[0], idx(0, 1));
-        assert_eq!(p[p.len() - 1], idx(4, 1));
+        e.set_tile_map(vec![1u8; 25]);
+        e.update_or_add_ambient(0, 0, 0, 128, 128, 128);
+        e.update_or_add_ambient(1, 0, 0, 128, 128, 128);
+        let grey = |e: &mut LightingEngine| {
+            e.compute_composite();
+            e.composite_canvas().unwrap()[0].0
+        };
+        assert_eq!(grey(&mut e), 255, "sRGB bytes add and clip");
+        e.set_color_space(ColorSpace::Linear);
+        assert_eq!(grey(&mut e), 176, "two ~22% lights make ~43%, not 100%");
+        e.set_color_space(ColorSpace::Srgb);
+        assert_eq!(grey(&mut e), 255);
     }
 
     #[test]
-    fn closed_door_blocks_light_into_adjacent_room() {
-        // Two rooms separated by a column of wall tiles. Light placed inside
-        // the west room must not leak into the east room when no door is open.
-        let mut e = LightingEngine::new(4, 8);
-        let tpr = e.tiles_per_row();
-        let cpt = e.cells_per_tile();
-        let mut tiles = vec![1u8; tpr * tpr];
-        for y in 0..tpr {
-            tiles[y * tpr + tpr / 2] = 0; // wall tiles
-        }
-        e.set_tile_map(tiles);
+    fn linear_space_keeps_authored_colour_and_brightens_falloff() {
+        let mut e = LightingEngine::new(2, 30);
+        e.update_or_add_light_with_rgb(1, 8, 30, 30, 200, 100, 50);
+        let srgb_mid = brightness_at(&e, 1, 4, 0);
+        e.set_color_space(ColorSpace::Linear);
+        assert!(e.dirty_lights().is_empty(), "switching re-renders at once");
+        let c = e.light_canvas(1).unwrap()[8 * 17 + 8];
+        assert_eq!((c.0, c.1, c.2), (200, 100, 50));
+        assert!(brightness_at(&e, 1, 4, 0) > srgb_mid);
+    }
 
-        let light_tx = tpr / 2 - 1;
-        let light_ty = tpr / 2;
-        let light_cx = (light_tx * cpt + cpt / 2) as i16;
-        let light_cy = (light_ty * cpt + cpt / 2) as i16;
-        e.update_or_add_light(1, (cpt * 4) as i16, light_cx, light_cy);
+    // --- Output formats ------------------------------------------------------
 
-        // Sample a cell deep inside the east room — must be dark.
-        let probe_cx = ((tpr / 2 + 1) * cpt + cpt / 2) as i16;
-        let probe_cy = light_cy;
-        let is_lit = !e.is_blocked(light_cx, light_cy, probe_cx, probe_cy);
-        assert!(!is_lit, "closed wall should block ray to east room");
+    #[test]
+    fn fov_mask_packs_to_one_bit_per_cell() {
+        let mut e = LightingEngine::new(2, 4);
+        assert!(
+            e.canvas_bits(CanvasSource::Fov).is_none(),
+            "not computed yet"
+        );
+        e.compute_fov(&[3, 3]);
+        let visible = e.fov_canvas().unwrap().iter().filter(|c| c.3 != 0).count();
+        let bits = e.canvas_bits(CanvasSource::Fov).unwrap();
+        assert_eq!(bits.len(), 64usize.div_ceil(8));
+        assert_eq!(
+            bits.iter().map(|b| b.count_ones() as usize).sum::<usize>(),
+            visible
+        );
+        let viewer = 3 * 8 + 3;
+        assert_eq!(
+            (bits[viewer / 8] >> (viewer % 8)) & 1,
+            1,
+            "the viewer's own cell"
+        );
     }
 
     #[test]
-    fn open_door_lets_light_cross_into_adjacent_room() {
-        let mut e = LightingEngine::new(4, 8);
-        let tpr = e.tiles_per_row();
-        let cpt = e.cells_per_tile();
-        let mut tiles = vec![1u8; tpr * tpr];
-        for y in 0..tpr {
-            tiles[y * tpr + tpr / 2] = 0;
-        }
-        e.set_tile_map(tiles);
+    fn light_formats_match_its_canvas() {
+        let mut e = LightingEngine::new(2, 30);
+        e.update_or_add_light_with_rgb(1, 4, 30, 30, 255, 255, 255);
+        e.update_or_add_light_with_intensity(1, 4, 30, 30, 512);
+        let cells = e.light_canvas(1).unwrap().len();
+        let centre = 4 * 9 + 4;
 
-        let light_ty = tpr / 2;
-        let light_tx = tpr / 2 - 1;
-        let wall_tx = tpr / 2;
-        let east_tx = tpr / 2 + 1;
-        e.set_door_edge(
-            light_ty * tpr + light_tx,
-            light_ty * tpr + wall_tx,
-            true,
+        assert_eq!(e.canvas_luma8(CanvasSource::Light(1)).unwrap()[centre], 255);
+        let wide = e.canvas_rgba16(CanvasSource::Light(1)).unwrap();
+        assert_eq!(wide.len(), cells * 4);
+        assert_eq!(wide[centre * 4], 2 * crate::lighting::HDR_ONE, "unclamped");
+        assert_eq!(
+            e.canvas_f32(CanvasSource::Light(1)).unwrap()[centre * 4],
+            2.0
         );
-        e.set_door_edge(
-            light_ty * tpr + wall_tx,
-            light_ty * tpr + east_tx,
-            true,
+        assert!(e.canvas_f32(CanvasSource::Light(2)).is_none());
+    }
+
+    // --- Light queries -------------------------------------------------------
+
+    #[test]
+    fn light_at_matches_the_composite() {
+        let mut e = LightingEngine::new(2, 5);
+        e.set_tile_map(vec![1u8; 25]);
+        e.update_or_add_ambient(0, 0, 0, 20, 20, 40);
+        e.update_or_add_light_with_rgb(1, 5, 3, 3, 255, 128, 0);
+        e.update_or_add_light_with_rgb(2, 5, 6, 3, 0, 0, 255);
+        e.set_blend_mode(BlendMode::Screen);
+        e.compute_composite();
+        let composite = e.composite_canvas().unwrap().to_vec();
+        for (cx, cy) in [(3, 3), (5, 3), (4, 6), (9, 9)] {
+            let c = composite[cy as usize * 10 + cx as usize];
+            assert_eq!(
+                e.light_at(cx, cy).color,
+                (c.0, c.1, c.2),
+                "cell ({cx}, {cy})"
+            );
+        }
+        let batch = e.light_at_cells(&[(3, 3), (-1, 0)]);
+        assert_eq!(batch[0], e.light_at(3, 3));
+        assert_eq!(batch[1], LightSample::default(), "off the map");
+    }
+
+    #[test]
+    fn light_at_tile_averages_its_cells() {
+        let mut e = LightingEngine::new(2, 30);
+        e.update_or_add_light_with_rgb(1, 6, 20, 20, 255, 255, 255);
+        let tile = e.light_at_tile(10, 10);
+        let cells =
+            [(20, 20), (21, 20), (20, 21), (21, 21)].map(|(x, y)| e.light_at(x, y).hdr.0 as u32);
+        assert_eq!(tile.hdr.0 as u32, cells.iter().sum::<u32>() / 4);
+        assert!(tile.level > 0 && tile.level < 255);
+        assert_eq!(e.light_at_tile(0, 0).level, 0);
+        assert_eq!(
+            e.light_at_tile(20000, 0),
+            LightSample::default(),
+            "off the map"
         );
+        assert_eq!(e.light_at_tile(-1, 3), LightSample::default());
+    }
 
-        let light_cx = (light_tx * cpt + cpt / 2) as i16;
-        let light_cy = (light_ty * cpt + cpt / 2) as i16;
-        // Probe a cell one cell east of the door's east boundary so it sits
-        // squarely inside the east room.
-        let probe_cx = (east_tx * cpt + cpt / 2) as i16;
-        let probe_cy = light_cy;
+    // --- Dynamic occluders ---------------------------------------------------
+
+    #[test]
+    fn dynamic_occluders_shadow_and_are_replaced_each_frame() {
+        let alphas = |e: &LightingEngine| -> Vec<u8> {
+            e.light_canvas(1).unwrap().iter().map(|c| c.3).collect()
+        };
+        let mut e = LightingEngine::new(2, 30);
+        e.set_pixel(28, 30, true);
+        e.update_or_add_light_with_solid_color(1, 8, 30, 30, 0);
+        let furniture_only = alphas(&e);
+
+        e.set_dynamic_occluders(&[(32, 30)]);
+        assert_eq!(e.dirty_lights(), vec![1]);
+        e.update_dirty_lights();
+        assert_ne!(alphas(&e), furniture_only, "the character casts a shadow");
+
+        // Next frame the character has moved; the furniture stays put.
+        e.set_dynamic_occluders(&[(30, 32)]);
+        e.update_dirty_lights();
+        let mut expected = LightingEngine::new(2, 30);
+        expected.set_pixel(28, 30, true);
+        expected.set_pixel(30, 32, true);
+        expected.update_or_add_light_with_solid_color(1, 8, 30, 30, 0);
+        assert_eq!(alphas(&e), alphas(&expected));
+        assert_eq!(e.dynamic_occluders(), &[(30, 32)]);
+
+        e.set_dynamic_occluders(&[(30, 32)]);
         assert!(
-            !e.is_blocked(light_cx, light_cy, probe_cx, probe_cy),
-            "open door should join rooms — ray must not be blocked"
+            e.dirty_lights().is_empty(),
+            "an unchanged frame costs nothing"
         );
+        e.set_dynamic_occluders(&[]);
+        e.update_dirty_lights();
+        assert_eq!(alphas(&e), furniture_only);
     }
 
+    // --- Objects -------------------------------------------------------------
+
     #[test]
-    fn open_door_does_not_dissolve_rest_of_wall_for_lighting() {
-        // Two rooms of different tile types share a horizontal boundary. A
-        // single door opens between (3,3) and (3,4). Rays crossing the
-        // boundary elsewhere (e.g. through (1,3)→(1,4)) must remain blocked.
-        let mut e = LightingEngine::new(4, 8);
-        let tpr = e.tiles_per_row();
-        let cpt = e.cells_per_tile();
-        let mut tiles = vec![0u8; tpr * tpr];
-        for y in 0..tpr {
-            for x in 0..tpr {
-                tiles[y * tpr + x] = if y < 4 { 1 } else { 2 };
-            }
+    fn overlapping_objects_move_and_remove_independently() {
+        let mut e = LightingEngine::new(2, 30);
+        e.update_or_add_object(
+            1,
+            33,
+            29,
+            ObjectShape::Rect {
+                width: 1,
+                height: 3,
+            },
+        );
+        e.update_or_add_object(2, 33, 30, ObjectShape::Cells(vec![(0, 0), (1, 0)]));
+        assert_eq!(e.object_ids(), vec![1, 2]);
+
+        assert!(e.remove_object(1));
+        assert!(
+            e.collision().pixel_map().get_pixel(33, 30),
+            "object 2 still covers it"
+        );
+        assert!(!e.collision().pixel_map().get_pixel(33, 29));
+        assert!(!e.remove_object(1));
+
+        e.update_or_add_light_with_solid_color(1, 8, 30, 30, 0);
+        assert!(e.move_object(2, 30, 26));
+        assert_eq!(e.dirty_lights(), vec![1]);
+        e.update_dirty_lights();
+        assert!(e.set_object_shape(2, ObjectShape::Circle { radius: 1 }));
+        e.update_dirty_lights();
+        assert_eq!(e.object(2).map(Object::position), Some((30, 26)));
+
+        let mut expected = LightingEngine::new(2, 30);
+        for (x, y) in [(30, 25), (29, 26), (30, 26), (31, 26), (30, 27)] {
+            expected.set_pixel(x, y, true);
         }
-        e.set_tile_map(tiles);
-        // Door joins exactly tile (3,3) and (3,4).
-        e.set_door_edge(3 * tpr + 3, 4 * tpr + 3, true);
+        expected.update_or_add_light_with_solid_color(1, 8, 30, 30, 0);
+        assert_eq!(
+            e.light_canvas(1)
+                .unwrap()
+                .iter()
+                .map(|c| c.3)
+                .collect::<Vec<_>>(),
+            expected
+                .light_canvas(1)
+                .unwrap()
+                .iter()
+                .map(|c| c.3)
+                .collect::<Vec<_>>()
+        );
+    }
 
-        // Probe across the boundary at a column far from the door (col 1).
-        // Cell coords: x=1*cpt+cpt/2, y just above and just below the boundary.
-        let cx = (1 * cpt + cpt / 2) as i16;
-        let cy_upper = (3 * cpt + cpt / 2) as i16;
-        let cy_lower = (4 * cpt + cpt / 2) as i16;
+    #[test]
+    fn set_pixel_cannot_punch_holes_in_objects() {
+        let mut e = LightingEngine::new(2, 30);
+        e.update_or_add_object(
+            7,
+            10,
+            10,
+            ObjectShape::Rect {
+                width: 2,
+                height: 1,
+            },
+        );
+        e.set_pixel(10, 10, false);
+        assert!(e.collision().pixel_map().get_pixel(10, 10));
+        e.set_pixel(11, 10, true);
+        e.remove_object(7);
+        assert!(!e.collision().pixel_map().get_pixel(10, 10));
         assert!(
-            e.is_blocked(cx, cy_upper, cx, cy_lower),
-            "ray crossing the boundary far from the door must stay blocked"
+            e.collision().pixel_map().get_pixel(11, 10),
+            "the plain write outlives the object"
         );
+
+        e.update_or_add_object(8, 0, 0, ObjectShape::Circle { radius: 2 });
+        e.clear_pixel_collisions();
+        assert!(e.object_ids().is_empty());
     }
 
+    // --- Collision channels ------------------------------------------------
+
     #[test]
-    fn open_door_does_not_dissolve_rest_of_wall_for_pathfinding() {
-        // Same shape as the lighting test: one door at column 3 must not let
-        // pathfinding cross the boundary at column 1.
-        let mut e = LightingEngine::new(2, 8);
-        let tpr = e.tiles_per_row();
-        let mut tiles = vec![0u8; tpr * tpr];
-        for y in 0..tpr {
-            for x in 0..tpr {
-                tiles[y * tpr + x] = if y < 4 { 1 } else { 2 };
-            }
-        }
-        e.set_tile_map(tiles);
-        e.set_door_edge(3 * tpr + 3, 4 * tpr + 3, true);
+    fn cell_channels_block_only_their_own_queries() {
+        let alphas = |e: &LightingEngine| -> Vec<u8> {
+            e.light_canvas(1).unwrap().iter().map(|c| c.3).collect()
+        };
+        let fov = |e: &mut LightingEngine| -> Vec<u8> {
+            e.compute_fov(&[30, 30]);
+            e.fov_canvas().unwrap().iter().map(|c| c.3).collect()
+        };
+        let mut open = LightingEngine::new(2, 30);
+        open.update_or_add_light_with_solid_color(1, 8, 30, 30, 0);
+        let open_fov = fov(&mut open);
 
-        // Path from (1, 3) to (1, 4) — different columns from the door —
-        // would have to detour through (3, 3)→(3, 4) (the door) and back.
-        // We don't assert path length, only that the path stays *off* the
-        // boundary at non-door columns. Easier: assert that the boundary at
-        // col 1 is NOT itself traversable as a direct step.
-        let row3_col1 = 3 * tpr + 1;
-        let row4_col1 = 4 * tpr + 1;
-        assert!(
-            !e.neighbours(row3_col1, false).contains(&row4_col1),
-            "non-door boundary must not be a neighbour"
+        let mut e = LightingEngine::new(2, 30);
+        e.update_or_add_light_with_solid_color(1, 8, 30, 30, 0);
+        e.set_pixel_channels(32, 30, Channels::MOVEMENT); // a low crate
+        assert!(e.dirty_lights().is_empty(), "light never sees the crate");
+        e.set_pixel_channels(30, 32, Channels::SIGHT); // a hologram
+        e.update_dirty_lights();
+
+        assert!(e.is_blocked_for(Channel::Movement, 30, 30, 35, 30));
+        assert!(!e.is_blocked_for(Channel::Light, 30, 30, 35, 30));
+        assert!(!e.is_blocked_for(Channel::Sight, 30, 30, 35, 30));
+        assert!(e.is_blocked_for(Channel::Sight, 30, 30, 30, 35));
+        assert!(!e.is_blocked_for(Channel::Light, 30, 30, 30, 35));
+        assert_eq!(alphas(&e), alphas(&open));
+        assert_ne!(
+            fov(&mut e),
+            open_fov,
+            "the hologram hides what is behind it"
         );
+
+        e.set_pixel_channels(30, 32, Channels::NONE);
+        assert_eq!(fov(&mut e), open_fov);
+        e.set_pixel(30, 32, true);
+        assert_eq!(e.collision().cell_channels(30, 32), Channels::ALL);
     }
 
-    // --- Ambient emitter ---------------------------------------------------
+    #[test]
+    fn tile_type_channels_open_walls_per_channel() {
+        // Left half type 1, right half type 2, on a 6×6 tile map.
+        let mut e = LightingEngine::new(2, 6);
+        e.set_tile_map((0..36).map(|i| if i % 6 < 3 { 1 } else { 2 }).collect());
+        assert!(e.path(0, 1, 4, 1).is_empty());
+        assert!(!e.cast_ray(0, 1, 4, 1));
+        assert!(e.is_blocked_for(Channel::Light, 3, 3, 9, 3));
 
-    /// Helper: is the cell at the centre of tile `(tx, ty)` opaque (in-room)?
-    fn ambient_cell_opaque(e: &LightingEngine, id: u8, tx: usize, ty: usize) -> bool {
-        let cpt = e.cells_per_tile();
-        let cpr = e.cells_per_row();
-        let cx = tx * cpt + cpt / 2;
-        let cy = ty * cpt + cpt / 2;
-        let canvas = e.ambient_canvas(id).expect("ambient exists");
-        canvas[cy * cpr + cx].3 != 0
+        // A glass case: blocks movement only.
+        e.set_tile_type_channels(2, Channels::MOVEMENT);
+        assert!(e.path(0, 1, 4, 1).is_empty());
+        assert!(e.cast_ray(0, 1, 4, 1));
+        assert!(!e.is_blocked_for(Channel::Light, 3, 3, 9, 3));
+        assert!(e.is_blocked_for(Channel::Movement, 3, 3, 9, 3));
+
+        // A curtain: blocks light and sight, not movement.
+        e.set_tile_type_channels(2, Channels::LIGHT | Channels::SIGHT);
+        assert_eq!(e.path(0, 1, 4, 1).len(), 5);
+        assert!(!e.cast_ray(0, 1, 4, 1));
+        assert!(e.is_blocked_for(Channel::Light, 3, 3, 9, 3));
+        assert_eq!(e.tile_type_channels(2), Channels::LIGHT | Channels::SIGHT);
+        assert_eq!(e.tile_type_channels(1), Channels::ALL);
     }
 
     #[test]
-    fn ambient_floods_only_its_room() {
-        // Two rooms separated by a column of wall tiles (type 0) at x=2.
-        let mut e = LightingEngine::new(2, 5);
-        let mut tiles = vec![1u8; 25];
-        for y in 0..5 {
-            tiles[y * 5 + 2] = 0; // wall column
-        }
-        e.set_tile_map(tiles);
+    fn clearing_objects_keeps_rooms_doors_and_tile_type_channels() {
+        let snapshot = |e: &mut LightingEngine| {
+            e.update_dirty_lights();
+            let light: Vec<u8> = e.light_canvas(1).unwrap().iter().map(|c| c.3).collect();
+            e.compute_fov(&[3, 3]);
+            let fov: Vec<u8> = e.fov_canvas().unwrap().iter().map(|c| c.3).collect();
+            let glass =
+                [Channel::Light, Channel::Movement].map(|c| e.is_blocked_for(c, 7, 9, 8, 9));
+            (e.path(0, 1, 5, 1), light, fov, glass)
+        };
+        let mut e = three_rooms_with_doors();
+        e.set_tile_type_channels(3, Channels::MOVEMENT); // glass between rooms 2 and 3
+        e.update_or_add_light_with_solid_color(1, 8, 3, 3, 0);
+        let before = snapshot(&mut e);
+        assert_eq!(before.0.len(), 6, "the path crosses both doors");
+        assert_eq!(before.3, [false, true]);
 
-        // Emitter in the west room (tile (1,1)).
-        e.update_or_add_ambient(0, 1, 1, 140, 130, 120);
+        e.update_or_add_object(
+            1,
+            1,
+            1,
+            ObjectShape::Rect {
+                width: 1,
+                height: 1,
+            },
+        );
+        e.clear_pixel_collisions();
+        assert!(e.object_ids().is_empty());
+        assert_eq!(snapshot(&mut e), before);
+    }
 
-        // West-room cells are filled with the authored colour...
-        let cpr = e.cells_per_row();
-        let cpt = e.cells_per_tile();
-        let west = e.ambient_canvas(0).unwrap()[(1 * cpt + 1) * cpr + (1 * cpt + 1)];
-        assert_eq!((west.0, west.1, west.2, west.3), (140, 130, 120, 255));
-        assert!(ambient_cell_opaque(&e, 0, 0, 0), "same-room tile filled");
+    // --- Window edges ------------------------------------------------------
 
-        // ...east-room cells (x>=3) stay transparent.
-        assert!(!ambient_cell_opaque(&e, 0, 3, 1), "east room must be dark");
-        assert!(!ambient_cell_opaque(&e, 0, 4, 0), "east room must be dark");
-        // Wall column itself stays transparent.
-        assert!(!ambient_cell_opaque(&e, 0, 2, 1), "wall tile must be dark");
+    #[test]
+    fn window_passes_light_and_sight_but_not_movement() {
+        // Left half type 1, right half type 2; a window between tiles 2 and
+        // 3 of the top row.
+        let mut e = LightingEngine::new(2, 6);
+        e.set_tile_map((0..36).map(|i| if i % 6 < 3 { 1 } else { 2 }).collect());
+        e.update_or_add_light_with_solid_color(1, 4, 4, 1, 0);
+        e.update_dirty_lights();
+        e.set_window_edge(3, 2, true);
+        assert_eq!(e.dirty_lights(), vec![1]);
+        assert!(e.has_window_edge(2, 3));
+
+        assert!(!e.is_blocked_for(Channel::Light, 4, 1, 8, 1));
+        assert!(!e.is_blocked_for(Channel::Sight, 4, 1, 8, 1));
+        assert!(e.is_blocked_for(Channel::Movement, 4, 1, 8, 1));
+        assert!(
+            e.is_blocked_for(Channel::Light, 4, 5, 8, 5),
+            "only along the window"
+        );
+        assert!(e.cast_ray(0, 0, 4, 0));
+        assert!(e.path(0, 0, 4, 0).is_empty());
+        assert_ne!(e.tile_find(2), e.tile_find(3), "the rooms stay apart");
+
+        e.update_dirty_lights();
+        assert!(
+            brightness_at_world(&e, 1, 7, 1) > 0,
+            "lit through the glass"
+        );
+        e.clear_window_edges();
+        e.update_dirty_lights();
+        assert_eq!(brightness_at_world(&e, 1, 7, 1), 0);
     }
 
     #[test]
-    fn ambient_does_not_cross_door_open_or_closed() {
-        // Two same-... no: two rooms of different tile types share a vertical
-        // boundary at column x=2 (west type 1, east type 2). A door joins them
-        // for pathfinding/lighting, but ambient is `tile_uf`-bounded and must
-        // not cross regardless of door state.
-        let mut e = LightingEngine::new(2, 5);
-        let mut tiles = vec![1u8; 25];
-        for y in 0..5 {
-            for x in 2..5 {
-                tiles[y * 5 + x] = 2;
-            }
-        }
-        e.set_tile_map(tiles);
+    fn windows_survive_object_clears() {
+        let mut e = LightingEngine::new(2, 6);
+        e.set_tile_map((0..36).map(|i| if i % 6 < 3 { 1 } else { 2 }).collect());
+        e.set_window_edge(2, 3, true);
+        e.set_pixel(6, 0, true);
+        e.clear_pixel_collisions();
 
-        // Closed door: east room dark.
-        e.update_or_add_ambient(0, 1, 1, 100, 100, 100);
-        assert!(ambient_cell_opaque(&e, 0, 1, 1), "west room filled");
-        assert!(!ambient_cell_opaque(&e, 0, 3, 1), "closed door: east dark");
+        assert!(!e.is_blocked_for(Channel::Light, 4, 1, 8, 1));
+        assert!(!e.is_blocked_for(Channel::Sight, 4, 1, 8, 1));
+        assert!(e.is_blocked_for(Channel::Movement, 4, 1, 8, 1));
+        assert!(e.path(0, 0, 4, 0).is_empty());
+        assert!(e.cast_ray(0, 0, 4, 0));
+    }
 
-        // Open the door between (1,1) and (2,1); re-flood. Still east dark.
-        e.set_door_edge(1 * 5 + 1, 1 * 5 + 2, true);
-        e.update_or_add_ambient(0, 1, 1, 100, 100, 100);
-        assert!(ambient_cell_opaque(&e, 0, 1, 1), "west room still filled");
-        assert!(
-            !ambient_cell_opaque(&e, 0, 3, 1),
-            "open door must NOT let ambient cross (tile_uf is door-agnostic)"
+    // --- Thin walls ----------------------------------------------------------
+
+    #[test]
+    fn thin_tile_walls_partition_without_splitting_the_room() {
+        let mut e = LightingEngine::new(2, 6);
+        e.set_tile_map(vec![1; 36]);
+        e.update_or_add_ambient(0, 0, 0, 200, 200, 200);
+        for ty in 0..6 {
+            e.set_wall_edge(ty * 6 + 2, ty * 6 + 3, true);
+        }
+        assert!(e.path(0, 0, 5, 0).is_empty());
+        assert!(!e.cast_ray(0, 0, 5, 0));
+        assert!(e.is_blocked(4, 4, 8, 4));
+        assert!(e.block_map()[5].e_blocked && e.block_map()[6].w_blocked);
+        assert_eq!(e.tile_find(2), e.tile_find(3), "still one Room");
+        e.update_dirty_lights();
+        assert_eq!(
+            ambient_red(&e, 0, 5, 5),
+            200,
+            "the ambient ignores thin walls"
         );
+
+        // A gap in the partition lets the path through.
+        e.set_wall_edge(3 * 6 + 2, 3 * 6 + 3, false);
+        assert_eq!(e.path(0, 0, 5, 0).len(), 12);
+        e.clear_wall_edges();
+        assert!(e.wall_edges().is_empty());
+        assert!(!e.block_map()[5].e_blocked);
+        assert!(!e.is_blocked(4, 4, 8, 4));
     }
 
     #[test]
-    fn ambient_on_non_floor_tile_is_empty() {
-        let mut e = LightingEngine::new(2, 5);
-        // Whole map is floor except tile (1,1) which is a wall (type 0).
-        let mut tiles = vec![1u8; 25];
-        tiles[1 * 5 + 1] = 0;
-        e.set_tile_map(tiles);
+    fn cell_wall_edges_block_rays_but_not_tile_paths() {
+        let mut e = LightingEngine::new(2, 6);
+        e.set_tile_map(vec![1; 36]);
+        e.update_or_add_light_with_solid_color(1, 4, 3, 1, 0);
+        e.set_cell_wall_edge(6, 1, 5, 1, true);
+        assert_eq!(e.dirty_lights(), vec![1]);
+        assert!(e.has_cell_wall_edge(5, 1, 6, 1));
+        assert!(e.is_blocked(3, 1, 7, 1));
+        assert!(!e.is_blocked(3, 0, 7, 0), "only that one cell edge");
+        assert_eq!(
+            e.path(0, 0, 5, 0).len(),
+            6,
+            "half a tile edge is not a wall"
+        );
 
-        e.update_or_add_ambient(0, 1, 1, 200, 50, 50);
-        let canvas = e.ambient_canvas(0).unwrap();
+        e.set_cell_wall_edge(0, 0, 1, 1, true);
+        assert_eq!(e.wall_edges().len(), 1, "diagonal pairs are ignored");
+    }
+
+    #[test]
+    fn wall_edits_refresh_flags_like_a_full_recompute() {
+        let mut e = LightingEngine::new(2, 6);
+        e.set_tile_map((0..36).map(|i| (i % 6 / 3 + 1) as u8).collect());
+        e.set_tile_diagonal(7, Some(Diagonal::NwSe));
+        e.set_wall_edge(7, 8, true);
+        e.set_cell_wall_edge(2, 3, 3, 3, true);
+        e.set_cell_wall_edge(2, 3, 3, 3, false);
+        e.set_wall_edge(13, 14, true);
+        e.set_wall_edge(7, 8, false);
+        let flags = |e: &LightingEngine| -> Vec<[bool; 4]> {
+            let edges = |c: &CellDetails| [c.n_blocked, c.e_blocked, c.s_blocked, c.w_blocked];
+            e.block_map().iter().map(edges).collect()
+        };
+        let incremental = flags(&e);
+        e.recompute_block_map();
+        assert_eq!(incremental, flags(&e));
         assert!(
-            canvas.iter().all(|c| c.3 == 0),
-            "emitter on a non-floor tile emits a fully transparent canvas"
+            e.is_blocked(2, 3, 3, 3),
+            "the diagonal's own cut stays walled"
         );
+        assert!(e.is_blocked(3, 4, 4, 4));
     }
 
+    // --- Diagonal walls ------------------------------------------------------
+
     #[test]
-    fn ambient_out_of_range_is_empty() {
-        let mut e = LightingEngine::new(2, 5);
-        e.set_tile_map(vec![1u8; 25]);
-        e.update_or_add_ambient(0, -1, 99, 10, 20, 30);
-        let canvas = e.ambient_canvas(0).unwrap();
-        assert!(canvas.iter().all(|c| c.3 == 0), "out-of-range → empty canvas");
+    fn diagonal_splits_a_tile_between_the_rooms_on_either_side() {
+        // A closet tile of type 1 walled in by type 2, cut NW-SE: only
+        // its north-east half is reachable from the corridor to the east.
+        let mut e = LightingEngine::new(4, 3);
+        e.set_tile_map(vec![2, 2, 2, 2, 1, 1, 2, 2, 2]);
+        e.set_tile_diagonal(4, Some(Diagonal::NwSe));
+        assert_eq!(e.tile_diagonal(4), Some(Diagonal::NwSe));
+        let room = |e: &LightingEngine, x, y| e.collision.cell_room(x, y).unwrap();
+        assert_eq!(
+            room(&e, 7, 4),
+            room(&e, 9, 5),
+            "north-east half joins the corridor"
+        );
+        assert_ne!(room(&e, 4, 7), room(&e, 7, 4));
+        assert!(e.is_blocked(6, 4, 4, 6), "rays cannot cross the diagonal");
+        assert!(
+            !e.is_blocked(9, 5, 5, 5),
+            "the diagonal cell is on the north side"
+        );
+        let flags = e.block_map()[5 * 12 + 5];
+        assert!(flags.s_blocked && flags.w_blocked && !flags.n_blocked && !flags.e_blocked);
+        assert!(e.block_map()[5 * 12 + 4].e_blocked);
+
+        e.set_tile_diagonal(4, None);
+        assert_eq!(room(&e, 4, 7), room(&e, 7, 4));
+        assert!(!e.is_blocked(6, 4, 4, 6));
+        assert!(!e.block_map()[5 * 12 + 5].s_blocked);
     }
 
     #[test]
-    fn two_ambient_emitters_fill_their_respective_rooms() {
-        // Two rooms split by a wall column at x=2; an emitter in each.
-        let mut e = LightingEngine::new(2, 5);
-        let mut tiles = vec![1u8; 25];
-        for y in 0..5 {
-            tiles[y * 5 + 2] = 0;
-        }
-        e.set_tile_map(tiles);
+    fn ambients_fill_only_the_diagonal_half_their_room_reaches() {
+        let mut e = LightingEngine::new(4, 3);
+        e.set_tile_map(vec![2, 2, 2, 2, 1, 1, 2, 2, 2]);
+        e.update_or_add_ambient(0, 2, 1, 90, 90, 90);
+        let alpha =
+            |e: &LightingEngine, x: usize, y: usize| e.ambient_canvas(0).unwrap()[y * 12 + x].3;
+        assert_eq!(alpha(&e, 4, 7), 255);
 
-        e.update_or_add_ambient(0, 1, 1, 100, 0, 0); // west
-        e.update_or_add_ambient(1, 3, 1, 0, 0, 100); // east
+        e.set_tile_diagonal(4, Some(Diagonal::NwSe));
+        e.update_dirty_lights();
+        assert_eq!(alpha(&e, 7, 4), 255, "the corridor's half");
+        assert_eq!(
+            alpha(&e, 5, 5),
+            255,
+            "the diagonal cells sit on the north side"
+        );
+        assert_eq!(alpha(&e, 4, 7), 0, "the cut-off half stays dark");
+        assert_eq!(alpha(&e, 4, 5), 0);
 
-        assert!(ambient_cell_opaque(&e, 0, 1, 1) && !ambient_cell_opaque(&e, 0, 3, 1));
-        assert!(ambient_cell_opaque(&e, 1, 3, 1) && !ambient_cell_opaque(&e, 1, 1, 1));
+        e.set_tile_diagonal(4, None);
+        e.update_dirty_lights();
+        assert_eq!(alpha(&e, 4, 7), 255);
     }
 
     #[test]
-    fn ambient_reflood_tracks_room_after_tile_edit() {
-        // An emitter floods the whole one-room map; painting a wall that splits
-        // the room (and re-flooding) leaves only the emitter's half lit.
-        let mut e = LightingEngine::new(2, 5);
-        e.set_tile_map(vec![1u8; 25]);
-        e.update_or_add_ambient(0, 0, 1, 80, 80, 80);
-        assert!(ambient_cell_opaque(&e, 0, 4, 1), "single room: far tile lit");
+    fn diagonal_halves_stay_apart_after_object_clears() {
+        let mut e = LightingEngine::new(4, 3);
+        e.set_tile_map(vec![2, 2, 2, 2, 1, 1, 2, 2, 2]);
+        e.update_or_add_ambient(0, 2, 1, 90, 90, 90);
+        e.set_tile_diagonal(4, Some(Diagonal::NwSe));
+        e.set_pixel(1, 1, true);
+        e.update_dirty_lights();
+        let fill: Vec<u8> = e.ambient_canvas(0).unwrap().iter().map(|c| c.3).collect();
 
-        // Split with a wall column at x=2.
-        for y in 0..5 {
-            e.set_tile(2, y as u32, 0);
-        }
-        e.update_or_add_ambient(0, 0, 1, 80, 80, 80);
-        assert!(ambient_cell_opaque(&e, 0, 0, 1), "emitter half stays lit");
-        assert!(!ambient_cell_opaque(&e, 0, 4, 1), "far half now dark after split");
+        e.clear_pixel_collisions();
+        let room = |x, y| e.collision().cell_room(x, y);
+        assert_ne!(room(7, 4), room(4, 7), "the cut still splits the tile");
+        assert_eq!(room(7, 4), room(9, 5));
+        assert!(e.dirty_ambients.contains(&0));
+        e.update_dirty_lights();
+        let alphas: Vec<u8> = e.ambient_canvas(0).unwrap().iter().map(|c| c.3).collect();
+        assert_eq!(alphas, fill);
     }
 
-    // --- FOV canvas --------------------------------------------------------
+    #[test]
+    fn diagonal_shadows_lights_and_survives_object_clears() {
+        let light_alphas = |e: &LightingEngine, id| -> Vec<u8> {
+            e.light_canvas(id).unwrap().iter().map(|c| c.3).collect()
+        };
+        let mut e = LightingEngine::new(4, 3);
+        e.set_tile_map(vec![1; 9]);
+        e.update_or_add_light_with_solid_color(1, 6, 7, 4, 0);
+        e.update_dirty_lights();
+        let open = light_alphas(&e, 1);
+        e.set_tile_diagonal(4, Some(Diagonal::NwSe));
+        assert_eq!(e.dirty_lights(), vec![1]);
+        e.update_dirty_lights();
+        assert_ne!(light_alphas(&e, 1), open, "the diagonal casts a shadow");
+        let shadowed = light_alphas(&e, 1);
 
-    /// Helper: is cell `(cx, cy)` opaque (visible) in the last FOV canvas?
-    fn fov_visible(e: &LightingEngine, cx: usize, cy: usize) -> bool {
-        let cpr = e.cells_per_row();
-        e.fov_canvas().expect("compute_fov was called")[cy * cpr + cx].3 != 0
+        e.clear_pixel_collisions();
+        e.update_dirty_lights();
+        assert_eq!(light_alphas(&e, 1), shadowed);
+        e.clear_tile_diagonals();
+        e.update_dirty_lights();
+        assert_eq!(light_alphas(&e, 1), open);
     }
 
-    /// Helper: count visible cells whose `cx` falls in `[cx_lo, cx_hi)`.
-    /// The low-res test ray table (36 angles / dist 10) paints a holey disc, so
-    /// region counts are robust where single-cell sampling is flaky.
-    fn fov_count_in_columns(e: &LightingEngine, cx_lo: usize, cx_hi: usize) -> usize {
-        let cpr = e.cells_per_row();
-        let canvas = e.fov_canvas().expect("compute_fov was called");
-        let mut n = 0;
-        for cy in 0..cpr {
-            for cx in cx_lo..cx_hi {
-                if canvas[cy * cpr + cx].3 != 0 {
-                    n += 1;
-                }
-            }
-        }
-        n
+    // --- Composite canvas --------------------------------------------------
+
+    #[test]
+    fn composite_is_empty_without_layers() {
+        let mut e = LightingEngine::new(2, 30);
+        e.compute_composite();
+        let canvas = e.composite_canvas().expect("computed");
+        assert_eq!(canvas.len(), e.cells_per_row() * e.cells_per_row());
+        assert!(canvas.iter().all(|c| c.3 == 0), "no layers → transparent");
     }
 
     #[test]
-    fn fov_single_viewer_in_open_room() {
-        // Empty world = one big room, no walls. A viewer sees its own cell and a
-        // fan of nearby cells, but nothing past the ray table's max distance.
+    fn composite_places_light_canvas_at_its_position() {
         let mut e = LightingEngine::new(2, 30);
-        e.compute_fov(&[20, 20]);
+        e.update_or_add_light_with_solid_color(1, 3, 20, 25, 0);
+        e.compute_composite();
         let cpr = e.cells_per_row();
-        // The viewer's own cell is opaque white — binary alpha, no falloff.
-        let here = e.fov_canvas().expect("computed")[20 * cpr + 20];
-        assert_eq!((here.0, here.1, here.2, here.3), (255, 255, 255, 255));
-        // Rays fan out, so many cells around the viewer are lit.
-        assert!(
-            fov_count_in_columns(&e, 0, cpr) > 20,
-            "an open-room viewer lights a fan of cells"
+        let canvas = e.composite_canvas().unwrap();
+        let light = e.light_canvas(1).unwrap();
+        let side = e.light_canvas_size(1).unwrap();
+        let centre = light[(side / 2) * side + side / 2];
+        let here = canvas[25 * cpr + 20];
+        assert_eq!(
+            (here.0, here.1, here.2, here.3),
+            (centre.0, centre.1, centre.2, centre.3)
         );
-        // Sight is capped at the ray table's max distance (10 cells in test
-        // builds): a cell well beyond it stays dark.
-        assert!(!fov_visible(&e, 20, 35), "a far cell is out of sight");
+        assert_eq!(canvas[0].3, 0, "cells outside every layer stay transparent");
     }
 
     #[test]
-    fn fov_blocked_by_wall() {
-        // Two rooms split by a wall column (type 0) at tile x=4; rest type 1.
-        // Without the wall the viewer would see east-room cells; the wall must
-        // occlude every one of them.
-        let mut e = LightingEngine::new(4, 8);
-        let tpr = e.tiles_per_row();
-        let cpt = e.cells_per_tile();
-        let wall_cx = (tpr / 2) * cpt; // first east-of-wall cell column
-        let vx = (3 * cpt + cpt / 2) as i16;
-        let vy = (4 * cpt + cpt / 2) as i16;
+    fn composite_blends_overlapping_layers() {
+        let mut e = LightingEngine::new(2, 30);
+        e.set_tile_map(vec![1u8; 30 * 30]);
+        e.update_or_add_ambient(0, 0, 0, 40, 40, 40);
+        e.update_or_add_light_with_solid_color(1, 3, 20, 20, 0);
         let cpr = e.cells_per_row();
+        let lit = e.light_canvas(1).unwrap()[3 * 7 + 3];
 
-        // Baseline: open room, viewer sees across into the (future) east columns.
-        e.set_tile_map(vec![1u8; tpr * tpr]);
-        e.compute_fov(&[vx, vy]);
-        assert!(
-            fov_count_in_columns(&e, wall_cx, cpr) > 0,
-            "without a wall the viewer sees east-of-boundary cells"
-        );
+        e.set_blend_mode(BlendMode::Additive);
+        e.compute_composite();
+        let add = e.composite_canvas().unwrap()[20 * cpr + 20];
+        assert_eq!(add.0, lit.0.saturating_add(40));
+        assert_eq!(add.2, lit.2.saturating_add(40));
 
-        // Now raise the wall column and re-cast: the east room goes fully dark.
-        let mut tiles = vec![1u8; tpr * tpr];
-        for y in 0..tpr {
-            tiles[y * tpr + tpr / 2] = 0;
-        }
-        e.set_tile_map(tiles);
-        e.compute_fov(&[vx, vy]);
-        assert!(fov_visible(&e, vx as usize, vy as usize), "viewer cell still lit");
-        assert_eq!(
-            fov_count_in_columns(&e, wall_cx, cpr),
-            0,
-            "wall must occlude all sight into the east room"
-        );
+        e.set_blend_mode(BlendMode::Max);
+        e.compute_composite();
+        let max = e.composite_canvas().unwrap()[20 * cpr + 20];
+        assert_eq!(max.0, lit.0.max(40));
+        assert_eq!(max.2, lit.2.max(40));
     }
 
     #[test]
-    fn fov_blocked_without_door_visible_with_open_door() {
-        // West tiles type 1 (x<4), east tiles type 2 (x>=4): a room boundary at
-        // the tile-3 / tile-4 edge. A viewer in the west reaches the east room
-        // only when a door is open across that exact edge.
-        let mut e = LightingEngine::new(4, 8);
-        let tpr = e.tiles_per_row();
-        let cpt = e.cells_per_tile();
-        let mut tiles = vec![1u8; tpr * tpr];
-        for y in 0..tpr {
-            for x in (tpr / 2)..tpr {
-                tiles[y * tpr + x] = 2;
+    fn composite_is_independent_of_insertion_order() {
+        let build = |order: &[LightId]| {
+            let mut e = LightingEngine::new(2, 30);
+            e.set_blend_mode(BlendMode::Screen);
+            for &id in order {
+                e.update_or_add_light_with_solid_color(id, 4, 20 + id as i16, 20, id as u8 * 40);
             }
-        }
-        e.set_tile_map(tiles);
+            e.compute_composite();
+            e.composite_canvas()
+                .unwrap()
+                .iter()
+                .map(|c| (c.0, c.1, c.2, c.3))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(build(&[1, 2, 3]), build(&[3, 1, 2]));
+    }
 
-        let vx = (3 * cpt + cpt / 2) as i16;
-        let vy = (4 * cpt + cpt / 2) as i16;
-        let east_cx = (tpr / 2) * cpt; // first east-room cell column
-        let cpr = e.cells_per_row();
+    // --- Intensity and tone mapping ----------------------------------------
 
-        // No door: the boundary occludes sight into the east room.
-        e.compute_fov(&[vx, vy]);
+    #[test]
+    fn intensity_scales_light_canvas() {
+        let mut e = LightingEngine::new(2, 30);
+        let centre = |e: &LightingEngine| {
+            let side = e.light_canvas_size(1).unwrap();
+            e.light_canvas(1).unwrap()[(side / 2) * side + side / 2]
+        };
+        e.update_or_add_light_with_rgb(1, 5, 20, 20, 200, 100, 40);
+        e.update_or_add_light_with_intensity(1, 5, 20, 20, 64);
+        let dim = centre(&e);
         assert_eq!(
-            fov_count_in_columns(&e, east_cx, cpr),
-            0,
-            "closed boundary blocks all sight into the east room"
+            (dim.0, dim.1, dim.2),
+            (50, 25, 10),
+            "quarter-strength candle"
         );
+        assert_eq!(e.light_intensity(1), Some(64));
 
-        // Open the door between tile (3,4) and (4,4): sight crosses there.
-        e.set_door_edge(4 * tpr + 3, 4 * tpr + 4, true);
-        e.compute_fov(&[vx, vy]);
-        assert!(
-            fov_count_in_columns(&e, east_cx, cpr) > 0,
-            "open door lets sight cross into the east room"
+        e.update_or_add_light_with_intensity(1, 5, 20, 20, 1024);
+        let bright = centre(&e);
+        assert_eq!(
+            (bright.0, bright.1, bright.2),
+            (255, 255, 160),
+            "own canvas clamps"
         );
     }
 
     #[test]
-    fn fov_multi_viewer_union() {
-        // Two viewers far enough apart that neither alone covers the other. The
-        // single FOV canvas is the union — both viewer cells are lit at once.
+    fn bright_lights_accumulate_in_hdr_and_tone_map() {
         let mut e = LightingEngine::new(2, 30);
-        e.compute_fov(&[10, 10, 50, 50]);
-        assert!(fov_visible(&e, 10, 10), "viewer A's cell visible");
-        assert!(fov_visible(&e, 50, 50), "viewer B's cell visible");
-        // A cell far from both viewers stays dark (out of range of either).
-        assert!(!fov_visible(&e, 30, 30), "midpoint out of both viewers' range");
-    }
+        e.update_or_add_light_with_rgb(1, 5, 20, 20, 200, 0, 0);
+        e.update_or_add_light_with_intensity(1, 5, 20, 20, 512);
+        e.update_or_add_light_with_rgb(2, 5, 20, 20, 40, 0, 0);
+        let cpr = e.cells_per_row();
 
-    #[test]
-    fn fov_empty_viewers_is_fully_transparent() {
-        let mut e = LightingEngine::new(2, 30);
-        e.compute_fov(&[]);
-        let canvas = e.fov_canvas().expect("computed");
+        e.compute_composite();
+        let hdr = e.composite_hdr_canvas().unwrap()[20 * cpr + 20];
+        // 2 × 200 + 40, in HDR units of 16 per 8-bit level.
+        assert_eq!(hdr.0, (2 * 200 + 40) * 16);
+        assert_eq!(
+            e.composite_canvas().unwrap()[20 * cpr + 20].0,
+            255,
+            "clamp clips"
+        );
+
+        e.set_tone_map(ToneMap::Reinhard);
+        e.compute_composite();
+        let reinhard = e.composite_canvas().unwrap()[20 * cpr + 20].0;
         assert!(
-            canvas.iter().all(|c| c.3 == 0),
-            "no viewers → fully transparent canvas"
+            reinhard > 127 && reinhard < 255,
+            "Reinhard compresses: {reinhard}"
         );
+
+        e.set_tone_map(ToneMap::Exposure(128));
+        e.compute_composite();
+        assert_eq!(e.composite_canvas().unwrap()[20 * cpr + 20].0, 220);
     }
 
     #[test]
@@ -0,0 +1,135 @@
+//! Compact encodings of engine canvases for transfer across the WASM boundary.
+//!
+//! Every canvas is rendered as RGBA8 ([`Color`]) — 32 bits per cell even for
+//! the binary FOV mask. The encoders here re-pack a rendered canvas into
+//! the format the consumer actually needs:
+//!
+//! - **luma8**: one byte of Rec. 709 luminance per cell
+//! - **bits**: one bit per cell, set where the cell is non-transparent,
+//!   packed least-significant bit first, row-major
+//! - **rgba16**: the raw [`Hdr`] channels ([`HDR_ONE`] = 1.0)
+//! - **f32**: the same channels as floats, `1.0` = [`HDR_ONE`]
+//!
+//! [`crate::engine::LightingEngine`] owns one reusable buffer per format, so a
+//! pointer handed to JS stays valid until the next encode into that format.
+
+use crate::engine::LightId;
+use crate::lighting::{Color, Hdr, HDR_ONE};
+
+/// Which rendered canvas to encode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CanvasSource {
+    /// A light's canvas (its bounding rectangle).
+    Light(LightId),
+    /// An ambient's full-map canvas.
+    Ambient(LightId),
+    /// The full-map emissive canvas.
+    Emissive,
+    /// The full-map FOV mask.
+    Fov,
+    /// The full-map composite.
+    Composite,
+}
+
+impl CanvasSource {
+    /// Decode the WASM-facing numeric form: `0` light `id`, `1` ambient
+    /// `id`, `2` emissive, `3` FOV, `4` composite. Unknown kinds yield `None`.
+    pub fn from_u8(kind: u8, id: LightId) -> Option<Self> {
+        match kind {
+            0 => Some(CanvasSource::Light(id)),
+            1 => Some(CanvasSource::Ambient(id)),
+            2 => Some(CanvasSource::Emissive),
+            3 => Some(CanvasSource::Fov),
+            4 => Some(CanvasSource::Composite),
+            _ => None,
+        }
+    }
+}
+
+/// Engine-owned output buffers, one per format.
+#[derive(Default)]
+pub(crate) struct Encoded {
+    pub(crate) luma8: Vec<u8>,
+    pub(crate) bits: Vec<u8>,
+    pub(crate) rgba16: Vec<u16>,
+    pub(crate) f32: Vec<f32>,
+}
+
+/// Rec. 709 luminance of a colour, with integer weights summing to 256.
+pub(crate) fn luma(c: Color) -> u8 {
+    ((54 * c.0 as u32 + 183 * c.1 as u32 + 19 * c.2 as u32) >> 8) as u8
+}
+
+/// Luminance of each cell.
+pub(crate) fn encode_luma8(src: &[Color], out: &mut Vec<u8>) {
+    out.clear();
+    out.extend(src.iter().map(|&c| luma(c)));
+}
+
+/// One bit per cell — set where alpha is non-zero — packed LSB-first into
+/// `src.len().div_ceil(8)` bytes.
+pub(crate) fn encode_bits(src: &[Color], out: &mut Vec<u8>) {
+    out.clear();
+    out.extend(src.chunks(8).map(|cells| {
+        cells
+            .iter()
+            .enumerate()
+            .fold(0u8, |byte, (bit, c)| byte | (u8::from(c.3 != 0) << bit))
+    }));
+}
+
+/// Interleaved RGBA `u16` channels.
+pub(crate) fn encode_rgba16(src: &[Hdr], out: &mut Vec<u16>) {
+    out.clear();
+    out.extend(src.iter().flat_map(|h| [h.0, h.1, h.2, h.3]));
+}
+
+/// Interleaved RGBA `f32` channels, `1.0` = [`HDR_ONE`].
+pub(crate) fn encode_f32(src: &[Hdr], out: &mut Vec<f32>) {
+    out.clear();
+    let one = HDR_ONE as f32;
+    out.extend(
+        src.iter()
+            .flat_map(|h| [h.0, h.1, h.2, h.3])
+            .map(|v| v as f32 / one),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bits_pack_alpha_lsb_first() {
+        let mut cells = vec![Color::default(); 10];
+        cells[0].3 = 255;
+        cells[3].3 = 1;
+        cells[9].3 = 255;
+        let mut out = Vec::new();
+        encode_bits(&cells, &mut out);
+        assert_eq!(out, vec![0b0000_1001, 0b0000_0010]);
+    }
+
+    #[test]
+    fn luma_weights_green_most_and_white_is_full() {
+        let cells = [
+            Color(255, 255, 255, 255),
+            Color(255, 0, 0, 255),
+            Color(0, 255, 0, 255),
+            Color(0, 0, 255, 255),
+        ];
+        let mut out = Vec::new();
+        encode_luma8(&cells, &mut out);
+        assert_eq!(out, vec![255, 53, 182, 18]);
+    }
+
+    #[test]
+    fn hdr_formats_keep_headroom() {
+        let src = [Hdr(HDR_ONE * 2, HDR_ONE / 2, 0, HDR_ONE)];
+        let (mut wide, mut float) = (Vec::new(), Vec::new());
+        encode_rgba16(&src, &mut wide);
+        encode_f32(&src, &mut float);
+        assert_eq!(wide, vec![8160, 2040, 0, 4080]);
+        assert_eq!(float, vec![2.0, 0.5, 0.0, 1.0]);
+    }
+}
@@ -28,6 +28,11 @@
 //! - [`arctan`]: Fast integer trigonometry functions
 //! - [`ray`]: Bresenham-style line stepping algorithms
 //! - [`block_map`]: World representation and obstacle detection
+//! - [`objects`]: Registry of shaped, movable Objects over the collision bitmap
+//! - [`composite`]: Blending every light and ambient into one full-map canvas
+//! - [`animation`]: Deterministic time-driven light animation
+//! - [`srgb`]: Table-driven sRGB ⇄ linear-light conversion
+//! - [`formats`]: Compact canvas encodings (luminance, bitsets, 16-bit, f32)
 //! - [`constants`]: Global configuration and world dimensions
 //!
 //! # Usage
@@ -84,15 +89,20 @@
 use wasm_bindgen::prelude::*;
 
 // Re-export public modules for library use
+pub mod animation;
 pub mod arctan;
 pub mod block_map;
 pub mod collision;
+pub mod composite;
 pub mod constants;
 pub mod engine;
+pub mod formats;
 pub mod lighting;
 pub mod map_grid;
+pub mod objects;
 pub mod ray;
 pub mod scenarios;
+pub mod srgb;
 
 pub use engine::LightingEngine;
 
@@ -539,23 +549,237 @@ impl WasmLightingEngine {
         self.inner.set_pixel(x, y, blocked != 0);
     }
 
-    /// Clear all object cells (does not touch the tile map).
+    /// Replace the transient occluder layer (moving characters) with the
+    /// cells in `cells` (`[x0, y0, x1, y1, …]`; a trailing odd element is
+    /// ignored). Call it every frame; `set_pixel` Objects are unaffected.
+    pub fn set_dynamic_occluders(&mut self, cells: Vec<u16>) {
+        let cells: Vec<(u16, u16)> = cells.chunks_exact(2).map(|p| (p[0], p[1])).collect();
+        self.inner.set_dynamic_occluders(&cells);
+    }
+
+    /// Choose which collision channels a cell blocks, as a bitmask: `1`
+    /// light, `2` sight, `4` movement (`0` clears it, `7` is `set_pixel`).
+    pub fn set_pixel_channels(&mut self, x: u16, y: u16, channels: u8) {
+        self.inner
+            .set_pixel_channels(x, y, collision::Channels::from_bits(channels));
+    }
+
+    /// Choose which channels (same bitmask as `set_pixel_channels`) the
+    /// Walls of tile type `tile` block. A Wall between two types lets a
+    /// channel through unless both types block it.
+    pub fn set_tile_type_channels(&mut self, tile: u8, channels: u8) {
+        self.inner
+            .set_tile_type_channels(tile, collision::Channels::from_bits(channels));
+    }
+
+    /// Whether the segment between two cells is blocked for `channel`
+    /// (`0` light, `1` sight, `2` movement). Unknown channels report `true`.
+    pub fn is_blocked_for(&self, channel: u8, x0: i16, y0: i16, x1: i16, y1: i16) -> bool {
+        collision::Channel::from_u8(channel)
+            .is_none_or(|channel| self.inner.is_blocked_for(channel, x0, y0, x1, y1))
+    }
+
+    /// Give a cell a translucent material: light passing through is scaled
+    /// by `transmittance` (0-255) and multiplied by the `(r, g, b)` tint.
+    pub fn set_pixel_material(&mut self, x: u16, y: u16, transmittance: u8, r: u8, g: u8, b: u8) {
+        let material = collision::PixelMaterial {
+            transmittance,
+            tint: (r, g, b),
+        };
+        self.inner.set_pixel_material(x, y, Some(material));
+    }
+
+    /// Remove a cell's translucent material.
+    pub fn clear_pixel_material(&mut self, x: u16, y: u16) {
+        self.inner.set_pixel_material(x, y, None);
+    }
+
+    /// Make cell `(x, y)` glow `(r, g, b)`, fading out over `radius` cells.
+    /// Emissive cells are rendered into one batched layer and composited
+    /// between ambients and lights.
+    pub fn set_emissive_cell(&mut self, x: u16, y: u16, r: u8, g: u8, b: u8, radius: u8) {
+        let emissive = lighting::Emissive {
+            color: (r, g, b),
+            radius,
+        };
+        self.inner.set_emissive_cell(x, y, Some(emissive));
+    }
+
+    /// Stop cell `(x, y)` glowing (its tile type's glow, if any, remains).
+    pub fn clear_emissive_cell(&mut self, x: u16, y: u16) {
+        self.inner.set_emissive_cell(x, y, None);
+    }
+
+    /// Make every tile of type `tile` glow `(r, g, b)` over `radius` cells.
+    /// The glow follows `set_tile` edits.
+    pub fn set_emissive_tile_type(&mut self, tile: u8, r: u8, g: u8, b: u8, radius: u8) {
+        let emissive = lighting::Emissive {
+            color: (r, g, b),
+            radius,
+        };
+        self.inner.set_emissive_tile_type(tile, Some(emissive));
+    }
+
+    /// Stop tiles of type `tile` glowing.
+    pub fn clear_emissive_tile_type(&mut self, tile: u8) {
+        self.inner.set_emissive_tile_type(tile, None);
+    }
+
+    /// Re-render the emissive canvas if stale and return a pointer to it
+    /// (`cells_per_row²` RGBA cells). Only needed to draw the glow on its
+    /// own; `compute_composite` already includes it.
+    pub fn compute_emissive(&mut self) -> *const lighting::Color {
+        self.inner.compute_emissive()
+    }
+
+    /// Clear all object cells and their materials, unregistering every
+    /// Object (does not touch the tile map).
     pub fn clear_pixel_collisions(&mut self) {
         self.inner.clear_pixel_collisions();
     }
 
-    /// Record (or remove) a door edge between two tiles. Open doors join
-    /// the two tiles' rooms for both pathfinding and lighting (per ADR-0003).
+    /// Create or replace Object `id` as a `width × height` block whose
+    /// top-left cell is `(x, y)`.
+    pub fn put_object_rect(&mut self, id: u32, x: i16, y: i16, width: u16, height: u16) {
+        self.inner
+            .update_or_add_object(id, x, y, objects::ObjectShape::Rect { width, height });
+    }
+
+    /// Create or replace Object `id` as a disc of `radius` cells centred on
+    /// `(x, y)`.
+    pub fn put_object_circle(&mut self, id: u32, x: i16, y: i16, radius: u16) {
+        self.inner
+            .update_or_add_object(id, x, y, objects::ObjectShape::Circle { radius });
+    }
+
+    /// Create or replace Object `id` from cell offsets relative to `(x, y)`
+    /// (`[dx0, dy0, dx1, dy1, …]`; a trailing odd element is ignored).
+    pub fn put_object_cells(&mut self, id: u32, x: i16, y: i16, offsets: Vec<i16>) {
+        let cells = offsets.chunks_exact(2).map(|p| (p[0], p[1])).collect();
+        self.inner
+            .update_or_add_object(id, x, y, objects::ObjectShape::Cells(cells));
+    }
+
+    /// Create or replace Object `id` from a `width × height` bitmask whose
+    /// top-left cell is `(x, y)`: row-major, least-significant bit first —
+    /// the layout `canvas_bits` returns.
+    pub fn put_object_mask(
+        &mut self,
+        id: u32,
+        x: i16,
+        y: i16,
+        width: u16,
+        height: u16,
+        bits: Vec<u8>,
+    ) {
+        let shape = objects::ObjectShape::Mask {
+            width,
+            height,
+            bits,
+        };
+        self.inner.update_or_add_object(id, x, y, shape);
+    }
+
+    /// Move Object `id`, keeping its shape. Returns `false` if the id is
+    /// unknown.
+    pub fn move_object(&mut self, id: u32, x: i16, y: i16) -> bool {
+        self.inner.move_object(id, x, y)
+    }
+
+    /// Choose which channels (same bitmask as `set_pixel_channels`) Object
+    /// `id` blocks. Returns `false` if the id is unknown.
+    pub fn set_object_channels(&mut self, id: u32, channels: u8) -> bool {
+        self.inner
+            .set_object_channels(id, collision::Channels::from_bits(channels))
+    }
+
+    /// Unregister Object `id`; cells another Object covers stay blocked.
+    pub fn remove_object(&mut self, id: u32) -> bool {
+        self.inner.remove_object(id)
+    }
+
+    /// Registered Object ids, ascending.
+    pub fn object_ids(&self) -> Vec<u32> {
+        self.inner.object_ids()
+    }
+
+    /// Record (or remove) a door edge between two tiles: `open=true` records
+    /// an open door, `open=false` removes the door whatever its state.
     pub fn set_door_edge(&mut self, t1_idx: usize, t2_idx: usize, open: bool) {
         self.inner.set_door_edge(t1_idx, t2_idx, open);
     }
 
+    /// Record a door between two tiles in a given state: `0` closed, `1`
+    /// open, `2` locked, `3` one-way (passable only out of tile `from`), `4`
+    /// curtain (movement only), `5` undiscovered / `6` discovered secret.
+    /// A one-way door whose `from` is neither tile is ignored.
+    pub fn set_door_state(&mut self, t1_idx: usize, t2_idx: usize, kind: u8, from: usize) {
+        self.inner
+            .set_door_state(t1_idx, t2_idx, engine::DoorState::from_u8(kind, from));
+    }
+
+    /// The state of the door between two tiles, in the numeric form of
+    /// `set_door_state`, or `undefined` if there is none.
+    pub fn door_state(&self, t1_idx: usize, t2_idx: usize) -> Option<u8> {
+        self.inner
+            .door_state(t1_idx, t2_idx)
+            .map(engine::DoorState::kind)
+    }
+
+    /// The tile a one-way door between two tiles opens from, or `undefined`
+    /// if the door there is not one-way.
+    pub fn door_one_way_from(&self, t1_idx: usize, t2_idx: usize) -> Option<usize> {
+        match self.inner.door_state(t1_idx, t2_idx) {
+            Some(engine::DoorState::OneWay { from }) => Some(from),
+            _ => None,
+        }
+    }
+
     /// Forget every recorded door edge. JS re-emits the door set from
     /// scratch when the Yjs token list changes.
     pub fn clear_door_edges(&mut self) {
         self.inner.clear_door_edges();
     }
 
+    /// Record (or remove) a window between two tiles: light and sight pass
+    /// along their shared boundary, pathfinding does not.
+    pub fn set_window_edge(&mut self, t1_idx: usize, t2_idx: usize, window: bool) {
+        self.inner.set_window_edge(t1_idx, t2_idx, window);
+    }
+
+    /// Forget every recorded window edge.
+    pub fn clear_window_edges(&mut self) {
+        self.inner.clear_window_edges();
+    }
+
+    /// Add (or remove) a thin wall along the boundary between two adjacent
+    /// tiles without changing their types.
+    pub fn set_wall_edge(&mut self, t1_idx: usize, t2_idx: usize, wall: bool) {
+        self.inner.set_wall_edge(t1_idx, t2_idx, wall);
+    }
+
+    /// Add (or remove) a thin wall on the edge between two 4-adjacent cells.
+    pub fn set_cell_wall_edge(&mut self, x0: u16, y0: u16, x1: u16, y1: u16, wall: bool) {
+        self.inner.set_cell_wall_edge(x0, y0, x1, y1, wall);
+    }
+
+    /// Remove every thin wall.
+    pub fn clear_wall_edges(&mut self) {
+        self.inner.clear_wall_edges();
+    }
+
+    /// Lay a diagonal wall across a tile: `1` = NE-SW, `2` = NW-SE, `0`
+    /// removes it.
+    pub fn set_tile_diagonal(&mut self, tile_idx: usize, diagonal: u8) {
+        self.inner
+            .set_tile_diagonal(tile_idx, block_map::Diagonal::from_u8(diagonal));
+    }
+
+    /// Remove every diagonal wall.
+    pub fn clear_tile_diagonals(&mut self) {
+        self.inner.clear_tile_diagonals();
+    }
+
     /// Tile-coord BFS pathfinder. Returns the chain of tile indices from
     /// `(x1,y1)` to `(x2,y2)`, or an empty `Vec` if no route exists.
     pub fn path(&mut self, x1: i32, y1: i32, x2: i32, y2: i32) -> Vec<usize> {
@@ -586,14 +810,14 @@ impl WasmLightingEngine {
 
     /// Create or update a rainbow light. Returns a pointer to the rendered
     /// canvas (RGBA, `(r*2+1)²` pixels) in wasm linear memory.
-    pub fn put(&mut self, id: u8, r: i16, x: i16, y: i16) -> *const lighting::Color {
+    pub fn put(&mut self, id: u32, r: i16, x: i16, y: i16) -> *const lighting::Color {
         self.inner.update_or_add_light(id, r, x, y)
     }
 
     /// Create or update a solid-color light.
     pub fn put_solid_color(
         &mut self,
-        id: u8,
+        id: u32,
         r: i16,
         x: i16,
         y: i16,
@@ -602,6 +826,181 @@ impl WasmLightingEngine {
         self.inner.update_or_add_light_with_solid_color(id, r, x, y, hue)
     }
 
+    /// Create or update a light with an exact RGB colour. Unlike
+    /// `put_solid_color`, the authored channels are not quantised through
+    /// HSV — the light's centre cell is exactly `(red, green, blue)`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn put_rgb(
+        &mut self,
+        id: u32,
+        r: i16,
+        x: i16,
+        y: i16,
+        red: u8,
+        green: u8,
+        blue: u8,
+    ) -> *const lighting::Color {
+        self.inner
+            .update_or_add_light_with_rgb(id, r, x, y, red, green, blue)
+    }
+
+    /// Create or update a light coloured by black-body temperature in kelvin
+    /// (clamped to 1000–12000 K), e.g. `1900` for a candle or `6500` for
+    /// daylight fluorescent.
+    pub fn put_kelvin(
+        &mut self,
+        id: u32,
+        r: i16,
+        x: i16,
+        y: i16,
+        kelvin: u16,
+    ) -> *const lighting::Color {
+        self.inner
+            .update_or_add_light_with_kelvin(id, r, x, y, kelvin)
+    }
+
+    /// Create or update a light with a brightness multiplier in 8.8 fixed
+    /// point (`256` = 1.0). Dim sources use less than 256; bright ones more,
+    /// and keep their headroom in `compute_composite` until tone mapping.
+    pub fn put_intensity(
+        &mut self,
+        id: u32,
+        r: i16,
+        x: i16,
+        y: i16,
+        intensity: u16,
+    ) -> *const lighting::Color {
+        self.inner
+            .update_or_add_light_with_intensity(id, r, x, y, intensity)
+    }
+
+    /// Create or update a spotlight facing `heading` degrees (`0` = +x/east,
+    /// `90` = +y/south) with a cone `half_width` degrees either side and a
+    /// `soft_edge`-degree fade inside the boundary. A `half_width` of 180 or
+    /// more turns the light back into an omnidirectional one.
+    #[allow(clippy::too_many_arguments)]
+    pub fn put_spotlight(
+        &mut self,
+        id: u32,
+        r: i16,
+        x: i16,
+        y: i16,
+        heading: u16,
+        half_width: u16,
+        soft_edge: u16,
+    ) -> *const lighting::Color {
+        let cone = (half_width < 180).then_some(lighting::Cone {
+            heading: heading % 360,
+            half_width,
+            soft_edge,
+        });
+        self.inner.update_or_add_light_with_cone(id, r, x, y, cone)
+    }
+
+    /// Create or update a light with an emitting disc of `source_radius`
+    /// cells, which casts soft-edged shadows. `0` is a point source.
+    pub fn put_source_radius(
+        &mut self,
+        id: u32,
+        r: i16,
+        x: i16,
+        y: i16,
+        source_radius: u8,
+    ) -> *const lighting::Color {
+        self.inner
+            .update_or_add_light_with_source_radius(id, r, x, y, source_radius)
+    }
+
+    /// Create or update a segment emitter — a neon tube, a lava river —
+    /// running from `(x, y)` to `(x2, y2)`. The canvas covers the segment's
+    /// bounding box grown by `r`; read its placement with `light_canvas_rect`.
+    pub fn put_segment(
+        &mut self,
+        id: u32,
+        r: i16,
+        x: i16,
+        y: i16,
+        x2: i16,
+        y2: i16,
+    ) -> *const lighting::Color {
+        let shape = lighting::LightShape::Segment { end: (x2, y2) };
+        self.inner
+            .update_or_add_light_with_shape(id, r, x, y, shape)
+    }
+
+    /// Create or update a rectangular emitter — a window, a glowing floor —
+    /// whose top-left cell is `(x, y)`.
+    pub fn put_rect(
+        &mut self,
+        id: u32,
+        r: i16,
+        x: i16,
+        y: i16,
+        width: u16,
+        height: u16,
+    ) -> *const lighting::Color {
+        let shape = lighting::LightShape::Rect { width, height };
+        self.inner
+            .update_or_add_light_with_shape(id, r, x, y, shape)
+    }
+
+    /// Turn a segment or rectangle emitter back into a point light.
+    pub fn put_point_emitter(&mut self, id: u32, r: i16, x: i16, y: i16) -> *const lighting::Color {
+        self.inner
+            .update_or_add_light_with_shape(id, r, x, y, lighting::LightShape::Point)
+    }
+
+    /// A light's canvas placement as `[origin_x, origin_y, width, height]`
+    /// (world cells), or an empty array if the id is unknown.
+    pub fn light_canvas_rect(&self, id: u32) -> Vec<i32> {
+        self.inner
+            .light_canvas_rect(id)
+            .map(|((x, y), w, h)| vec![x as i32, y as i32, w as i32, h as i32])
+            .unwrap_or_default()
+    }
+
+    /// Create or update a light with a built-in falloff curve: `kind` `0`
+    /// linear, `1` quadratic, `2` inverse-square, `3` smoothstep, `4` flat
+    /// core then fade (`param` = core size as a fraction of the radius,
+    /// 0-255). The light's colour mode is left unchanged.
+    pub fn put_falloff(
+        &mut self,
+        id: u32,
+        r: i16,
+        x: i16,
+        y: i16,
+        kind: u8,
+        param: u8,
+    ) -> *const lighting::Color {
+        self.inner.update_or_add_light_with_falloff(
+            id,
+            r,
+            x,
+            y,
+            lighting::Falloff::from_u8(kind, param),
+        )
+    }
+
+    /// Create or update a light with a user-supplied falloff curve: a
+    /// 256-entry `Uint8Array` of brightness indexed by `255·d / r`. Returns a
+    /// null pointer (and leaves the light untouched) if `table` is not
+    /// exactly 256 entries long.
+    pub fn put_falloff_table(
+        &mut self,
+        id: u32,
+        r: i16,
+        x: i16,
+        y: i16,
+        table: Vec<u8>,
+    ) -> *const lighting::Color {
+        match lighting::Falloff::from_table(&table) {
+            Some(falloff) => self
+                .inner
+                .update_or_add_light_with_falloff(id, r, x, y, falloff),
+            None => std::ptr::null(),
+        }
+    }
+
     /// Create or update a room-bounded ambient emitter. Floods the same-type
     /// `UnionFind` room of tile `(tile_x, tile_y)` with a flat `(r, g, b)`,
     /// returning a pointer to its full-map canvas (`cells_per_row²` RGBA cells).
@@ -610,7 +1009,7 @@ impl WasmLightingEngine {
     /// (`tile <= 0`) yields an empty canvas. See ADR-0004.
     pub fn put_ambient(
         &mut self,
-        id: u8,
+        id: u32,
         tile_x: i16,
         tile_y: i16,
         r: u8,
@@ -620,6 +1019,129 @@ impl WasmLightingEngine {
         self.inner.update_or_add_ambient(id, tile_x, tile_y, r, g, b)
     }
 
+    /// Unregister a light (e.g. when its token is deleted). Returns `false`
+    /// if the id is unknown. Pointers previously returned for it are invalid.
+    pub fn remove_light(&mut self, id: u32) -> bool {
+        self.inner.remove_light(id)
+    }
+
+    /// Unregister an ambient emitter. Returns `false` if the id is unknown.
+    pub fn remove_ambient(&mut self, id: u32) -> bool {
+        self.inner.remove_ambient(id)
+    }
+
+    /// Re-trace the lights (and re-flood the ambients) invalidated by
+    /// `set_tile`, `set_pixel`, `set_pixel_material` or door edits since their
+    /// last render. Returns the re-traced light ids (a `Uint32Array`) so JS
+    /// re-uploads only those canvases.
+    pub fn update_dirty_lights(&mut self) -> Vec<u32> {
+        self.inner.update_dirty_lights()
+    }
+
+    /// Ids of every registered light, ascending (arrives as a `Uint32Array`).
+    pub fn light_ids(&self) -> Vec<u32> {
+        self.inner.light_ids()
+    }
+
+    /// Ids of every registered ambient emitter, ascending.
+    pub fn ambient_ids(&self) -> Vec<u32> {
+        self.inner.ambient_ids()
+    }
+
+    /// Switch a light on or off without unregistering it. A disabled light
+    /// keeps its canvas allocated (transparent) and is left out of
+    /// `compute_composite`. Returns `false` if the id is unknown.
+    pub fn set_light_enabled(&mut self, id: u32, enabled: bool) -> bool {
+        self.inner.set_light_enabled(id, enabled)
+    }
+
+    /// Switch an ambient emitter on or off without unregistering it.
+    pub fn set_ambient_enabled(&mut self, id: u32, enabled: bool) -> bool {
+        self.inner.set_ambient_enabled(id, enabled)
+    }
+
+    /// Let an ambient spill past its room through open doors: `kind` `0`
+    /// room only (default), `1` dim by `param`/255 per door crossed, `2`
+    /// fade out over `param` tile steps. Spill is re-routed by
+    /// `update_dirty_lights` after door edits. Returns `false` if the id is
+    /// unknown.
+    pub fn set_ambient_spread(&mut self, id: u32, kind: u8, param: u8) -> bool {
+        self.inner
+            .set_ambient_spread(id, lighting::AmbientSpread::from_u8(kind, param))
+    }
+
+    /// Darken an ambient's fill near Walls and Objects: up to `strength`
+    /// (0-255) per axis, fading out over `radius` cells; corners darken on
+    /// both axes. `0` for either turns it off. Returns `false` if the id is
+    /// unknown.
+    pub fn set_ambient_occlusion(&mut self, id: u32, radius: u8, strength: u8) -> bool {
+        let occlusion =
+            (radius > 0 && strength > 0).then_some(lighting::AmbientOcclusion { radius, strength });
+        self.inner.set_ambient_occlusion(id, occlusion)
+    }
+
+    /// Animate a light with fire-like flicker: intensity wanders between
+    /// `min` and `max` (8.8 multipliers, `256` = 1.0), drawing a new target
+    /// from `seed` every `interval_ms`. Returns `false` if the id is unknown.
+    pub fn animate_flicker(
+        &mut self,
+        id: u32,
+        seed: u32,
+        min: u16,
+        max: u16,
+        interval_ms: u32,
+    ) -> bool {
+        let animation = animation::LightAnimation::Flicker {
+            seed,
+            min,
+            max,
+            interval_ms,
+        };
+        self.inner.set_light_animation(id, Some(animation))
+    }
+
+    /// Animate a light with a smooth intensity pulse between `min` and `max`
+    /// (8.8 multipliers) over `period_ms`.
+    pub fn animate_pulse(&mut self, id: u32, min: u16, max: u16, period_ms: u32) -> bool {
+        let animation = animation::LightAnimation::Pulse {
+            min,
+            max,
+            period_ms,
+        };
+        self.inner.set_light_animation(id, Some(animation))
+    }
+
+    /// Animate a light as a strobe: on for `on_ms`, off for `off_ms`.
+    pub fn animate_strobe(&mut self, id: u32, on_ms: u32, off_ms: u32) -> bool {
+        let animation = animation::LightAnimation::Strobe { on_ms, off_ms };
+        self.inner.set_light_animation(id, Some(animation))
+    }
+
+    /// Animate a light's reach: the lit radius swings between `min` (a
+    /// fraction of the radius, 0-255) and the full radius over `period_ms`.
+    pub fn animate_radius(&mut self, id: u32, min: u8, period_ms: u32) -> bool {
+        let animation = animation::LightAnimation::RadiusOscillation { min, period_ms };
+        self.inner.set_light_animation(id, Some(animation))
+    }
+
+    /// Turn a spotlight into a rotating beacon turning `degrees_per_second`.
+    pub fn animate_beacon(&mut self, id: u32, degrees_per_second: i16) -> bool {
+        let animation = animation::LightAnimation::Beacon { degrees_per_second };
+        self.inner.set_light_animation(id, Some(animation))
+    }
+
+    /// Stop a light's animation and render it with its configured values.
+    pub fn clear_animation(&mut self, id: u32) -> bool {
+        self.inner.set_light_animation(id, None)
+    }
+
+    /// Advance the engine clock by `dt_ms` and re-render every animated light.
+    /// Returns the re-rendered ids (a `Uint32Array`). Animations depend only
+    /// on the clock, so clients ticking the same amounts stay in lockstep.
+    pub fn tick(&mut self, dt_ms: u32) -> Vec<u32> {
+        self.inner.tick(dt_ms)
+    }
+
     /// Compute the live field-of-view mask for a flat array of viewer points in
     /// cell coords (`[x0, y0, x1, y1, …]`, arriving as an `Int16Array`) and
     /// return a pointer to a full-map **FOV canvas** (`cells_per_row²` RGBA
@@ -630,6 +1152,115 @@ impl WasmLightingEngine {
     pub fn compute_fov(&mut self, viewers: Vec<i16>) -> *const lighting::Color {
         self.inner.compute_fov(&viewers)
     }
+
+    /// Select how `compute_composite` combines overlapping layers:
+    /// `0` additive (saturating), `1` max, `2` screen. Unknown values fall
+    /// back to additive.
+    pub fn set_blend_mode(&mut self, mode: u8) {
+        self.inner
+            .set_blend_mode(composite::BlendMode::from_u8(mode));
+    }
+
+    /// Select how `compute_composite` maps its 16-bit accumulator to 8-bit
+    /// colour: `0` clamp, `1` Reinhard, `2` exposure (`param` = 8.8 gain,
+    /// `256` = 1.0). Unknown kinds fall back to clamp.
+    pub fn set_tone_map(&mut self, kind: u8, param: u16) {
+        self.inner
+            .set_tone_map(composite::ToneMap::from_u8(kind, param));
+    }
+
+    /// Shade lights and blend the composite in linear light (`true`,
+    /// gamma-correct) or on sRGB bytes (`false`, the default and historical
+    /// output). Switching re-renders every light.
+    pub fn set_linear_light(&mut self, enabled: bool) {
+        let space = if enabled {
+            lighting::ColorSpace::Linear
+        } else {
+            lighting::ColorSpace::Srgb
+        };
+        self.inner.set_color_space(space);
+    }
+
+    /// Enable single-bounce indirect light in `compute_composite`: lit cells
+    /// on a Wall edge or beside an Object give back `fraction` (0-255) of
+    /// their light, spread evenly over their Room. `0` turns it off.
+    pub fn set_bounce(&mut self, fraction: u8) {
+        let bounce = (fraction > 0).then_some(composite::Bounce { fraction });
+        self.inner.set_bounce(bounce);
+    }
+
+    /// Blend every ambient and light into one full-map canvas and return a
+    /// pointer to it (`cells_per_row²` RGBA cells in wasm linear memory).
+    /// Layers are composited in a fixed order — ambients, then emissive,
+    /// then lights, each by ascending id — so the output is deterministic.
+    /// The canvas is reused between calls — read it back before the next
+    /// `compute_composite`.
+    pub fn compute_composite(&mut self) -> *const lighting::Color {
+        self.inner.compute_composite()
+    }
+
+    /// Light reaching cell `(cx, cy)` from every enabled ambient, emissive
+    /// cell and light, blended and tone-mapped as `compute_composite` would
+    /// (without the bounce fill): `[r, g, b, level]`, where `level` is the
+    /// luminance.
+    pub fn light_at(&self, cx: i16, cy: i16) -> Vec<u8> {
+        let sample = self.inner.light_at(cx, cy);
+        let (r, g, b) = sample.color;
+        vec![r, g, b, sample.level]
+    }
+
+    /// As `light_at`, averaged over the cells of tile `(tx, ty)`.
+    pub fn light_at_tile(&self, tx: i16, ty: i16) -> Vec<u8> {
+        let sample = self.inner.light_at_tile(tx, ty);
+        let (r, g, b) = sample.color;
+        vec![r, g, b, sample.level]
+    }
+
+    /// Batched `light_at`: `cells` is `[x0, y0, x1, y1, …]` (a trailing odd
+    /// element is ignored); returns `[r, g, b, level]` per cell, flattened.
+    pub fn light_at_cells(&self, cells: Vec<i16>) -> Vec<u8> {
+        let points: Vec<(i16, i16)> = cells.chunks_exact(2).map(|p| (p[0], p[1])).collect();
+        self.inner
+            .light_at_cells(&points)
+            .into_iter()
+            .flat_map(|s| [s.color.0, s.color.1, s.color.2, s.level])
+            .collect()
+    }
+
+    /// Re-encode a canvas as one luminance byte per cell and return a pointer
+    /// to it. `kind` picks the canvas: `0` light `id`, `1` ambient `id`, `2`
+    /// emissive, `3` FOV, `4` composite (as last computed). Null if it does
+    /// not exist. The buffer is reused by the next `canvas_luma8` call.
+    pub fn canvas_luma8(&mut self, kind: u8, id: u32) -> *const u8 {
+        formats::CanvasSource::from_u8(kind, id)
+            .and_then(|source| self.inner.canvas_luma8(source))
+            .map_or(std::ptr::null(), |out| out.as_ptr())
+    }
+
+    /// Re-encode a canvas (`kind`/`id` as for `canvas_luma8`) as a bitset —
+    /// `ceil(cells / 8)` bytes, LSB-first, bit set where the cell is not
+    /// transparent — and return a pointer to it, or null.
+    pub fn canvas_bits(&mut self, kind: u8, id: u32) -> *const u8 {
+        formats::CanvasSource::from_u8(kind, id)
+            .and_then(|source| self.inner.canvas_bits(source))
+            .map_or(std::ptr::null(), |out| out.as_ptr())
+    }
+
+    /// Re-encode a canvas as interleaved RGBA `u16` (4080 = 1.0, unclamped
+    /// for lights and the composite) and return a pointer, or null.
+    pub fn canvas_rgba16(&mut self, kind: u8, id: u32) -> *const u16 {
+        formats::CanvasSource::from_u8(kind, id)
+            .and_then(|source| self.inner.canvas_rgba16(source))
+            .map_or(std::ptr::null(), |out| out.as_ptr())
+    }
+
+    /// Re-encode a canvas as interleaved RGBA `f32` (1.0 = full, unclamped
+    /// for lights and the composite) and return a pointer, or null.
+    pub fn canvas_f32(&mut self, kind: u8, id: u32) -> *const f32 {
+        formats::CanvasSource::from_u8(kind, id)
+            .and_then(|source| self.inner.canvas_f32(source))
+            .map_or(std::ptr::null(), |out| out.as_ptr())
+    }
 }
 
 #[cfg(test)]
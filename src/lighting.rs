@@ -15,9 +15,12 @@
 
 use std::collections::HashMap;
 
-use crate::collision::{CollisionDetector, HybridCollisionMap};
-use crate::engine::DEFAULT_ENGINE;
+use crate::animation::{AnimationFrame, LightAnimation};
 use crate::arctan;
+use crate::block_map::CellDetails;
+use crate::collision::{Channel, CollisionDetector, Filter, HybridCollisionMap, PixelCollisionMap};
+use crate::engine::DEFAULT_ENGINE;
+use crate::srgb;
 
 /// Color mode configuration for light sources.
 #[derive(Clone, Debug, PartialEq)]
@@ -26,6 +29,139 @@ pub enum ColorMode {
     Solid(u8),
     /// Custom HSV color with specified hue and saturation.
     Custom { hue: u8, saturation: u8 },
+    /// Exact RGB colour at full brightness, scaled by falloff. Bypasses the
+    /// 43-step `hsv2rgb` quantisation, so authored palettes survive intact.
+    Rgb(u8, u8, u8),
+    /// Black-body colour temperature in kelvin (e.g. `1900` candle, `2700`
+    /// tungsten, `6500` daylight). Clamped to 1000–12000 K.
+    Kelvin(u16),
+}
+
+/// Brightness falloff curve from a light's centre to its radius.
+///
+/// Every curve is evaluated on the integer ratio `t = 255·d / r` (`0` at the
+/// centre, approaching `255` at the radius) and baked into a 256-entry table
+/// once per render, so rendering stays integer-only and deterministic.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Falloff {
+    /// `255 - t` — the historical curve.
+    #[default]
+    Linear,
+    /// `(255 - t)² / 255` — dimmer mid-range, soft tail.
+    Quadratic,
+    /// Inverse-square `1 / (1 + 16·(t/255)²)`, rescaled so it reaches zero at
+    /// the radius instead of being cut off mid-brightness.
+    InverseSquare,
+    /// Hermite smoothstep of `255 - t` — flat near the centre and the edge.
+    Smoothstep,
+    /// Full brightness out to `t < core` (a fraction of the radius, 0-255),
+    /// then a linear fade to zero at the radius.
+    FlatCore(u8),
+    /// User-supplied curve: brightness indexed directly by `t`.
+    Table(Box<[u8; 256]>),
+}
+
+impl Falloff {
+    /// Decode the WASM-facing numeric form: `0` linear, `1` quadratic,
+    /// `2` inverse-square, `3` smoothstep, `4` flat core (`param` = core
+    /// fraction). Unknown kinds fall back to [`Falloff::Linear`].
+    pub fn from_u8(kind: u8, param: u8) -> Self {
+        match kind {
+            1 => Falloff::Quadratic,
+            2 => Falloff::InverseSquare,
+            3 => Falloff::Smoothstep,
+            4 => Falloff::FlatCore(param),
+            _ => Falloff::Linear,
+        }
+    }
+
+    /// Build a [`Falloff::Table`] from a slice, which must hold exactly 256
+    /// entries.
+    pub fn from_table(table: &[u8]) -> Option<Self> {
+        let table: [u8; 256] = table.try_into().ok()?;
+        Some(Falloff::Table(Box::new(table)))
+    }
+
+    /// Bake the curve into a brightness table indexed by `t = 255·d / r`.
+    pub(crate) fn table(&self) -> [u8; 256] {
+        if let Falloff::Table(table) = self {
+            return **table;
+        }
+        let mut out = [0u8; 256];
+        for (t, entry) in out.iter_mut().enumerate() {
+            let t = t as u32;
+            let s = 255 - t;
+            *entry = match self {
+                Falloff::Linear => s,
+                Falloff::Quadratic => s * s / 255,
+                Falloff::InverseSquare => {
+                    let inv = |t: u32| 255 * 65025 / (65025 + 16 * t * t);
+                    let floor = inv(255);
+                    (inv(t) - floor) * 255 / (255 - floor)
+                }
+                Falloff::Smoothstep => s * s * (3 * 255 - 2 * s) / (255 * 255),
+                Falloff::FlatCore(core) => {
+                    let core = *core as u32;
+                    if t < core {
+                        255
+                    } else {
+                        255 - (t - core) * 255 / (256 - core)
+                    }
+                }
+                Falloff::Table(_) => unreachable!("handled above"),
+            } as u8;
+        }
+        out
+    }
+}
+
+/// Directional restriction that turns a point light into a spotlight.
+///
+/// Angles are in degrees using the ray table's convention: `0` points east
+/// (+x) and angles increase towards +y (screen-down), so `90` points south.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cone {
+    /// Direction the cone faces, in degrees (taken modulo 360).
+    pub heading: u16,
+    /// Angular half-width of the cone in degrees; cells more than this far
+    /// off `heading` receive no light.
+    pub half_width: u16,
+    /// Width in degrees of the soft band just inside the cone boundary over
+    /// which brightness fades to zero. `0` gives a hard edge.
+    pub soft_edge: u16,
+}
+
+impl Cone {
+    /// Angular distance (0–180) between direction `deg` and the heading.
+    fn offset(&self, deg: u16) -> u16 {
+        let diff = (deg as i32 - self.heading as i32).rem_euclid(360) as u16;
+        diff.min(360 - diff)
+    }
+
+    /// Whether direction `deg` falls inside the cone.
+    pub(crate) fn contains(&self, deg: u16) -> bool {
+        self.offset(deg) <= self.half_width
+    }
+
+    /// Soft-edge attenuation for direction `deg`: `255` inside the hard core,
+    /// fading linearly to `0` at the cone boundary.
+    pub(crate) fn edge_factor(&self, deg: u16) -> u8 {
+        let offset = self.offset(deg);
+        let soft = self.soft_edge.min(self.half_width);
+        let core = self.half_width - soft;
+        if offset <= core {
+            255
+        } else if offset >= self.half_width {
+            0
+        } else {
+            ((self.half_width - offset) as u32 * 255 / soft as u32) as u8
+        }
+    }
+}
+
+/// Direction in degrees (ray-table convention) from the origin to `offset`.
+fn direction(offset: PtI) -> u16 {
+    arctan::rad_to_deg(arctan::atan2_int(offset.1 as i32, offset.0 as i32)) as u16
 }
 
 /// Maximum ray distance from a light's centre, in cells.
@@ -57,6 +193,98 @@ type PtI = (i16, i16);
 #[derive(Copy, Clone, Debug, Default)]
 pub struct Color(pub u8, pub u8, pub u8, pub u8);
 
+/// Fixed-point value of `1.0` in an [`Hdr`] channel. `255 × 16`, so every
+/// 8-bit level maps to an exact HDR value and back.
+pub const HDR_ONE: u16 = 4080;
+
+/// 16-bit fixed-point RGBA used for intensity scaling and compositing.
+///
+/// Each channel is a multiple of [`HDR_ONE`] (`1.0`, i.e. 8-bit `255`), with
+/// roughly 16× headroom above it, so bright lights can overlap without
+/// clipping until a tone-mapping operator produces the final [`Color`].
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Hdr(pub u16, pub u16, pub u16, pub u16);
+
+impl Hdr {
+    /// Lift an 8-bit colour into HDR space exactly (`255` → [`HDR_ONE`]).
+    pub fn from_color(c: Color) -> Self {
+        let lift = |v: u8| v as u16 * (HDR_ONE / 255);
+        Hdr(lift(c.0), lift(c.1), lift(c.2), lift(c.3))
+    }
+
+    /// Multiply the colour channels by an 8.8 fixed-point factor (`256` =
+    /// 1.0), saturating at `u16::MAX`. Alpha is left untouched.
+    pub fn scale(self, factor: u16) -> Self {
+        let mul = |v: u16| (v as u32 * factor as u32 / 256).min(u16::MAX as u32) as u16;
+        Hdr(mul(self.0), mul(self.1), mul(self.2), self.3)
+    }
+
+    /// Clamp back to 8 bits — the identity on anything [`Hdr::from_color`]
+    /// produced.
+    pub fn to_color_clamped(self) -> Color {
+        let down = |v: u16| (v / (HDR_ONE / 255)).min(255) as u8;
+        Color(down(self.0), down(self.1), down(self.2), down(self.3))
+    }
+}
+
+/// Space in which lights are shaded and layers are blended.
+///
+/// [`ColorSpace::Srgb`] does the arithmetic directly on gamma-encoded bytes,
+/// as the engine always has, which darkens falloff and muddies coloured
+/// overlaps. [`ColorSpace::Linear`] decodes to linear light first and
+/// encodes back to sRGB only for output (see [`crate::srgb`]).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorSpace {
+    /// Arithmetic on sRGB bytes — the historical output.
+    #[default]
+    Srgb,
+    /// Gamma-correct: shading and blending in linear light.
+    Linear,
+}
+
+impl ColorSpace {
+    /// Lift an 8-bit colour into [`Hdr`] in this space. Alpha is always
+    /// lifted linearly.
+    pub fn lift(self, c: Color) -> Hdr {
+        match self {
+            ColorSpace::Srgb => Hdr::from_color(c),
+            ColorSpace::Linear => Hdr(
+                srgb::decode(c.0),
+                srgb::decode(c.1),
+                srgb::decode(c.2),
+                Hdr::from_color(c).3,
+            ),
+        }
+    }
+
+    /// Clamp an [`Hdr`] value back to 8-bit output in this space.
+    pub fn lower(self, h: Hdr) -> Color {
+        match self {
+            ColorSpace::Srgb => h.to_color_clamped(),
+            ColorSpace::Linear => Color(
+                srgb::encode(h.0),
+                srgb::encode(h.1),
+                srgb::encode(h.2),
+                h.to_color_clamped().3,
+            ),
+        }
+    }
+}
+
+/// Linear-light shading of a full-brightness sRGB colour: decoded, scaled by
+/// brightness `v` (`0`–`255`) and by `filter`, all in linear light.
+fn shade_linear(full: Color, v: u8, filter: Filter) -> Hdr {
+    let lit = ColorSpace::Linear.lift(full);
+    let scale = |c: u16, f: u8| (c as u32 * v as u32 / 255 * f as u32 / 255) as u16;
+    Hdr(
+        scale(lit.0, filter.0),
+        scale(lit.1, filter.1),
+        scale(lit.2, filter.2),
+        HDR_ONE,
+    )
+}
+
 /// Per-engine precomputed Bresenham ray table.
 ///
 /// Keyed by `(distance, angle)`, each entry lists the cell offsets at that
@@ -106,37 +334,89 @@ pub(crate) fn build_ray_table(max_dist: usize) -> RayTable {
 /// coloured pixel per visited cell) and [`crate::engine::LightingEngine::compute_fov`]
 /// (which marks a binary visibility mask).
 ///
-/// `visit` receives `(offset, angle, d)`, where `offset` is the visited cell's
-/// position relative to `pos` and `angle`/`d` identify the ray. World (cell)
-/// coords are just `pos + offset`. Distance is capped at `max_dist`; the same
-/// occlusion rules as `Light::update` apply, minus colour and falloff.
+/// `visit` receives `(offset, angle, d, filter)`, where `offset` is the visited
+/// cell's position relative to `pos`, `angle`/`d` identify the ray and
+/// `filter` is what the translucent materials along the way let through.
+/// World (cell) coords are just `pos + offset`. Distance is capped at
+/// `max_dist`; the same occlusion rules as `Light::update` apply, minus colour
+/// and falloff.
+///
+/// With a `cone`, only cells whose direction from `pos` falls inside it are
+/// visited (the origin cell always is), and ray-table buckets lying wholly
+/// outside the cone are skipped without being walked.
+///
+/// Occlusion is for [`Channel::Light`]; see [`trace_visible_cells_for`].
 pub(crate) fn trace_visible_cells<F>(
     pos: PtI,
     collision: &HybridCollisionMap,
     rays: &RayTable,
     max_dist: usize,
+    cone: Option<&Cone>,
+    visit: F,
+) where
+    F: FnMut(PtI, usize, u8, Filter),
+{
+    trace_visible_cells_for(Channel::Light, pos, collision, rays, max_dist, cone, visit)
+}
+
+/// [`trace_visible_cells`] with occlusion for any one [`Channel`].
+pub(crate) fn trace_visible_cells_for<F>(
+    channel: Channel,
+    pos: PtI,
+    collision: &HybridCollisionMap,
+    rays: &RayTable,
+    max_dist: usize,
+    cone: Option<&Cone>,
     mut visit: F,
 ) where
-    F: FnMut(PtI, usize, u8),
+    F: FnMut(PtI, usize, u8, Filter),
 {
     let mut blocked_angles = [255u8; ANGLES];
 
+    // A bucket holds the directions `angle + k·ANGLES` (just `angle` degrees
+    // in full-resolution builds); it can be skipped only if none of them is
+    // inside the cone.
+    let mut bucket_in_cone = [true; ANGLES];
+    if let Some(cone) = cone {
+        for (angle, in_cone) in bucket_in_cone.iter_mut().enumerate() {
+            *in_cone = (angle..360)
+                .step_by(ANGLES)
+                .any(|deg| cone.contains(deg as u16));
+        }
+    }
+
     for d in 0..max_dist {
         for angle in 0..ANGLES {
             if blocked_angles[angle] < d as u8 {
                 continue;
             }
+            if d > 0 && !bucket_in_cone[angle] {
+                continue;
+            }
 
             if let Some(cells) = rays.get(&(d, angle)) {
                 for cell in cells {
                     if d == 0 && angle % 90 != 0 {
                         continue;
                     }
+                    if let Some(cone) = cone {
+                        if *cell != (0, 0) && !cone.contains(direction(*cell)) {
+                            continue;
+                        }
+                    }
 
                     let curr = (cell.0 + pos.0, cell.1 + pos.1);
 
-                    // Full-ray occlusion check from the viewer origin to cell.
-                    if collision.is_blocked(pos.0, pos.1, curr.0, curr.1) {
+                    // Full-ray occlusion check from the viewer origin to cell,
+                    // then the translucent materials the ray passes through.
+                    // A filter that stops every channel counts as a hit.
+                    let filter = if collision.is_blocked_for(channel, pos.0, pos.1, curr.0, curr.1)
+                    {
+                        Filter(0, 0, 0)
+                    } else {
+                        collision.filter_along(pos.0, pos.1, curr.0, curr.1)
+                    };
+                    if filter.is_opaque() {
                         blocked_angles[angle] = d as u8;
 
                         if d < 3 {
@@ -154,14 +434,95 @@ pub(crate) fn trace_visible_cells<F>(
                         break;
                     }
 
-                    visit(*cell, angle, d as u8);
+                    visit(*cell, angle, d as u8, filter);
                 }
             }
         }
     }
 }
 
-/// A single point light's per-instance state and render output.
+/// A light's configuration with its animation applied, fixed for one render.
+struct Shading {
+    /// Baked falloff curve.
+    falloff: [u8; 256],
+    /// Lit radius (at most the configured one; the canvas keeps that size).
+    r: i16,
+    intensity: u16,
+    cone: Option<Cone>,
+}
+
+/// Geometry of a light's emitter, anchored at the light's position.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LightShape {
+    /// A single cell at the light's position.
+    #[default]
+    Point,
+    /// A line of cells from the light's position to `end` inclusive — a neon
+    /// tube, a lava river.
+    Segment { end: PtI },
+    /// A `width × height` block of cells whose top-left cell is the light's
+    /// position — a window, a glowing floor strip.
+    Rect { width: u16, height: u16 },
+}
+
+impl LightShape {
+    /// Inclusive bounding box `(min, max)` of the emitter cells that lie on
+    /// a `size × size` map, or `None` if none does. A point is never clipped.
+    pub(crate) fn bounds(&self, pos: PtI, size: usize) -> Option<(PtI, PtI)> {
+        let (x, y) = (pos.0 as i32, pos.1 as i32);
+        let (x0, y0, x1, y1) = match *self {
+            LightShape::Point => return Some((pos, pos)),
+            LightShape::Segment { end } => {
+                let (ex, ey) = (end.0 as i32, end.1 as i32);
+                (x.min(ex), y.min(ey), x.max(ex), y.max(ey))
+            }
+            LightShape::Rect { width, height } => (
+                x,
+                y,
+                x + width.max(1) as i32 - 1,
+                y + height.max(1) as i32 - 1,
+            ),
+        };
+        let last = size as i32 - 1;
+        let (x0, y0, x1, y1) = (x0.max(0), y0.max(0), x1.min(last), y1.min(last));
+        (x0 <= x1 && y0 <= y1).then_some(((x0 as i16, y0 as i16), (x1 as i16, y1 as i16)))
+    }
+
+    /// Cells rays are traced from, within the on-map `bounds`: every cell of
+    /// a segment, the perimeter of a rectangle (interior cells can't see
+    /// anything the perimeter can't).
+    fn trace_origins(&self, pos: PtI, bounds: (PtI, PtI)) -> Vec<PtI> {
+        let ((x0, y0), (x1, y1)) = bounds;
+        match *self {
+            LightShape::Point => vec![pos],
+            LightShape::Segment { end } => {
+                let on_map = |c: PtI| c.0 >= x0 && c.0 <= x1 && c.1 >= y0 && c.1 <= y1;
+                let mut cells: Vec<PtI> = on_map(pos).then_some(pos).into_iter().collect();
+                let mut curr = pos;
+                while curr != end {
+                    curr = crate::ray::step(curr, end);
+                    if on_map(curr) {
+                        cells.push(curr);
+                    }
+                }
+                cells
+            }
+            LightShape::Rect { .. } => {
+                let mut cells = Vec::new();
+                for y in y0..=y1 {
+                    for x in x0..=x1 {
+                        if x == x0 || x == x1 || y == y0 || y == y1 {
+                            cells.push((x, y));
+                        }
+                    }
+                }
+                cells
+            }
+        }
+    }
+}
+
+/// A single light's per-instance state and render output.
 ///
 /// Owned by [`crate::engine::LightingEngine`]; not constructed directly by
 /// callers.
@@ -169,8 +530,34 @@ pub struct Light {
     pos: PtI,
     r: i16,
     color_mode: Option<ColorMode>,
+    falloff: Falloff,
+    /// Spotlight restriction; `None` for an omnidirectional light.
+    cone: Option<Cone>,
+    /// Emitter geometry; everything but [`LightShape::Point`] is traced from
+    /// several cells (see [`Light::update`]).
+    shape: LightShape,
+    /// Radius in cells of the emitting disc. `0` is a point source with hard
+    /// shadows; larger sources cast penumbrae (see [`Light::update`]).
+    source_radius: u8,
+    /// Brightness multiplier in 8.8 fixed point (`256` = 1.0).
+    intensity: u16,
+    /// Disabled lights skip tracing and keep a transparent canvas.
+    enabled: bool,
+    /// Time-driven modulation, sampled at the engine clock on every render.
+    animation: Option<LightAnimation>,
+    /// Space the light is shaded in; follows the engine's setting.
+    color_space: ColorSpace,
+    /// Intensity-scaled render output, read by the compositor so overlapping
+    /// bright lights accumulate past 8 bits.
+    hdr: Vec<Hdr>,
+    /// `hdr` clamped to 8 bits (and encoded back to sRGB when shading in
+    /// linear light) — the per-light canvas handed to JS.
     canvas: Vec<Color>,
-    canvas_size: usize,
+    /// World cell of the canvas's top-left cell: the emitter's bounding box
+    /// grown by the radius on every side.
+    canvas_origin: PtI,
+    canvas_width: usize,
+    canvas_height: usize,
 }
 
 impl Light {
@@ -178,11 +565,22 @@ impl Light {
         let canvas_size = (r * 2 + 1) as usize;
         let canvas_pixels = canvas_size * canvas_size;
         Light {
+            shape: LightShape::Point,
             pos,
             r,
             color_mode,
+            falloff: Falloff::default(),
+            cone: None,
+            source_radius: 0,
+            intensity: 256,
+            enabled: true,
+            animation: None,
+            color_space: ColorSpace::Srgb,
+            hdr: vec![Hdr::default(); canvas_pixels],
             canvas: vec![Color::default(); canvas_pixels],
-            canvas_size,
+            canvas_origin: (pos.0 - r, pos.1 - r),
+            canvas_width: canvas_size,
+            canvas_height: canvas_size,
         }
     }
 
@@ -198,107 +596,428 @@ impl Light {
         &self.color_mode
     }
 
+    pub(crate) fn falloff(&self) -> &Falloff {
+        &self.falloff
+    }
+
+    pub(crate) fn cone(&self) -> Option<&Cone> {
+        self.cone.as_ref()
+    }
+
+    pub(crate) fn shape(&self) -> LightShape {
+        self.shape
+    }
+
+    pub(crate) fn source_radius(&self) -> u8 {
+        self.source_radius
+    }
+
+    pub(crate) fn intensity(&self) -> u16 {
+        self.intensity
+    }
+
+    pub(crate) fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub(crate) fn animation(&self) -> Option<&LightAnimation> {
+        self.animation.as_ref()
+    }
+
     pub(crate) fn canvas(&self) -> &[Color] {
         &self.canvas
     }
 
-    pub(crate) fn canvas_size(&self) -> usize {
-        self.canvas_size
+    pub(crate) fn hdr_canvas(&self) -> &[Hdr] {
+        &self.hdr
     }
 
-    pub(crate) fn set_state(&mut self, pos: PtI, r: i16, color_mode: Option<ColorMode>) {
+    /// World cell of the canvas's top-left cell, and its width and height.
+    pub(crate) fn canvas_rect(&self) -> (PtI, usize, usize) {
+        (self.canvas_origin, self.canvas_width, self.canvas_height)
+    }
+
+    pub(crate) fn set_state(&mut self, pos: PtI, r: i16) {
         self.pos = pos;
         self.r = r;
+    }
+
+    pub(crate) fn set_color_mode(&mut self, color_mode: Option<ColorMode>) {
         self.color_mode = color_mode;
     }
 
+    pub(crate) fn set_falloff(&mut self, falloff: Falloff) {
+        self.falloff = falloff;
+    }
+
+    pub(crate) fn set_cone(&mut self, cone: Option<Cone>) {
+        self.cone = cone;
+    }
+
+    pub(crate) fn set_shape(&mut self, shape: LightShape) {
+        self.shape = shape;
+    }
+
+    pub(crate) fn set_source_radius(&mut self, source_radius: u8) {
+        self.source_radius = source_radius;
+    }
+
+    pub(crate) fn set_intensity(&mut self, intensity: u16) {
+        self.intensity = intensity;
+    }
+
+    pub(crate) fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub(crate) fn set_color_space(&mut self, color_space: ColorSpace) {
+        self.color_space = color_space;
+    }
+
+    pub(crate) fn set_animation(&mut self, animation: Option<LightAnimation>) {
+        self.animation = animation;
+    }
+
     /// Recalculate this light's canvas, consulting `collision` for occlusion
     /// and `rays` for precomputed Bresenham geometry. `max_dist` caps the
     /// effective light radius for this pass.
+    ///
+    /// A point source (`source_radius == 0`) is traced once from its centre.
+    /// An area source is traced from up to nine fixed sub-origins on its disc
+    /// — the centre, the four cardinals and the four diagonals — and every
+    /// cell is lit in proportion to how many of them see it, giving
+    /// deterministic penumbrae at roughly nine times the cost.
+    ///
+    /// Segment and rectangle emitters are traced once per on-map cell of the
+    /// segment or of the rectangle's perimeter, and each canvas cell is
+    /// shaded from the nearest of those traces, so the cost grows with the
+    /// emitter's length. Their canvas covers only the on-map part of the
+    /// emitter, grown by the radius, and their source radius is ignored.
+    ///
+    /// A disabled light is not traced; its canvas is just cleared. An animated
+    /// light is rendered as its animation stands at engine time `time_ms`.
     pub(crate) fn update(
         &mut self,
         collision: &HybridCollisionMap,
         rays: &RayTable,
         max_dist: usize,
+        time_ms: u64,
     ) -> *const Color {
-        let new_canvas_size = (self.r * 2 + 1) as usize;
-        let new_canvas_pixels = new_canvas_size * new_canvas_size;
+        let bounds = self.shape.bounds(self.pos, collision.map_size());
+        let ((x0, y0), (x1, y1)) = bounds.unwrap_or((self.pos, self.pos));
+        let r = self.r as i32;
+        self.canvas_origin = ((x0 as i32 - r) as i16, (y0 as i32 - r) as i16);
+        self.canvas_width = (x1 as i32 - x0 as i32 + r * 2 + 1) as usize;
+        self.canvas_height = (y1 as i32 - y0 as i32 + r * 2 + 1) as usize;
+        let new_canvas_pixels = self.canvas_width * self.canvas_height;
         if self.canvas.len() != new_canvas_pixels {
             self.canvas = vec![Color::default(); new_canvas_pixels];
-            self.canvas_size = new_canvas_size;
+            self.hdr = vec![Hdr::default(); new_canvas_pixels];
         }
 
         self.canvas.iter_mut().for_each(|p| *p = Color::default());
+        self.hdr.iter_mut().for_each(|p| *p = Hdr::default());
+        if !self.enabled {
+            return self.canvas.as_ptr();
+        }
+
+        let frame = self
+            .animation
+            .map_or(AnimationFrame::STILL, |a| a.sample(time_ms));
+        let shading = Shading {
+            falloff: self.falloff.table(),
+            r: (self.r as u32 * frame.radius as u32).div_ceil(255).max(1) as i16,
+            intensity: (self.intensity as u32 * frame.intensity as u32 / 256).min(u16::MAX as u32)
+                as u16,
+            cone: self.cone.map(|c| Cone {
+                heading: (c.heading % 360 + frame.rotation % 360) % 360,
+                ..c
+            }),
+        };
 
         let pos = self.pos;
-        let effective_max = (self.r as usize).min(max_dist);
-        trace_visible_cells(pos, collision, rays, effective_max, |offset, angle, d| {
-            self.render_light_pixel(offset, angle, d);
-        });
+        let effective_max = (shading.r as usize).min(max_dist);
+        if self.shape != LightShape::Point {
+            if let Some(bounds) = bounds {
+                self.render_shaped_emitter(collision, rays, effective_max, &shading, bounds);
+            }
+        } else if self.source_radius == 0 {
+            trace_visible_cells(
+                pos,
+                collision,
+                rays,
+                effective_max,
+                shading.cone.as_ref(),
+                |offset, angle, d, filter| {
+                    let edge = edge_factor(shading.cone.as_ref(), offset);
+                    let world = (pos.0 + offset.0, pos.1 + offset.1);
+                    self.render_light_pixel(world, angle, d, filter, edge, &shading);
+                },
+            );
+        } else {
+            self.render_area_source(collision, rays, effective_max, &shading);
+        }
 
         self.canvas.as_ptr()
     }
 
-    fn render_light_pixel(&mut self, cell: PtI, angle: usize, distance: u8) {
-        let c = (
-            cell.0 + self.canvas_size as i16 / 2,
-            cell.1 + self.canvas_size as i16 / 2,
-        );
+    /// The light's HDR value at world cell `world`, or `None` if the cell
+    /// lies outside its canvas.
+    pub(crate) fn hdr_at(&self, world: PtI) -> Option<Hdr> {
+        self.canvas_index(world).map(|idx| self.hdr[idx])
+    }
 
-        if c.0 < 0 || c.1 < 0 || c.0 >= self.canvas_size as i16 || c.1 >= self.canvas_size as i16 {
-            return;
+    /// Canvas index of world cell `world`, or `None` if it falls outside.
+    fn canvas_index(&self, world: PtI) -> Option<usize> {
+        let x = world.0 as i32 - self.canvas_origin.0 as i32;
+        let y = world.1 as i32 - self.canvas_origin.1 as i32;
+        if x < 0 || y < 0 || x >= self.canvas_width as i32 || y >= self.canvas_height as i32 {
+            return None;
         }
+        Some(x as usize + y as usize * self.canvas_width)
+    }
 
-        let cell_idx = c.0 as usize + c.1 as usize * self.canvas_size;
-        let falloff = 255 - (255 * distance as u16) / (self.r as u16);
+    /// Sub-origins sampled on an area source's disc, as offsets from its
+    /// centre. Diagonals sit at `radius / √2` (`181 / 256`), so all eight
+    /// outer samples lie on the rim.
+    fn source_samples(&self) -> Vec<PtI> {
+        let r = self.source_radius as i16;
+        let k = ((r as i32 * 181 + 128) / 256) as i16;
+        let mut samples = vec![
+            (0, 0),
+            (r, 0),
+            (-r, 0),
+            (0, r),
+            (0, -r),
+            (k, k),
+            (-k, k),
+            (k, -k),
+            (-k, -k),
+        ];
+        samples.dedup();
+        samples
+    }
 
-        if cell_idx < self.canvas.len() {
-            let color = match &self.color_mode {
-                None => {
-                    let scaled_hue = (angle * 255) / (ANGLES - 1);
-                    hsv2rgb(scaled_hue as u8, 255, falloff as u8)
-                }
-                Some(ColorMode::Solid(hue)) => hsv2rgb(*hue, 255, falloff as u8),
-                Some(ColorMode::Custom { hue, saturation }) => {
-                    hsv2rgb(*hue, *saturation, falloff as u8)
+    /// Penumbra pass: average, per canvas cell, the filters of the sub-origins
+    /// that can see it (a sub-origin that can't contributes black), then shade
+    /// every cell from the centre's geometry through that mean filter.
+    /// Sub-origins the centre cannot reach (inside or behind a wall) are
+    /// dropped so light never leaks through thin occluders.
+    fn render_area_source(
+        &mut self,
+        collision: &HybridCollisionMap,
+        rays: &RayTable,
+        effective_max: usize,
+        shading: &Shading,
+    ) {
+        let pos = self.pos;
+        let cells = self.canvas.len();
+        // Per-channel sum of the filters of every sub-origin that sees a cell.
+        let mut coverage = vec![[0u16; 3]; cells];
+        let mut seen = vec![false; cells];
+        let mut samples = 0u16;
+
+        for sample in self.source_samples() {
+            let origin = (pos.0 + sample.0, pos.1 + sample.1);
+            if sample != (0, 0) && collision.is_blocked(pos.0, pos.1, origin.0, origin.1) {
+                continue;
+            }
+            samples += 1;
+            seen.iter_mut().for_each(|s| *s = false);
+            trace_visible_cells(
+                origin,
+                collision,
+                rays,
+                effective_max,
+                None,
+                |offset, _, _, filter| {
+                    let Some(idx) = self.canvas_index((origin.0 + offset.0, origin.1 + offset.1))
+                    else {
+                        return;
+                    };
+                    if !seen[idx] {
+                        seen[idx] = true;
+                        coverage[idx][0] += filter.0 as u16;
+                        coverage[idx][1] += filter.1 as u16;
+                        coverage[idx][2] += filter.2 as u16;
+                    }
+                },
+            );
+        }
+
+        let width = self.canvas_width;
+        for (idx, &sum) in coverage.iter().enumerate() {
+            if sum == [0; 3] {
+                continue;
+            }
+            let world = (
+                self.canvas_origin.0 + (idx % width) as i16,
+                self.canvas_origin.1 + (idx / width) as i16,
+            );
+            let cell = (world.0 - pos.0, world.1 - pos.1);
+            let distance = arctan::distance(cell) as usize;
+            if distance >= effective_max {
+                continue;
+            }
+            if let Some(cone) = &shading.cone {
+                if cell != (0, 0) && !cone.contains(direction(cell)) {
+                    continue;
                 }
-            };
+            }
+            let angle = direction(cell) as usize % ANGLES;
+            let mean = |c: u16| (c / samples) as u8;
+            let filter = Filter(mean(sum[0]), mean(sum[1]), mean(sum[2]));
+            let edge = edge_factor(shading.cone.as_ref(), cell);
+            self.render_light_pixel(world, angle, distance as u8, filter, edge, shading);
+        }
+    }
+
+    /// Segment / rectangle pass: trace from every emitter origin within the
+    /// on-map `bounds` and keep, per canvas cell, the nearest trace that
+    /// reached it. Cells inside a rectangle emitter count as distance `0`.
+    fn render_shaped_emitter(
+        &mut self,
+        collision: &HybridCollisionMap,
+        rays: &RayTable,
+        effective_max: usize,
+        shading: &Shading,
+        bounds: (PtI, PtI),
+    ) {
+        let ((x0, y0), (x1, y1)) = bounds;
+        let inside_rect = |w: PtI| {
+            matches!(self.shape, LightShape::Rect { .. })
+                && w.0 >= x0
+                && w.0 <= x1
+                && w.1 >= y0
+                && w.1 <= y1
+        };
+        // Nearest trace per canvas cell: (distance, angle, filter, edge).
+        let mut nearest: Vec<Option<(u8, usize, Filter, u8)>> = vec![None; self.canvas.len()];
+
+        for origin in self.shape.trace_origins(self.pos, bounds) {
+            trace_visible_cells(
+                origin,
+                collision,
+                rays,
+                effective_max,
+                shading.cone.as_ref(),
+                |offset, angle, d, filter| {
+                    let world = (origin.0 + offset.0, origin.1 + offset.1);
+                    let Some(idx) = self.canvas_index(world) else {
+                        return;
+                    };
+                    let d = if inside_rect(world) { 0 } else { d };
+                    if nearest[idx].is_none_or(|(best, ..)| d < best) {
+                        let edge = edge_factor(shading.cone.as_ref(), offset);
+                        nearest[idx] = Some((d, angle, filter, edge));
+                    }
+                },
+            );
+        }
+
+        let width = self.canvas_width;
+        for (idx, hit) in nearest.into_iter().enumerate() {
+            if let Some((d, angle, filter, edge)) = hit {
+                let world = (
+                    self.canvas_origin.0 + (idx % width) as i16,
+                    self.canvas_origin.1 + (idx / width) as i16,
+                );
+                self.render_light_pixel(world, angle, d, filter, edge, shading);
+            }
+        }
+    }
+
+    /// Shade world cell `world`. `filter` is the light that reaches the cell
+    /// after translucent materials and, for area sources, partial occlusion;
+    /// `edge` is the spotlight soft-edge factor (`255` outside any soft band).
+    fn render_light_pixel(
+        &mut self,
+        world: PtI,
+        angle: usize,
+        distance: u8,
+        filter: Filter,
+        edge: u8,
+        shading: &Shading,
+    ) {
+        let Some(cell_idx) = self.canvas_index(world) else {
+            return;
+        };
+
+        let t = ((255 * distance as u16) / (shading.r as u16)).min(255);
+        let falloff = (shading.falloff[t as usize] as u16 * edge as u16 / 255) as u8;
 
-            self.canvas[cell_idx] = color;
+        let shade = |v: u8| match &self.color_mode {
+            None => {
+                let scaled_hue = (angle * 255) / (ANGLES - 1);
+                hsv2rgb(scaled_hue as u8, 255, v)
+            }
+            Some(ColorMode::Solid(hue)) => hsv2rgb(*hue, 255, v),
+            Some(ColorMode::Custom { hue, saturation }) => hsv2rgb(*hue, *saturation, v),
+            Some(ColorMode::Rgb(r, g, b)) => scale_rgb((*r, *g, *b), v),
+            Some(ColorMode::Kelvin(temp)) => scale_rgb(kelvin2rgb(*temp), v),
+        };
+        let hdr = match self.color_space {
+            ColorSpace::Srgb => {
+                let Color(r, g, b, a) = shade(falloff);
+                let (r, g, b) = filter.apply((r, g, b));
+                Hdr::from_color(Color(r, g, b, a))
+            }
+            ColorSpace::Linear => shade_linear(shade(255), falloff, filter),
         }
+        .scale(shading.intensity);
+
+        self.hdr[cell_idx] = hdr;
+        self.canvas[cell_idx] = self.color_space.lower(hdr);
+    }
+}
+
+/// Spotlight soft-edge factor for a cell at `offset` from the ray origin:
+/// `255` without a cone or at the origin itself.
+fn edge_factor(cone: Option<&Cone>, offset: PtI) -> u8 {
+    match cone {
+        Some(cone) if offset != (0, 0) => cone.edge_factor(direction(offset)),
+        _ => 255,
     }
 }
 
 /// A full-map RGBA canvas: `size²` cells in row-major order, blitted at origin
 /// `(0,0)` by the JS compositor. Shared storage behind the engine's full-map
-/// effects ([`Ambient`], [`Fov`]); each wraps one and adds its own write
-/// primitive. The persistent allocation keeps the pointer handed to JS valid
-/// between frames.
-struct FullMapCanvas {
+/// effects ([`Ambient`], [`Fov`], [`crate::composite::Composite`]); each wraps
+/// one and adds its own write primitive. The persistent allocation keeps the
+/// pointer handed to JS valid between frames.
+pub(crate) struct FullMapCanvas {
     cells: Vec<Color>,
     size: usize,
 }
 
 impl FullMapCanvas {
     /// Allocate a fully-transparent `size²` canvas.
-    fn new(size: usize) -> Self {
+    pub(crate) fn new(size: usize) -> Self {
         FullMapCanvas {
             cells: vec![Color::default(); size * size],
             size,
         }
     }
 
-    fn cells(&self) -> &[Color] {
+    pub(crate) fn cells(&self) -> &[Color] {
         &self.cells
     }
 
+    pub(crate) fn cells_mut(&mut self) -> &mut [Color] {
+        &mut self.cells
+    }
+
+    pub(crate) fn size(&self) -> usize {
+        self.size
+    }
+
     /// Reset every cell to transparent.
-    fn clear(&mut self) {
+    pub(crate) fn clear(&mut self) {
         self.cells.iter_mut().for_each(|p| *p = Color::default());
     }
 
     /// Write `color` to cell `(x, y)`; out-of-bounds writes are ignored.
-    fn set(&mut self, x: i16, y: i16, color: Color) {
+    pub(crate) fn set(&mut self, x: i16, y: i16, color: Color) {
         if x < 0 || y < 0 || x >= self.size as i16 || y >= self.size as i16 {
             return;
         }
@@ -306,6 +1025,78 @@ impl FullMapCanvas {
     }
 }
 
+/// How far an [`Ambient`] reaches beyond its emitter's Room.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AmbientSpread {
+    /// Fill the emitter's Room only; never crosses a Door (the default).
+    #[default]
+    Room,
+    /// Also spill through open Doors, scaling the colour by `factor`
+    /// (`0`–`255`, of full) for every Door crossed on the way.
+    DoorHops { factor: u8 },
+    /// Also spill through open Doors, fading linearly to nothing `tiles`
+    /// tile steps from the Room, counting each step into and across the
+    /// rooms beyond.
+    Geodesic { tiles: u8 },
+}
+
+impl AmbientSpread {
+    /// Decode the WASM-facing numeric form: `0` room, `1` door hops
+    /// (`param` = factor), `2` geodesic (`param` = tiles). Unknown kinds fall
+    /// back to [`AmbientSpread::Room`].
+    pub fn from_u8(kind: u8, param: u8) -> Self {
+        match kind {
+            1 => AmbientSpread::DoorHops { factor: param },
+            2 => AmbientSpread::Geodesic { tiles: param },
+            _ => AmbientSpread::Room,
+        }
+    }
+
+    /// Brightness (`0`–`255`) of a tile `steps` away from the emitter's
+    /// Room: Doors crossed for [`AmbientSpread::DoorHops`], tile steps for
+    /// [`AmbientSpread::Geodesic`]. `255` inside the Room itself.
+    pub(crate) fn brightness(self, steps: u32) -> u8 {
+        match self {
+            _ if steps == 0 => 255,
+            AmbientSpread::Room => 0,
+            AmbientSpread::DoorHops { factor } => (0..steps)
+                .try_fold(255u32, |b, _| {
+                    let next = b * factor as u32 / 255;
+                    (next > 0).then_some(next)
+                })
+                .unwrap_or(0) as u8,
+            AmbientSpread::Geodesic { tiles } => {
+                let tiles = tiles as u32;
+                (255 * tiles.saturating_sub(steps) / tiles.max(1)) as u8
+            }
+        }
+    }
+}
+
+/// Wall-hugging darkening of an [`Ambient`]'s fill.
+///
+/// A cell is darkened by up to `strength` (`0`–`255`) for each axis along
+/// which a Wall edge or an Object cell lies within `radius` cells, fading
+/// linearly with distance; the two axes combine multiplicatively, so a room
+/// corner is darker than the middle of a wall.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AmbientOcclusion {
+    pub radius: u8,
+    pub strength: u8,
+}
+
+impl AmbientOcclusion {
+    /// Darkening (`0`–`255`) for an occluder `distance` cells away, if any.
+    fn darkness(self, distance: Option<u8>) -> u32 {
+        match distance {
+            Some(d) if d < self.radius => {
+                self.strength as u32 * (self.radius - d) as u32 / self.radius as u32
+            }
+            _ => 0,
+        }
+    }
+}
+
 /// A room-bounded flat ambient fill.
 ///
 /// Unlike a [`Light`] (a point source with radial falloff), an `Ambient` has
@@ -314,9 +1105,18 @@ impl FullMapCanvas {
 /// mask (`255` inside the room, `0` everywhere else).
 ///
 /// Owned by [`crate::engine::LightingEngine`], which floods it via
-/// `update_or_add_ambient`.
+/// `update_or_add_ambient` and re-floods it from the stored emitter when it is
+/// re-enabled. With an [`AmbientSpread`] other than `Room` it also spills,
+/// dimmer, into rooms joined to its own by open Doors.
 pub struct Ambient {
     canvas: FullMapCanvas,
+    /// Emitter tile and fill colour, kept so the flood can be redone.
+    tile: PtI,
+    color: Color,
+    /// Disabled ambients keep their canvas allocated but transparent.
+    enabled: bool,
+    spread: AmbientSpread,
+    occlusion: Option<AmbientOcclusion>,
 }
 
 impl Ambient {
@@ -324,9 +1124,47 @@ impl Ambient {
     pub(crate) fn new(canvas_size: usize) -> Self {
         Ambient {
             canvas: FullMapCanvas::new(canvas_size),
+            tile: (0, 0),
+            color: Color::default(),
+            enabled: true,
+            spread: AmbientSpread::Room,
+            occlusion: None,
         }
     }
 
+    pub(crate) fn emitter(&self) -> (PtI, Color) {
+        (self.tile, self.color)
+    }
+
+    pub(crate) fn set_emitter(&mut self, tile: PtI, color: Color) {
+        self.tile = tile;
+        self.color = color;
+    }
+
+    pub(crate) fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub(crate) fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub(crate) fn spread(&self) -> AmbientSpread {
+        self.spread
+    }
+
+    pub(crate) fn set_spread(&mut self, spread: AmbientSpread) {
+        self.spread = spread;
+    }
+
+    pub(crate) fn occlusion(&self) -> Option<AmbientOcclusion> {
+        self.occlusion
+    }
+
+    pub(crate) fn set_occlusion(&mut self, occlusion: Option<AmbientOcclusion>) {
+        self.occlusion = occlusion;
+    }
+
     pub(crate) fn canvas(&self) -> &[Color] {
         self.canvas.cells()
     }
@@ -336,20 +1174,77 @@ impl Ambient {
         self.canvas.clear();
     }
 
-    /// Fill the `cells_per_tile²` block of cells belonging to tile
-    /// `(tile_x, tile_y)` with `color`.
+    /// Fill the cells of the `cells_per_tile²` block belonging to tile
+    /// `(tile_x, tile_y)` for which `keep(dx, dy)` (tile-local) holds with
+    /// `color`.
     pub(crate) fn fill_tile(
         &mut self,
         tile_x: usize,
         tile_y: usize,
         cells_per_tile: usize,
         color: Color,
+        keep: impl Fn(usize, usize) -> bool,
     ) {
         let cx0 = tile_x * cells_per_tile;
         let cy0 = tile_y * cells_per_tile;
         for dy in 0..cells_per_tile {
             for dx in 0..cells_per_tile {
-                self.canvas.set((cx0 + dx) as i16, (cy0 + dy) as i16, color);
+                if keep(dx, dy) {
+                    self.canvas.set((cx0 + dx) as i16, (cy0 + dy) as i16, color);
+                }
+            }
+        }
+    }
+
+    /// Darken every filled cell by the configured [`AmbientOcclusion`], if
+    /// any. Only filled cells are touched, so the fill never grows past its
+    /// rooms. `cells` is the engine's block map (same `size²` layout as the
+    /// canvas); a step off the map counts as hitting a Wall.
+    pub(crate) fn apply_occlusion(&mut self, cells: &[CellDetails], objects: &PixelCollisionMap) {
+        let Some(ao) = self.occlusion.filter(|ao| ao.radius > 0 && ao.strength > 0) else {
+            return;
+        };
+        let size = self.canvas.size() as i32;
+        // Cells walked from (x, y) in direction (dx, dy) before crossing a
+        // Wall edge or reaching an Object cell, up to the radius.
+        let reach = |x: i32, y: i32, (dx, dy): (i32, i32)| -> Option<u8> {
+            let (mut x, mut y) = (x, y);
+            for d in 0..ao.radius {
+                let edge = cells[(x + y * size) as usize];
+                let walled = match (dx, dy) {
+                    (1, _) => edge.e_blocked,
+                    (-1, _) => edge.w_blocked,
+                    (_, 1) => edge.s_blocked,
+                    _ => edge.n_blocked,
+                };
+                let (nx, ny) = (x + dx, y + dy);
+                if walled
+                    || nx < 0
+                    || ny < 0
+                    || nx >= size
+                    || ny >= size
+                    || objects.get_pixel(nx as u16, ny as u16)
+                {
+                    return Some(d);
+                }
+                (x, y) = (nx, ny);
+            }
+            None
+        };
+
+        for y in 0..size {
+            for x in 0..size {
+                let idx = (x + y * size) as usize;
+                let Color(r, g, b, a) = self.canvas.cells()[idx];
+                if a == 0 {
+                    continue;
+                }
+                let nearest = |p: Option<u8>, q: Option<u8>| p.into_iter().chain(q).min();
+                let across = ao.darkness(nearest(reach(x, y, (-1, 0)), reach(x, y, (1, 0))));
+                let along = ao.darkness(nearest(reach(x, y, (0, -1)), reach(x, y, (0, 1))));
+                let keep = (255 - across) * (255 - along) / 255;
+                let dim = |c: u8| (c as u32 * keep / 255) as u8;
+                self.canvas.cells_mut()[idx] = Color(dim(r), dim(g), dim(b), a);
             }
         }
     }
@@ -393,6 +1288,132 @@ impl Fov {
     }
 }
 
+/// A built-in glow for a cell or a whole tile type (lava, crystals, screens):
+/// `color` at full brightness on the emitting cell, fading linearly to zero
+/// `radius` cells away. A radius of `0` lights only the cell itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Emissive {
+    pub color: (u8, u8, u8),
+    pub radius: u8,
+}
+
+/// The full-map **emissive canvas**: the combined glow of every emissive cell.
+///
+/// Rendering is batched per region rather than per cell. Every emissive cell
+/// lights itself, but only cells on the edge of a region of identical
+/// [`Emissive`]s are traced — light from an interior cell leaves the region
+/// through its edge anyway — and overlapping glows keep the brightest value
+/// per channel. Occlusion is the same Room + Object check as a [`Light`]'s, so
+/// an emissive tile type's glow stays inside its own tiles unless a door
+/// joins them to a neighbour.
+pub struct EmissiveLayer {
+    canvas: FullMapCanvas,
+}
+
+impl EmissiveLayer {
+    /// Allocate a fully-transparent full-map canvas of `canvas_size²` cells.
+    pub(crate) fn new(canvas_size: usize) -> Self {
+        EmissiveLayer {
+            canvas: FullMapCanvas::new(canvas_size),
+        }
+    }
+
+    pub(crate) fn canvas(&self) -> &[Color] {
+        self.canvas.cells()
+    }
+
+    /// Re-render the glow of `sources` (world cell → emitter) from scratch.
+    ///
+    /// An emissive Object cell blocks its own rays, so it radiates from its
+    /// open 4-neighbours instead, one cell further out. The fade is shaded
+    /// in `space`.
+    pub(crate) fn render(
+        &mut self,
+        sources: &HashMap<PtI, Emissive>,
+        collision: &HybridCollisionMap,
+        rays: &RayTable,
+        max_dist: usize,
+        space: ColorSpace,
+    ) {
+        const NEIGHBOURS: [PtI; 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+        let size = self.canvas.size();
+        let blocked = |c: PtI| {
+            c.0 < 0
+                || c.1 < 0
+                || c.0 as usize >= size
+                || c.1 as usize >= size
+                || collision.is_occupied(c.0 as u16, c.1 as u16)
+        };
+        let mut glow = vec![[0u8; 3]; size * size];
+        let mut brighten = |c: PtI, rgb: (u8, u8, u8)| {
+            if c.0 < 0 || c.1 < 0 || c.0 as usize >= size || c.1 as usize >= size {
+                return;
+            }
+            let cell = &mut glow[c.0 as usize + c.1 as usize * size];
+            cell[0] = cell[0].max(rgb.0);
+            cell[1] = cell[1].max(rgb.1);
+            cell[2] = cell[2].max(rgb.2);
+        };
+
+        for (&cell, emissive) in sources {
+            brighten(cell, emissive.color);
+            let edge = NEIGHBOURS
+                .iter()
+                .any(|n| sources.get(&(cell.0 + n.0, cell.1 + n.1)) != Some(emissive));
+            if !edge || emissive.radius == 0 {
+                continue;
+            }
+            let reach = (emissive.radius as usize).min(max_dist);
+            let origins: Vec<(PtI, usize)> = if blocked(cell) {
+                NEIGHBOURS
+                    .iter()
+                    .map(|n| (cell.0 + n.0, cell.1 + n.1))
+                    .filter(|&n| !blocked(n))
+                    .map(|n| (n, 1))
+                    .collect()
+            } else {
+                vec![(cell, 0)]
+            };
+            for (origin, start) in origins {
+                if start >= reach {
+                    continue;
+                }
+                trace_visible_cells(
+                    origin,
+                    collision,
+                    rays,
+                    reach - start,
+                    None,
+                    |offset, _, d, filter| {
+                        let t = (255 * (d as usize + start) / reach).min(255) as u8;
+                        let (r, g, b) = match space {
+                            ColorSpace::Srgb => {
+                                let Color(r, g, b, _) = scale_rgb(emissive.color, 255 - t);
+                                filter.apply((r, g, b))
+                            }
+                            ColorSpace::Linear => {
+                                let (r, g, b) = emissive.color;
+                                let Color(r, g, b, _) =
+                                    space.lower(shade_linear(Color(r, g, b, 255), 255 - t, filter));
+                                (r, g, b)
+                            }
+                        };
+                        brighten((origin.0 + offset.0, origin.1 + offset.1), (r, g, b));
+                    },
+                );
+            }
+        }
+
+        for (dst, [r, g, b]) in self.canvas.cells_mut().iter_mut().zip(glow) {
+            *dst = if [r, g, b] == [0; 3] {
+                Color::default()
+            } else {
+                Color(r, g, b, 255)
+            };
+        }
+    }
+}
+
 /// HSV-to-RGB conversion. Alpha is always 255.
 pub(crate) fn hsv2rgb(h: u8, s: u8, v: u8) -> Color {
     if s == 0 {
@@ -416,13 +1437,59 @@ pub(crate) fn hsv2rgb(h: u8, s: u8, v: u8) -> Color {
     }
 }
 
+/// Scale an RGB triple by brightness `v` (0-255). Alpha is always 255.
+fn scale_rgb((r, g, b): (u8, u8, u8), v: u8) -> Color {
+    let scale = |c: u8| (c as u16 * v as u16 / 255) as u8;
+    Color(scale(r), scale(g), scale(b), 255)
+}
+
+/// Black-body colours (sRGB, brightest channel normalised to 255) sampled
+/// every 500 K from 1000 K to 12000 K.
+const KELVIN_TABLE: [(u8, u8, u8); 23] = [
+    (255, 56, 0),
+    (255, 109, 0),
+    (255, 137, 18),
+    (255, 161, 72),
+    (255, 180, 107),
+    (255, 196, 137),
+    (255, 209, 163),
+    (255, 219, 186),
+    (255, 228, 206),
+    (255, 236, 224),
+    (255, 243, 239),
+    (255, 249, 253),
+    (245, 243, 255),
+    (235, 238, 255),
+    (227, 233, 255),
+    (220, 229, 255),
+    (214, 225, 255),
+    (208, 222, 255),
+    (204, 219, 255),
+    (200, 217, 255),
+    (196, 215, 255),
+    (193, 213, 255),
+    (191, 211, 255),
+];
+
+/// Colour-temperature-to-RGB conversion by linear interpolation between
+/// [`KELVIN_TABLE`] samples. Integer-only; `temp` is clamped to 1000–12000 K.
+pub(crate) fn kelvin2rgb(temp: u16) -> (u8, u8, u8) {
+    let offset = temp.clamp(1000, 12000) as u32 - 1000;
+    let i = (offset / 500) as usize;
+    let frac = offset % 500;
+    let lo = KELVIN_TABLE[i];
+    let hi = KELVIN_TABLE[(i + 1).min(KELVIN_TABLE.len() - 1)];
+    let lerp = |a: u8, b: u8| ((a as u32 * (500 - frac) + b as u32 * frac) / 500) as u8;
+    (lerp(lo.0, hi.0), lerp(lo.1, hi.1), lerp(lo.2, hi.2))
+}
+
 // ------------------------------- shims ----------------------------------
 
 /// WASM/back-compat shim. Forwards to [`crate::engine::DEFAULT_ENGINE`].
 pub fn update_or_add_light(id: u8, r: i16, x: i16, y: i16) -> *const Color {
     DEFAULT_ENGINE
         .write()
-        .map(|mut e| e.update_or_add_light(id, r, x, y))
+        .map(|mut e| e.update_or_add_light(id.into(), r, x, y))
         .unwrap_or(std::ptr::null())
 }
 
@@ -436,7 +1503,7 @@ pub fn update_or_add_light_with_solid_color(
 ) -> *const Color {
     DEFAULT_ENGINE
         .write()
-        .map(|mut e| e.update_or_add_light_with_solid_color(id, r, x, y, hue))
+        .map(|mut e| e.update_or_add_light_with_solid_color(id.into(), r, x, y, hue))
         .unwrap_or(std::ptr::null())
 }
 
@@ -451,7 +1518,7 @@ pub fn update_or_add_light_with_custom_color(
 ) -> *const Color {
     DEFAULT_ENGINE
         .write()
-        .map(|mut e| e.update_or_add_light_with_custom_color(id, r, x, y, hue, saturation))
+        .map(|mut e| e.update_or_add_light_with_custom_color(id.into(), r, x, y, hue, saturation))
         .unwrap_or(std::ptr::null())
 }
 
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Point {
@@ -24,6 +24,12 @@ pub struct UnionFind {
 
 impl UnionFind {
     pub fn new(map: Vec<i32>, layer_size: usize) -> Self {
+        Self::new_with_cuts(map, layer_size, &HashSet::new())
+    }
+
+    /// Like [`Self::new`], but never unions across the canonical `(lo, hi)`
+    /// index pairs in `cuts`, even when both sides hold the same value.
+    pub fn new_with_cuts(map: Vec<i32>, layer_size: usize, cuts: &HashSet<(usize, usize)>) -> Self {
         let size = map.len();
         let mut parent = vec![0; size];
         for i in 0..size {
@@ -38,23 +44,23 @@ impl UnionFind {
             layer_size,
         };
 
-        uf.initialize();
+        uf.initialize(cuts);
         uf
     }
 
-    fn initialize(&mut self) {
+    fn initialize(&mut self, cuts: &HashSet<(usize, usize)>) {
         for col in 0..self.layer_size {
             for row in 0..self.layer_size {
                 let current = self.index(col as i32, row as i32);
                 if row + 1 < self.layer_size {
                     let next = self.index(col as i32, (row + 1) as i32);
-                    if self.map[current] == self.map[next] {
+                    if self.map[current] == self.map[next] && !cuts.contains(&(current, next)) {
                         self.union(current, next);
                     }
                 }
                 if col + 1 < self.layer_size {
                     let next = self.index((col + 1) as i32, row as i32);
-                    if self.map[current] == self.map[next] {
+                    if self.map[current] == self.map[next] && !cuts.contains(&(current, next)) {
                         self.union(current, next);
                     }
                 }
@@ -0,0 +1,431 @@
+//! A registry of Objects — coherent groups of blocked Cells with an id, a
+//! position and a shape.
+//!
+//! `set_pixel` only knows single bits; an [`ObjectRegistry`] rasterises each
+//! [`ObjectShape`] at its Object's position and writes the result into the
+//! static bitmap of every [`Channel`] the Object blocks. Occupancy is
+//! reference-counted per cell and channel, so overlapping Objects can be
+//! moved or removed independently: a cell is only cleared once no Object
+//! covers it. A cell that was already blocked by a plain `set_pixel` write
+//! when an Object claimed it is left blocked after the Object goes.
+
+use std::collections::HashMap;
+
+use crate::collision::{Channel, Channels, HybridCollisionMap, PixelCollisionMap};
+
+/// Identifier of an Object, chosen by the caller.
+pub type ObjectId = u32;
+
+/// Footprint of an Object, anchored at the Object's position.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ObjectShape {
+    /// A `width × height` block whose top-left cell is the position.
+    Rect { width: u16, height: u16 },
+    /// Every cell within `radius` (Euclidean) of the position.
+    Circle { radius: u16 },
+    /// Arbitrary cells, as offsets from the position.
+    Cells(Vec<(i16, i16)>),
+    /// A `width × height` bitmap whose top-left cell is the position: one bit
+    /// per cell, row-major, least-significant bit first — the layout
+    /// `canvas_bits` produces. Missing trailing bytes read as clear.
+    Mask {
+        width: u16,
+        height: u16,
+        bits: Vec<u8>,
+    },
+}
+
+impl ObjectShape {
+    /// The on-map cells of the shape anchored at `position` in a
+    /// `size × size` map, sorted row-major and without duplicates. The
+    /// bounding box is clipped to the map before rasterising, so a huge or
+    /// far-off shape costs only the cells it actually covers.
+    pub fn cells_at(&self, position: (i16, i16), size: u16) -> Vec<(u16, u16)> {
+        let (x, y, size) = (position.0 as i32, position.1 as i32, size as i32);
+        // The on-map part of the half-open box `[x0, x1) × [y0, y1)`.
+        let clip = |x0: i32, y0: i32, x1: i32, y1: i32| {
+            let (x0, y0, x1, y1) = (x0.max(0), y0.max(0), x1.min(size), y1.min(size));
+            (y0..y1).flat_map(move |cy| (x0..x1).map(move |cx| (cx, cy)))
+        };
+        let mut cells: Vec<(i32, i32)> = match self {
+            ObjectShape::Rect { width, height } => {
+                clip(x, y, x + *width as i32, y + *height as i32).collect()
+            }
+            ObjectShape::Circle { radius } => {
+                let r = *radius as i32;
+                let r2 = r as i64 * r as i64;
+                clip(x - r, y - r, x + r + 1, y + r + 1)
+                    .filter(|&(cx, cy)| {
+                        let (dx, dy) = ((cx - x) as i64, (cy - y) as i64);
+                        dx * dx + dy * dy <= r2
+                    })
+                    .collect()
+            }
+            ObjectShape::Cells(offsets) => offsets
+                .iter()
+                .map(|&(dx, dy)| (x + dx as i32, y + dy as i32))
+                .filter(|&(cx, cy)| cx >= 0 && cy >= 0 && cx < size && cy < size)
+                .collect(),
+            ObjectShape::Mask {
+                width,
+                height,
+                bits,
+            } => {
+                let width = *width as i32;
+                clip(x, y, x + width, y + *height as i32)
+                    .filter(|&(cx, cy)| {
+                        let i = ((cy - y) * width + cx - x) as usize;
+                        bits.get(i / 8).is_some_and(|byte| byte >> (i % 8) & 1 != 0)
+                    })
+                    .collect()
+            }
+        };
+        cells.sort_unstable_by_key(|&(cx, cy)| (cy, cx));
+        cells.dedup();
+        cells
+            .into_iter()
+            .map(|(cx, cy)| (cx as u16, cy as u16))
+            .collect()
+    }
+}
+
+/// One registered Object.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Object {
+    position: (i16, i16),
+    shape: ObjectShape,
+    channels: Channels,
+}
+
+impl Object {
+    /// An Object blocking every channel.
+    pub fn new(position: (i16, i16), shape: ObjectShape) -> Self {
+        Object {
+            position,
+            shape,
+            channels: Channels::ALL,
+        }
+    }
+
+    /// The same Object, blocking only `channels` — a low crate blocks
+    /// movement but not light.
+    pub fn with_channels(self, channels: Channels) -> Self {
+        Object { channels, ..self }
+    }
+
+    pub fn channels(&self) -> Channels {
+        self.channels
+    }
+
+    pub fn position(&self) -> (i16, i16) {
+        self.position
+    }
+
+    pub fn shape(&self) -> &ObjectShape {
+        &self.shape
+    }
+
+    /// The cells the Object covers on a `size × size` map.
+    pub fn cells(&self, size: u16) -> Vec<(u16, u16)> {
+        self.shape.cells_at(self.position, size)
+    }
+}
+
+/// How many Objects cover a cell, and whether a plain `set_pixel` write
+/// would have it blocked on its own.
+#[derive(Clone, Copy, Debug, Default)]
+struct Owners {
+    count: u16,
+    loose: bool,
+}
+
+/// Every registered Object and the per-cell occupancy they add up to.
+///
+/// Mutators take the collision map whose bitmaps they keep in sync and
+/// return the `(channel, x, y)` bits that actually flipped, so the caller can
+/// mark exactly those dirty.
+pub struct ObjectRegistry {
+    size: u16,
+    objects: HashMap<ObjectId, Object>,
+    owners: HashMap<(Channel, u16, u16), Owners>,
+}
+
+impl ObjectRegistry {
+    /// An empty registry for a `size × size` cell map.
+    pub fn new(size: u16) -> Self {
+        ObjectRegistry {
+            size,
+            objects: HashMap::new(),
+            owners: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, id: ObjectId) -> Option<&Object> {
+        self.objects.get(&id)
+    }
+
+    /// Registered ids, ascending.
+    pub fn ids(&self) -> Vec<ObjectId> {
+        let mut ids: Vec<ObjectId> = self.objects.keys().copied().collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Number of Objects blocking `channel` at cell `(x, y)`.
+    pub fn owner_count(&self, channel: Channel, x: u16, y: u16) -> u16 {
+        self.owners.get(&(channel, x, y)).map_or(0, |o| o.count)
+    }
+
+    /// Register `object` under `id`, replacing any Object already there.
+    /// The new footprint is claimed before the old one is released, so cells
+    /// shared by both never flicker clear.
+    pub fn insert(
+        &mut self,
+        id: ObjectId,
+        object: Object,
+        maps: &mut HybridCollisionMap,
+    ) -> Vec<(Channel, u16, u16)> {
+        let mut flipped = Vec::new();
+        let cells = object.cells(self.size);
+        for channel in object.channels.iter() {
+            for &cell in &cells {
+                self.claim(channel, cell, maps.channel_map_mut(channel), &mut flipped);
+            }
+        }
+        if let Some(old) = self.objects.insert(id, object) {
+            self.release_all(&old, maps, &mut flipped);
+        }
+        flipped.sort_unstable_by_key(|&(channel, x, y)| (channel as u8, y, x));
+        flipped.dedup();
+        flipped
+    }
+
+    /// Unregister `id`, clearing the cells no other Object covers. `None` if
+    /// the id is unknown.
+    pub fn remove(
+        &mut self,
+        id: ObjectId,
+        maps: &mut HybridCollisionMap,
+    ) -> Option<Vec<(Channel, u16, u16)>> {
+        let old = self.objects.remove(&id)?;
+        let mut flipped = Vec::new();
+        self.release_all(&old, maps, &mut flipped);
+        Some(flipped)
+    }
+
+    /// Route a plain `set_pixel` write: the cell should block exactly
+    /// `blocked`. Channels no Object covers at the cell are written straight
+    /// into `maps`; for the others the write is remembered until the last
+    /// Object leaves, and the cell stays blocked meanwhile.
+    pub fn write_loose(
+        &mut self,
+        x: u16,
+        y: u16,
+        blocked: Channels,
+        maps: &mut HybridCollisionMap,
+    ) {
+        for channel in Channel::ALL {
+            let on = blocked.contains(channel);
+            match self.owners.get_mut(&(channel, x, y)) {
+                Some(owners) => owners.loose = on,
+                None => maps.channel_map_mut(channel).set_pixel(x, y, on),
+            }
+        }
+    }
+
+    /// Forget every Object without touching any bitmap (the caller is
+    /// clearing it wholesale).
+    pub fn clear(&mut self) {
+        self.objects.clear();
+        self.owners.clear();
+    }
+
+    fn release_all(
+        &mut self,
+        object: &Object,
+        maps: &mut HybridCollisionMap,
+        flipped: &mut Vec<(Channel, u16, u16)>,
+    ) {
+        let cells = object.cells(self.size);
+        for channel in object.channels.iter() {
+            for &cell in &cells {
+                self.release(channel, cell, maps.channel_map_mut(channel), flipped);
+            }
+        }
+    }
+
+    fn claim(
+        &mut self,
+        channel: Channel,
+        (x, y): (u16, u16),
+        map: &mut PixelCollisionMap,
+        flipped: &mut Vec<(Channel, u16, u16)>,
+    ) {
+        let owners = self
+            .owners
+            .entry((channel, x, y))
+            .or_insert_with(|| Owners {
+                count: 0,
+                loose: map.get_pixel(x, y),
+            });
+        if owners.count == 0 && !owners.loose {
+            map.set_pixel(x, y, true);
+            flipped.push((channel, x, y));
+        }
+        owners.count += 1;
+    }
+
+    fn release(
+        &mut self,
+        channel: Channel,
+        (x, y): (u16, u16),
+        map: &mut PixelCollisionMap,
+        flipped: &mut Vec<(Channel, u16, u16)>,
+    ) {
+        let Some(owners) = self.owners.get_mut(&(channel, x, y)) else {
+            return;
+        };
+        owners.count -= 1;
+        if owners.count == 0 {
+            let loose = owners.loose;
+            self.owners.remove(&(channel, x, y));
+            if !loose {
+                map.set_pixel(x, y, false);
+                flipped.push((channel, x, y));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shapes_rasterise_to_cells() {
+        assert_eq!(
+            ObjectShape::Rect {
+                width: 2,
+                height: 2
+            }
+            .cells_at((1, 1), 10),
+            vec![(1, 1), (2, 1), (1, 2), (2, 2)]
+        );
+        assert_eq!(
+            ObjectShape::Circle { radius: 1 }.cells_at((5, 5), 10).len(),
+            5
+        );
+        assert_eq!(
+            ObjectShape::Circle { radius: 2 }.cells_at((5, 5), 10).len(),
+            13
+        );
+        assert_eq!(
+            ObjectShape::Cells(vec![(1, 0), (0, 0), (1, 0)]).cells_at((0, 0), 10),
+            vec![(0, 0), (1, 0)]
+        );
+        let mask = ObjectShape::Mask {
+            width: 3,
+            height: 3,
+            bits: vec![0b0101_0001, 0b1],
+        };
+        assert_eq!(
+            mask.cells_at((0, 0), 10),
+            vec![(0, 0), (1, 1), (0, 2), (2, 2)]
+        );
+        assert_eq!(
+            mask.cells_at((-1, 8), 10),
+            vec![(0, 9)],
+            "clipped to the map"
+        );
+    }
+
+    #[test]
+    fn huge_and_far_shapes_only_rasterise_on_map_cells() {
+        let rect = ObjectShape::Rect {
+            width: 40000,
+            height: 1,
+        };
+        assert_eq!(rect.cells_at((32000, 0), 10), vec![]);
+        assert_eq!(rect.cells_at((-5, 0), 10).len(), 10);
+        let circle = ObjectShape::Circle { radius: 30000 };
+        assert_eq!(circle.cells_at((0, 0), 10).len(), 100);
+        let far = ObjectShape::Cells(vec![(i16::MAX, i16::MAX), (-1, 0)]);
+        assert_eq!(far.cells_at((i16::MAX, 0), 10), vec![]);
+    }
+
+    fn maps() -> HybridCollisionMap {
+        HybridCollisionMap::new(vec![1; 100], 10)
+    }
+
+    fn light(flipped: Vec<(Channel, u16, u16)>) -> Vec<(u16, u16)> {
+        flipped
+            .into_iter()
+            .filter(|f| f.0 == Channel::Light)
+            .map(|(_, x, y)| (x, y))
+            .collect()
+    }
+
+    #[test]
+    fn overlapping_objects_share_cells_by_count() {
+        let mut maps = maps();
+        let mut reg = ObjectRegistry::new(10);
+        let bar = ObjectShape::Rect {
+            width: 2,
+            height: 1,
+        };
+        let flipped = reg.insert(1, Object::new((2, 2), bar.clone()), &mut maps);
+        assert_eq!(light(flipped), vec![(2, 2), (3, 2)]);
+        let flipped = reg.insert(2, Object::new((3, 2), bar), &mut maps);
+        assert_eq!(light(flipped), vec![(4, 2)]);
+        assert_eq!(reg.owner_count(Channel::Light, 3, 2), 2);
+
+        assert_eq!(reg.remove(1, &mut maps).map(light), Some(vec![(2, 2)]));
+        assert!(!maps.pixel_map().get_pixel(2, 2));
+        assert!(
+            maps.pixel_map().get_pixel(3, 2),
+            "still covered by object 2"
+        );
+        assert_eq!(reg.remove(1, &mut maps), None);
+    }
+
+    #[test]
+    fn loose_writes_survive_objects_and_off_map_cells_are_skipped() {
+        let mut maps = maps();
+        maps.pixel_map_mut().set_pixel(0, 0, true);
+        let mut reg = ObjectRegistry::new(10);
+        let corner = Object::new(
+            (-1, -1),
+            ObjectShape::Rect {
+                width: 2,
+                height: 2,
+            },
+        );
+        let flipped = reg.insert(1, corner.with_channels(Channels::LIGHT), &mut maps);
+        assert!(flipped.is_empty(), "(0,0) was already blocked");
+        assert_eq!(reg.remove(1, &mut maps), Some(vec![]));
+        assert!(maps.pixel_map().get_pixel(0, 0));
+
+        reg.insert(
+            2,
+            Object::new((5, 5), ObjectShape::Cells(vec![(0, 0)])),
+            &mut maps,
+        );
+        reg.write_loose(5, 5, Channels::LIGHT, &mut maps);
+        reg.remove(2, &mut maps);
+        assert!(
+            maps.pixel_map().get_pixel(5, 5),
+            "the loose write outlives the object"
+        );
+        assert!(!maps.channel_map(Channel::Sight).get_pixel(5, 5));
+    }
+
+    #[test]
+    fn objects_only_touch_their_own_channels() {
+        let mut maps = maps();
+        let mut reg = ObjectRegistry::new(10);
+        let crate_ = Object::new((4, 4), ObjectShape::Cells(vec![(0, 0)]));
+        let flipped = reg.insert(1, crate_.with_channels(Channels::MOVEMENT), &mut maps);
+        assert_eq!(flipped, vec![(Channel::Movement, 4, 4)]);
+        assert_eq!(maps.cell_channels(4, 4), Channels::MOVEMENT);
+    }
+}
@@ -22,12 +22,12 @@
 //!
 //! # Adding a scenario
 //!
-//! 1. Write a function `pub fn my_scenario(e: &mut LightingEngine) -> u8`.
+//! 1. Write a function `pub fn my_scenario(e: &mut LightingEngine) -> LightId`.
 //! 2. Add a [`Scenario`] entry to [`SCENARIOS`].
 //! 3. Run `cargo run --example scenario -- --name my_scenario` to eyeball it.
 //! 4. Add a regression test in `tests/scenarios.rs`.
 
-use crate::engine::LightingEngine;
+use crate::engine::{LightId, LightingEngine};
 
 /// A named scenario the exploration and regression loops can reference.
 pub struct Scenario {
@@ -37,7 +37,7 @@ pub struct Scenario {
     pub description: &'static str,
     /// Builds the scenario into the given engine and returns the id of the
     /// primary light to inspect.
-    pub build: fn(&mut LightingEngine) -> u8,
+    pub build: fn(&mut LightingEngine) -> LightId,
 }
 
 /// All scenarios known to the exploration and regression loops.
@@ -72,14 +72,14 @@ pub fn find(name: &str) -> Option<&'static Scenario> {
 // ----- scenario definitions ---------------------------------------------
 
 /// Single rainbow light, no walls, no objects.
-pub fn single_light(engine: &mut LightingEngine) -> u8 {
+pub fn single_light(engine: &mut LightingEngine) -> LightId {
     engine.update_or_add_light(1, 5, 90, 90);
     1
 }
 
 /// One light with a vertical line of object cells to the east — should
 /// cast a shadow on the east side of the canvas.
-pub fn object_shadow(engine: &mut LightingEngine) -> u8 {
+pub fn object_shadow(engine: &mut LightingEngine) -> LightId {
     let (cx, cy) = (90i16, 90i16);
     // A short vertical wall of object cells, 2 cells east of the light.
     for dy in -3..=3 {
@@ -94,7 +94,7 @@ pub fn object_shadow(engine: &mut LightingEngine) -> u8 {
 ///
 /// This is the regression test for issue #67: walls authored via the
 /// tile-map API must occlude light, not just `set_pixel` Objects.
-pub fn tile_wall_shadow(engine: &mut LightingEngine) -> u8 {
+pub fn tile_wall_shadow(engine: &mut LightingEngine) -> LightId {
     let tpr = engine.tiles_per_row();
     let cpt = engine.cells_per_tile();
     // West half = room "1"; east half = room "2"; the boundary between them
@@ -117,7 +117,7 @@ pub fn tile_wall_shadow(engine: &mut LightingEngine) -> u8 {
 }
 
 /// Light surrounded on all four sides by object cells at distance 2.
-pub fn object_wall(engine: &mut LightingEngine) -> u8 {
+pub fn object_wall(engine: &mut LightingEngine) -> LightId {
     let (cx, cy) = (90i16, 90i16);
     let r = 2i16;
     for d in -r..=r {
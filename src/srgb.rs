@@ -0,0 +1,80 @@
+//! sRGB ⇄ linear-light conversion through lookup tables.
+//!
+//! Colours are authored and displayed as gamma-encoded sRGB bytes, but light
+//! adds up linearly: blending encoded bytes darkens and muddies overlaps.
+//! [`decode`] lifts a byte into linear light in [`Hdr`] fixed point
+//! (`255` → [`HDR_ONE`]) and [`encode`] maps it back. Both are table lookups,
+//! and the tables hold only integers — the decode table is baked in below and
+//! the encode table is derived from it by integer comparison — so the
+//! conversion is identical on every platform.
+//!
+//! [`Hdr`]: crate::lighting::Hdr
+
+use once_cell::sync::Lazy;
+
+use crate::lighting::HDR_ONE;
+
+/// Linear light of every sRGB byte, in `HDR_ONE` fixed point: the IEC 61966-2-1
+/// curve `((c + 0.055) / 1.055)^2.4` (linear below `0.04045`), rounded.
+const DECODE: [u16; 256] = [
+    0, 1, 2, 4, 5, 6, 7, 9, 10, 11, 12, 14, 15, 16, 18, 19, 21, 23, 25, 27, 29, 31, 33, 35, 37, 40,
+    42, 45, 47, 50, 53, 56, 59, 62, 65, 69, 72, 75, 79, 83, 87, 90, 94, 99, 103, 107, 111, 116,
+    121, 125, 130, 135, 140, 145, 151, 156, 161, 167, 173, 178, 184, 190, 197, 203, 209, 216, 222,
+    229, 236, 243, 250, 257, 264, 272, 279, 287, 295, 303, 311, 319, 327, 336, 344, 353, 362, 371,
+    380, 389, 398, 408, 417, 427, 437, 447, 457, 467, 477, 488, 498, 509, 520, 531, 542, 553, 565,
+    576, 588, 600, 612, 624, 636, 649, 661, 674, 687, 699, 713, 726, 739, 753, 766, 780, 794, 808,
+    822, 837, 851, 866, 881, 896, 911, 926, 941, 957, 973, 989, 1005, 1021, 1037, 1053, 1070, 1087,
+    1104, 1121, 1138, 1155, 1173, 1190, 1208, 1226, 1244, 1263, 1281, 1300, 1318, 1337, 1356, 1376,
+    1395, 1415, 1434, 1454, 1474, 1494, 1515, 1535, 1556, 1577, 1598, 1619, 1640, 1662, 1683, 1705,
+    1727, 1749, 1771, 1794, 1816, 1839, 1862, 1885, 1909, 1932, 1956, 1979, 2003, 2027, 2052, 2076,
+    2101, 2126, 2151, 2176, 2201, 2227, 2252, 2278, 2304, 2330, 2357, 2383, 2410, 2437, 2464, 2491,
+    2518, 2546, 2573, 2601, 2629, 2658, 2686, 2715, 2744, 2773, 2802, 2831, 2860, 2890, 2920, 2950,
+    2980, 3011, 3041, 3072, 3103, 3134, 3165, 3197, 3228, 3260, 3292, 3325, 3357, 3390, 3422, 3455,
+    3488, 3522, 3555, 3589, 3623, 3657, 3691, 3725, 3760, 3795, 3830, 3865, 3900, 3936, 3972, 4008,
+    4044, 4080,
+];
+
+/// For every linear value `0..=HDR_ONE`, the sRGB byte whose decoded value is
+/// nearest (ties go to the brighter byte).
+static ENCODE: Lazy<Vec<u8>> = Lazy::new(|| {
+    let mut table = Vec::with_capacity(HDR_ONE as usize + 1);
+    let mut byte = 0usize;
+    for v in 0..=HDR_ONE as u32 {
+        while byte < 255 && 2 * v >= DECODE[byte] as u32 + DECODE[byte + 1] as u32 {
+            byte += 1;
+        }
+        table.push(byte as u8);
+    }
+    table
+});
+
+/// Linear light of sRGB byte `c`, with `255` decoding to [`HDR_ONE`].
+pub fn decode(c: u8) -> u16 {
+    DECODE[c as usize]
+}
+
+/// sRGB byte for linear value `v`; anything above [`HDR_ONE`] encodes as `255`.
+pub fn encode(v: u16) -> u8 {
+    ENCODE[v.min(HDR_ONE) as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_byte_round_trips() {
+        for c in 0..=255u8 {
+            assert_eq!(encode(decode(c)), c);
+        }
+    }
+
+    #[test]
+    fn mid_grey_is_about_a_fifth_of_full_light() {
+        assert_eq!(decode(0), 0);
+        assert_eq!(decode(128), 881);
+        assert_eq!(decode(255), HDR_ONE);
+        assert_eq!(encode(HDR_ONE / 2), 188);
+        assert_eq!(encode(u16::MAX), 255);
+    }
+}
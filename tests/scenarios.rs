@@ -16,7 +16,7 @@
 //! out of the box. If invoking the test binary directly, set the same env
 //! var manually.
 
-use bresenham_lighting_engine::engine::LightingEngine;
+use bresenham_lighting_engine::engine::{LightId, LightingEngine};
 use bresenham_lighting_engine::lighting::Color;
 use bresenham_lighting_engine::scenarios;
 
@@ -28,7 +28,7 @@ fn brightness(pixel: Color) -> u8 {
 
 /// Build a scenario by name into a fresh engine. Returns the engine and the
 /// id of the primary light.
-fn build(name: &str) -> (LightingEngine, u8) {
+fn build(name: &str) -> (LightingEngine, LightId) {
     let scenario = scenarios::find(name)
         .unwrap_or_else(|| panic!("unknown scenario {:?} — fix the test", name));
     let mut engine = LightingEngine::default();
@@ -37,14 +37,14 @@ fn build(name: &str) -> (LightingEngine, u8) {
 }
 
 /// Render the canvas of `light_id` as an ASCII matrix for panic messages.
-fn matrix(engine: &LightingEngine, light_id: u8) -> String {
+fn matrix(engine: &LightingEngine, light_id: LightId) -> String {
     engine
         .render_canvas_text(light_id)
         .unwrap_or_else(|| String::from("<no canvas>"))
 }
 
 /// Brightness of the cell at the centre of the light's canvas.
-fn center_brightness(engine: &LightingEngine, light_id: u8) -> u8 {
+fn center_brightness(engine: &LightingEngine, light_id: LightId) -> u8 {
     let size = engine
         .light_canvas_size(light_id)
         .expect("light exists");
@@ -53,7 +53,7 @@ fn center_brightness(engine: &LightingEngine, light_id: u8) -> u8 {
 }
 
 /// Average brightness of one half of the canvas (split by column).
-fn half_brightness(engine: &LightingEngine, light_id: u8, half: Half) -> u32 {
+fn half_brightness(engine: &LightingEngine, light_id: LightId, half: Half) -> u32 {
     let size = engine.light_canvas_size(light_id).expect("light exists");
     let canvas = engine.light_canvas(light_id).expect("light exists");
     let (col_start, col_end) = match half {